@@ -0,0 +1,98 @@
+//! CryptoPals challenge 16: CBC bitflipping attacks.
+//!
+//! The server encrypts user data in a cookie and grants admin rights if the cookie contains
+//! `;admin=true;`, which the user data can't contain. This server also happens to be a padding
+//! oracle, so the cookie layout doesn't even have to be known: decrypt it first, then flip the
+//! bits to inject the admin role.
+
+use aes::cipher::{
+    block_padding::{NoPadding, Pkcs7},
+    BlockDecryptMut, BlockEncryptMut, KeyIvInit,
+};
+
+type Aes128CbcEnc = cbc::Encryptor<aes::Aes128>;
+type Aes128CbcDec = cbc::Decryptor<aes::Aes128>;
+
+const KEY: [u8; 16] = *b"YELLOW SUBMARINE";
+const IV: [u8; 16] = *b"0123456789abcdef";
+
+/// Build the encrypted cookie, with the IV prepended
+fn cookie(userdata: &str) -> Vec<u8> {
+    let userdata = userdata.replace(';', "%3B").replace('=', "%3D");
+    let plaintext = format!(
+        "comment1=cooking%20MCs;userdata={userdata};comment2=%20like%20a%20pound%20of%20bacon"
+    );
+    let plaintext = plaintext.as_bytes();
+
+    let mut buf = vec![0u8; (plaintext.len() / 16 + 1) * 16];
+    buf[..plaintext.len()].copy_from_slice(plaintext);
+
+    let ciphertext = Aes128CbcEnc::new(&KEY.into(), &IV.into())
+        .encrypt_padded_mut::<Pkcs7>(&mut buf, plaintext.len())
+        .unwrap();
+
+    [&IV[..], ciphertext].concat()
+}
+
+fn decrypt_cookie(cookie: &[u8]) -> Option<Vec<u8>> {
+    let (iv, ciphertext) = cookie.split_at(16);
+    let mut buf = ciphertext.to_vec();
+
+    Aes128CbcDec::new(&KEY.into(), iv.into())
+        .decrypt_padded_mut::<Pkcs7>(&mut buf)
+        .ok()
+        .map(<[u8]>::to_vec)
+}
+
+/// The server leaks whether the padding is valid
+fn oracle(cookie: &[u8]) -> bool {
+    decrypt_cookie(cookie).is_some()
+}
+
+fn is_admin(cookie: &[u8]) -> bool {
+    decrypt_cookie(cookie)
+        .map(|p| p.windows(12).any(|w| w == b";admin=true;"))
+        .unwrap_or(false)
+}
+
+/// Decrypt a cookie without looking at its padding, to show the result
+fn show(cookie: &[u8]) -> String {
+    let (iv, ciphertext) = cookie.split_at(16);
+    let mut buf = ciphertext.to_vec();
+
+    let plaintext = Aes128CbcDec::new(&KEY.into(), iv.into())
+        .decrypt_padded_mut::<NoPadding>(&mut buf)
+        .unwrap();
+
+    String::from_utf8_lossy(plaintext).into_owned()
+}
+
+fn main() {
+    // Two blocks of user data, so one of them is fully aligned whatever the prefix length
+    let userdata = "A".repeat(32);
+    let cookie = cookie(&userdata);
+
+    // The decryption side: find out where the user data lies in the cookie
+    let plaintext = padding_oracle::decrypt(&cookie, 16, oracle).unwrap();
+    let offset = plaintext
+        .windows(12)
+        .position(|w| w == &userdata.as_bytes()[..12])
+        .unwrap();
+
+    // The tampering side: inject the admin role in the second block of user data, at the cost of
+    // garbling the first one
+    let offset = offset + 16 + (16 - offset % 16) % 16;
+    let forged = padding_oracle::flip_plaintext(
+        &cookie,
+        16,
+        offset,
+        &userdata.as_bytes()[..12],
+        b";admin=true;",
+    )
+    .unwrap();
+
+    println!("{}", show(&forged));
+    assert!(!is_admin(&cookie));
+    assert!(is_admin(&forged));
+    println!("Logged in as admin!");
+}
@@ -0,0 +1,42 @@
+//! CryptoPals challenge 17: the CBC padding oracle.
+//!
+//! The server hands out an encrypted token and tells whether a ciphertext is correctly padded,
+//! which is enough to decrypt the token.
+
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+
+type Aes128CbcEnc = cbc::Encryptor<aes::Aes128>;
+type Aes128CbcDec = cbc::Decryptor<aes::Aes128>;
+
+const KEY: [u8; 16] = *b"YELLOW SUBMARINE";
+const IV: [u8; 16] = *b"0123456789abcdef";
+
+/// The token handed out by the server, with the IV prepended
+fn token() -> Vec<u8> {
+    let plaintext = b"000001With the bass kicked in and the Vega's are pumpin'";
+    let mut buf = vec![0u8; (plaintext.len() / 16 + 1) * 16];
+
+    buf[..plaintext.len()].copy_from_slice(plaintext);
+
+    let ciphertext = Aes128CbcEnc::new(&KEY.into(), &IV.into())
+        .encrypt_padded_mut::<Pkcs7>(&mut buf, plaintext.len())
+        .unwrap();
+
+    [&IV[..], ciphertext].concat()
+}
+
+/// Whether the server accepts the padding of a token
+fn oracle(token: &[u8]) -> bool {
+    let (iv, ciphertext) = token.split_at(16);
+    let mut buf = ciphertext.to_vec();
+
+    Aes128CbcDec::new(&KEY.into(), iv.into())
+        .decrypt_padded_mut::<Pkcs7>(&mut buf)
+        .is_ok()
+}
+
+fn main() {
+    let plaintext = padding_oracle::decrypt(&token(), 16, oracle).unwrap();
+
+    println!("{}", String::from_utf8_lossy(&plaintext));
+}
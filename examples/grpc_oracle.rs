@@ -0,0 +1,101 @@
+//! Using a gRPC service as the oracle.
+//!
+//! The `proto` module below stands in for the code `tonic-build` generates for this service
+//! definition, so the example runs without a gRPC toolchain:
+//!
+//! ```proto
+//! service Session {
+//!   rpc Resume (ResumeRequest) returns (ResumeReply);
+//! }
+//!
+//! message ResumeRequest { bytes token = 1; }
+//! message ResumeReply { Code code = 1; }
+//! ```
+//!
+//! With the real generated client, the oracle looks exactly the same: tonic clients are cheap to
+//! clone, so each probe gets its own handle to move into the future, and the response is
+//! classified based on one of its fields. An RPC failure (`Err(Status)`) is not an answer about
+//! the padding, so it is reported as an invalid padding here.
+
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+
+const KEY: [u8; 16] = *b"YELLOW SUBMARINE";
+
+mod proto {
+    use super::*;
+
+    pub struct ResumeRequest {
+        pub token: Vec<u8>,
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum Code {
+        BadPadding,
+        Expired,
+    }
+
+    pub struct ResumeReply {
+        pub code: Code,
+    }
+
+    #[derive(Debug)]
+    pub struct Status;
+
+    /// A stand-in for `session_client::SessionClient<tonic::transport::Channel>`
+    #[derive(Clone)]
+    pub struct SessionClient;
+
+    impl SessionClient {
+        pub async fn resume(&mut self, request: ResumeRequest) -> Result<ResumeReply, Status> {
+            // The server decrypts the token and leaks whether the padding is valid
+            let (iv, ciphertext) = request.token.split_at(16);
+            let mut buf = ciphertext.to_vec();
+
+            let code = match cbc::Decryptor::<aes::Aes128>::new(&KEY.into(), iv.into())
+                .decrypt_padded_mut::<Pkcs7>(&mut buf)
+            {
+                Ok(_) => Code::Expired,
+                Err(_) => Code::BadPadding,
+            };
+
+            Ok(ResumeReply { code })
+        }
+    }
+}
+
+use proto::{Code, ResumeRequest, SessionClient};
+
+fn session_token() -> Vec<u8> {
+    let iv = [0x42u8; 16];
+    let plaintext = b"{\"user\":\"alice\",\"role\":\"user\"}";
+    let mut buf = vec![0u8; (plaintext.len() / 16 + 1) * 16];
+
+    buf[..plaintext.len()].copy_from_slice(plaintext);
+
+    let ciphertext = cbc::Encryptor::<aes::Aes128>::new(&KEY.into(), &iv.into())
+        .encrypt_padded_mut::<Pkcs7>(&mut buf, plaintext.len())
+        .unwrap();
+
+    [&iv[..], ciphertext].concat()
+}
+
+fn main() {
+    let client = SessionClient;
+    let token = session_token();
+
+    let attack = padding_oracle::decrypt_async(&token, 16, |token| {
+        let mut client = client.clone();
+
+        async move {
+            match client.resume(ResumeRequest { token }).await {
+                Ok(reply) => reply.code != Code::BadPadding,
+                Err(_) => false,
+            }
+        }
+    });
+
+    // Any executor works: this would typically be `#[tokio::main]`
+    let plaintext = futures::executor::block_on(attack).unwrap();
+
+    println!("{}", String::from_utf8_lossy(&plaintext));
+}
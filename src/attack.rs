@@ -0,0 +1,1078 @@
+//! A pull-based driver for the attack.
+//!
+//! [PaddingOracleAttack] doesn't talk to the oracle itself: it hands out the ciphertexts to send
+//! and expects the oracle's answers back. This lets the caller own the whole I/O loop.
+
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::guess::{self, GuessContext};
+use crate::rng::SplitMix64;
+use crate::text::LIKELY_BYTES;
+use crate::{
+    AttackReport, AttackState, AttackStats, BlockOrder, CandidateOrder, DecryptOptions, Error,
+    FinalBlock, Layout, PaddingScheme, Progress, Result, Verification,
+};
+
+/// The attack as a state machine.
+///
+/// Call [next_probe](Self::next_probe) to get the next ciphertext to send to the oracle, feed the
+/// answer back with [submit](Self::submit), and repeat until `next_probe` returns `None`.
+/// Then, call [finish](Self::finish) to get the plaintext.
+///
+/// Like [decrypt](crate::decrypt), this assumes the IV is prepended to the ciphertext.
+///
+/// # Example
+/// ```
+/// # use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+/// # type Aes128CbcEnc = cbc::Encryptor<aes::Aes128>;
+/// # type Aes128CbcDec = cbc::Decryptor<aes::Aes128>;
+/// # const KEY: [u8; 16] = [0u8; 16];
+/// # const IV: [u8; 16] = [0u8; 16];
+/// # fn oracle(ciphertext: &[u8]) -> bool {
+/// #     let mut buf = ciphertext.to_vec();
+/// #     Aes128CbcDec::new(&KEY.into(), &IV.into())
+/// #         .decrypt_padded_mut::<Pkcs7>(&mut buf)
+/// #         .is_ok()
+/// # }
+/// # let plaintext = b"000000Now that the party is jumping";
+/// # let mut ciphertext = vec![0u8; (plaintext.len() / 16 + 1) * 16];
+/// # ciphertext[..plaintext.len()].copy_from_slice(plaintext);
+/// # let ciphertext = Aes128CbcEnc::new(&KEY.into(), &IV.into())
+/// #    .encrypt_padded_mut::<Pkcs7>(&mut ciphertext, plaintext.len())
+/// #    .unwrap();
+/// # let mut iv = IV.to_vec();
+/// # iv.extend_from_slice(ciphertext);
+/// # let ciphertext = iv;
+/// use padding_oracle::PaddingOracleAttack;
+///
+/// let mut attack = PaddingOracleAttack::new(&ciphertext, 16).unwrap();
+///
+/// while let Some(probe) = attack.next_probe() {
+///     // Send the probe any way you like
+///     let result = oracle(&probe);
+///
+///     attack.submit(result);
+/// }
+///
+/// let plaintext = attack.finish().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct PaddingOracleAttack {
+    ciphertext: Vec<u8>,
+    blocksize: usize,
+    options: DecryptOptions,
+
+    /// Blocks to recover, as indices in the ciphertext
+    selected: Range<usize>,
+
+    /// Blocks left to attack. The next one is at the end.
+    pending: Vec<usize>,
+
+    /// Block currently attacked, as an index in the ciphertext
+    block: Option<usize>,
+
+    /// Padding length currently forged
+    pad: usize,
+
+    /// Position in the candidate order of the value currently tried for the byte being recovered
+    candidate: usize,
+
+    /// Plaintext values to guess for the byte being recovered, unless they are tried in sequence
+    guesses: Vec<u8>,
+
+    /// Whether the last valid candidate is currently being double checked
+    verifying: bool,
+
+    /// Valid candidates for the byte being recovered which were rejected by their double check
+    rejected: usize,
+
+    /// Whether the recovered block is currently being confirmed
+    confirming: bool,
+
+    /// Intermediate decryption state of every ciphertext block, excluding the IV
+    intermediates: Vec<u8>,
+
+    /// Whether each ciphertext block, excluding the IV, has been fully recovered
+    recovered: Vec<bool>,
+
+    stats: AttackStats,
+    error: Option<Error>,
+
+    #[cfg(feature = "std")]
+    started: std::time::Instant,
+
+    #[cfg(feature = "std")]
+    throughput: crate::progress::Throughput,
+}
+
+impl PaddingOracleAttack {
+    /// Prepare the attack of a ciphertext, with the IV prepended.
+    pub fn new(ciphertext: &[u8], blocksize: usize) -> Result<Self> {
+        Self::with_options(ciphertext, blocksize, &DecryptOptions::default())
+    }
+
+    /// Prepare the attack of a ciphertext whose IV is known but not prepended, as when it is
+    /// transmitted separately.
+    ///
+    /// The ciphertext the attack works on is the IV followed by `ciphertext`, so every block
+    /// index still counts the IV as block `0`.
+    pub fn with_iv(
+        iv: &[u8],
+        ciphertext: &[u8],
+        blocksize: usize,
+        options: &DecryptOptions,
+    ) -> Result<Self> {
+        Self::with_options(
+            &crate::records::with_iv(iv, ciphertext, blocksize)?,
+            blocksize,
+            options,
+        )
+    }
+
+    /// Pick up an attack from a [checkpoint](Self::checkpoint).
+    ///
+    /// The options don't have to be the same as the original ones, although the
+    /// [block order](DecryptOptions::block_order), the selected [blocks](DecryptOptions::blocks)
+    /// and the [final block](DecryptOptions::final_block) are already decided. Keep the same
+    /// [candidate order](DecryptOptions::candidate_order) as well, since the snapshot holds the
+    /// position in it. Fails with
+    /// [Error::InvalidCheckpoint] if the snapshot is inconsistent.
+    pub fn resume(state: AttackState, options: &DecryptOptions) -> Result<Self> {
+        let bs = state.blocksize;
+        let blocks = state.ciphertext.len().checked_div(bs).unwrap_or(0);
+        let in_range = |block: &usize| (1..blocks).contains(block);
+
+        let consistent = bs > 0
+            && state.ciphertext.len().is_multiple_of(bs)
+            && state.intermediates.len() == blocks.saturating_sub(1) * bs
+            && state.recovered.len() == blocks.saturating_sub(1)
+            && state.stats.block_queries.len() == blocks.saturating_sub(1)
+            && state.selected.start > 0
+            && state.selected.start <= state.selected.end
+            && state.selected.end <= blocks.max(1)
+            && state.pending.iter().all(in_range)
+            && state.block.as_ref().is_none_or(in_range)
+            && (1..=bs + 1).contains(&state.pad)
+            // A whole block of padding is only forged to confirm the recovered block, and a
+            // candidate is double checked by flipping the byte before it
+            && (state.pad <= bs || state.confirming)
+            && (!state.verifying || state.pad < bs)
+            // Every candidate was tried once the byte is exhausted, which ends the block
+            && (state.candidate < 256 || (state.block.is_none() && state.candidate == 256));
+
+        if !consistent {
+            return Err(Error::InvalidCheckpoint);
+        }
+
+        #[cfg(feature = "std")]
+        let started = std::time::Instant::now();
+
+        let mut attack = Self {
+            ciphertext: state.ciphertext,
+            blocksize: bs,
+            options: options.clone(),
+            selected: state.selected,
+            pending: state.pending,
+            block: state.block,
+            pad: state.pad,
+            candidate: state.candidate,
+            guesses: Vec::new(),
+            rejected: 0,
+            verifying: state.verifying,
+            confirming: state.confirming,
+            intermediates: state.intermediates,
+            recovered: state.recovered,
+            error: None,
+            // Keep counting the time from where it was
+            #[cfg(feature = "std")]
+            started: started.checked_sub(state.stats.duration).unwrap_or(started),
+            #[cfg(feature = "std")]
+            throughput: Default::default(),
+            stats: state.stats,
+        };
+
+        if attack.block.is_some() && attack.pad <= bs {
+            attack.guesses = attack.byte_guesses();
+        }
+
+        Ok(attack)
+    }
+
+    /// A snapshot of the attack, to [resume](Self::resume) it later, or `None` if it failed.
+    pub fn checkpoint(&self) -> Option<AttackState> {
+        if self.error.is_some() {
+            return None;
+        }
+
+        Some(AttackState {
+            ciphertext: self.ciphertext.clone(),
+            blocksize: self.blocksize,
+            selected: self.selected.clone(),
+            pending: self.pending.clone(),
+            block: self.block,
+            pad: self.pad,
+            candidate: self.candidate,
+            verifying: self.verifying,
+            confirming: self.confirming,
+            intermediates: self.intermediates.clone(),
+            recovered: self.recovered.clone(),
+            stats: self.stats.clone(),
+        })
+    }
+
+    /// Same as [new](Self::new), with custom options.
+    pub fn with_options(
+        ciphertext: &[u8],
+        blocksize: usize,
+        options: &DecryptOptions,
+    ) -> Result<Self> {
+        crate::check_blocksize(blocksize)?;
+
+        // Returns if ciphertext length does not align with blocks
+        if !ciphertext.len().is_multiple_of(blocksize) {
+            return Err(Error::WrongSize {
+                blocksize,
+                found: ciphertext.len(),
+            });
+        }
+
+        let blocks = ciphertext.len() / blocksize;
+
+        if let PaddingScheme::Iso10126 = options.padding {
+            return Err(Error::UnsupportedPadding {
+                padding: options.padding,
+            });
+        }
+
+        if let Layout::FixedLength {
+            checked_block: Some(block),
+        } = options.layout
+        {
+            if block == 0 || block >= blocks {
+                return Err(Error::InvalidBlock { block, blocks });
+            }
+        }
+
+        let selected = match &options.blocks {
+            Some(range) if range.start == 0 || range.start > range.end => {
+                return Err(Error::InvalidBlock {
+                    block: range.start,
+                    blocks,
+                })
+            }
+            Some(range) if range.end > blocks => {
+                return Err(Error::InvalidBlock {
+                    block: range.end - 1,
+                    blocks,
+                })
+            }
+            Some(range) => range.clone(),
+            None => 1..blocks.max(1),
+        };
+
+        let mut attack = Self {
+            ciphertext: ciphertext.to_vec(),
+            blocksize,
+            options: options.clone(),
+            selected: selected.clone(),
+            pending: selected.clone().collect(),
+            block: None,
+            pad: 1,
+            candidate: 0,
+            guesses: Vec::new(),
+            rejected: 0,
+            verifying: false,
+            confirming: false,
+            intermediates: alloc::vec![0u8; blocks.saturating_sub(1) * blocksize],
+            recovered: alloc::vec![false; blocks.saturating_sub(1)],
+            stats: AttackStats {
+                block_queries: alloc::vec![0; blocks.saturating_sub(1)],
+                ..Default::default()
+            },
+            error: None,
+            #[cfg(feature = "std")]
+            started: std::time::Instant::now(),
+            #[cfg(feature = "std")]
+            throughput: Default::default(),
+        };
+
+        for (&block, intermediate) in &options.known_intermediates {
+            if block == 0 || block >= blocks {
+                return Err(Error::InvalidBlock { block, blocks });
+            }
+
+            if intermediate.len() != blocksize {
+                return Err(Error::WrongSize {
+                    blocksize,
+                    found: intermediate.len(),
+                });
+            }
+
+            attack.intermediates[(block - 1) * blocksize..block * blocksize]
+                .copy_from_slice(intermediate);
+            attack.recovered[block - 1] = true;
+            attack.pending.retain(|&b| b != block);
+        }
+
+        #[cfg(feature = "std")]
+        if let Some(cache) = &options.intermediate_cache {
+            for block in attack.pending.clone() {
+                let Some(intermediate) = cache.get(attack.ciphertext_block(block)) else {
+                    continue;
+                };
+
+                if intermediate.len() == blocksize {
+                    attack.intermediates[(block - 1) * blocksize..block * blocksize]
+                        .copy_from_slice(&intermediate);
+                    attack.recovered[block - 1] = true;
+                    attack.pending.retain(|&b| b != block);
+                }
+            }
+        }
+
+        if options.check_padding
+            && attack.recovered.last() == Some(&true)
+            && !attack.has_valid_padding(blocks - 1)
+        {
+            return Err(Error::InconsistentPadding);
+        }
+
+        if options.final_block == FinalBlock::AssumePadding
+            && !selected.is_empty()
+            && selected.end == blocks
+        {
+            attack.pending.retain(|&b| b != blocks - 1);
+            attack.fill_padding_block(blocks - 1);
+        }
+
+        match options.block_order {
+            BlockOrder::Backward => {}
+            BlockOrder::Forward => attack.pending.reverse(),
+            BlockOrder::Shuffled { seed } => SplitMix64::new(seed).shuffle(&mut attack.pending),
+        }
+
+        attack.next_block();
+
+        Ok(attack)
+    }
+
+    /// The next ciphertext to submit to the oracle, or `None` if the attack is over.
+    ///
+    /// Calling this multiple times without calling [submit](Self::submit) in between returns the
+    /// same probe.
+    pub fn next_probe(&self) -> Option<Vec<u8>> {
+        let mut probe = Vec::new();
+
+        self.next_probe_into(&mut probe).then_some(probe)
+    }
+
+    /// Same as [next_probe](Self::next_probe), but writes the probe into `probe`, replacing its
+    /// contents, and returns `false` if the attack is over.
+    ///
+    /// Reusing the same buffer for every probe saves allocating one per query.
+    ///
+    /// # Example
+    /// ```
+    /// # fn oracle(_: &[u8]) -> bool { false }
+    /// # let ciphertext = [0u8; 32];
+    /// use padding_oracle::PaddingOracleAttack;
+    ///
+    /// let mut attack = PaddingOracleAttack::new(&ciphertext, 16).unwrap();
+    /// let mut probe = Vec::new();
+    ///
+    /// while attack.next_probe_into(&mut probe) {
+    ///     attack.submit(oracle(&probe));
+    /// }
+    /// ```
+    pub fn next_probe_into(&self, probe: &mut Vec<u8>) -> bool {
+        if self.confirming {
+            return self.confirmation_probe(probe);
+        }
+
+        self.block.is_some()
+            && self.write_probe(self.candidate_value(self.candidate), self.verifying, probe)
+    }
+
+    /// The probe trying a candidate for the byte being recovered
+    pub(crate) fn probe_for(&self, candidate: u8, verifying: bool) -> Option<Vec<u8>> {
+        let mut probe = Vec::new();
+
+        self.write_probe(candidate, verifying, &mut probe)
+            .then_some(probe)
+    }
+
+    /// Write the probe trying a candidate for the byte being recovered
+    fn write_probe(&self, candidate: u8, verifying: bool, probe: &mut Vec<u8>) -> bool {
+        let Some(block) = self.block else {
+            return false;
+        };
+        let bs = self.blocksize;
+
+        let prev = self.probe_base(block, probe);
+        let index = bs - self.pad;
+
+        // Fix remaining bytes of the padding
+        for j in index + 1..bs {
+            probe[prev + j] = self.intermediate(block)[j] ^ self.padding_byte(self.pad, j - index);
+        }
+
+        probe[prev + index] = candidate;
+
+        if verifying {
+            probe[prev + index - 1] = self.flip(probe[prev + index - 1]);
+        }
+
+        true
+    }
+
+    /// Write the probe forging a whole block of padding out of the recovered block
+    fn confirmation_probe(&self, probe: &mut Vec<u8>) -> bool {
+        let Some(block) = self.block else {
+            return false;
+        };
+        let bs = self.blocksize;
+
+        let prev = self.probe_base(block, probe);
+
+        for j in 0..bs {
+            probe[prev + j] = self.intermediate(block)[j] ^ self.padding_byte(bs, j);
+        }
+
+        true
+    }
+
+    /// Write the probe to tamper with, and return the offset of the tampered block in it
+    fn probe_base(&self, block: usize, probe: &mut Vec<u8>) -> usize {
+        let bs = self.blocksize;
+
+        // Position of the attacked block in the probe
+        let position = match self.options.layout {
+            Layout::Truncated => block,
+            Layout::TwoBlocks => 1,
+            Layout::FixedLength { checked_block } => {
+                checked_block.unwrap_or(self.ciphertext.len() / bs - 1)
+            }
+        };
+
+        probe.clear();
+        probe.extend_from_slice(&self.options.prefix);
+
+        match self.options.layout {
+            Layout::Truncated => probe.extend_from_slice(&self.ciphertext[..(block + 1) * bs]),
+            Layout::TwoBlocks => {
+                probe.extend_from_slice(&self.ciphertext[(block - 1) * bs..(block + 1) * bs])
+            }
+            Layout::FixedLength { .. } => probe.extend_from_slice(&self.ciphertext),
+        }
+
+        let prev = self.options.prefix.len() + (position - 1) * bs;
+        probe[prev..prev + 2 * bs]
+            .copy_from_slice(&self.ciphertext[(block - 1) * bs..(block + 1) * bs]);
+
+        probe.extend_from_slice(&self.options.suffix);
+        prev
+    }
+
+    /// Feed back the oracle's answer to the last probe.
+    ///
+    /// `result` is `true` if the oracle reported a valid padding. This does nothing if the
+    /// attack is over.
+    pub fn submit(&mut self, result: bool) {
+        let Some(block) = self.block else {
+            return;
+        };
+
+        self.count_queries(block, 1);
+
+        if self.confirming {
+            self.confirming = false;
+
+            if result {
+                self.complete_block(block);
+            } else {
+                self.recovered[block - 1] = false;
+                self.error = Some(Error::UnconfirmedBlock { block });
+                self.block = None;
+            }
+        } else if self.verifying {
+            self.verifying = false;
+
+            if result {
+                self.accept(self.candidate_value(self.candidate));
+            } else {
+                self.stats.false_positives += 1;
+                self.rejected += 1;
+                self.next_candidate();
+            }
+        } else if result {
+            if self.needs_verification() {
+                self.verifying = true;
+            } else {
+                self.accept(self.candidate_value(self.candidate));
+            }
+        } else {
+            self.next_candidate();
+        }
+
+        self.check_limits();
+    }
+
+    /// Whether a valid candidate for the current byte must be double checked
+    pub(crate) fn needs_verification(&self) -> bool {
+        // Make sure this is the padding we're looking for
+        // See https://crypto.stackexchange.com/questions/40800/is-the-padding-oracle-attack-deterministic
+        let ambiguous = match self.options.verification {
+            Verification::Always => true,
+            Verification::Minimal => self.pad == 1 || self.options.padding.is_ambiguous(),
+        };
+
+        self.pad != self.blocksize && ambiguous
+    }
+
+    /// Whether the attack is looking for the value of a byte, rather than double checking
+    pub(crate) fn is_searching(&self) -> bool {
+        self.block.is_some() && !self.verifying && !self.confirming
+    }
+
+    /// The candidates left to try for the current byte
+    pub(crate) fn remaining_candidates(&self) -> Vec<u8> {
+        (self.candidate..=255)
+            .map(|i| self.candidate_value(i))
+            .filter(|&c| self.options.allowed_bytes.is_none_or(|a| a.contains(c)))
+            .collect()
+    }
+
+    /// Record the outcome of trying the remaining candidates out of order, with `queries` answers
+    /// received and `false_positives` candidates rejected by their double check
+    pub(crate) fn resolve(&mut self, found: Option<u8>, queries: usize, false_positives: usize) {
+        let Some(block) = self.block else {
+            return;
+        };
+
+        self.count_queries(block, queries);
+        self.stats.false_positives += false_positives;
+        self.rejected += false_positives;
+
+        match found {
+            Some(candidate) => self.accept(candidate),
+            None => {
+                self.candidate = 256;
+                self.skip_disallowed();
+            }
+        }
+
+        self.check_limits();
+    }
+
+    /// Stop the attack once the driver ran out of queries or time before resolving the current
+    /// byte, with `queries` answers received and `false_positives` candidates rejected by their
+    /// double check
+    pub(crate) fn interrupt(&mut self, queries: usize, false_positives: usize) {
+        let Some(block) = self.block else {
+            return;
+        };
+
+        self.count_queries(block, queries);
+        self.stats.false_positives += false_positives;
+        self.rejected += false_positives;
+
+        self.check_limits();
+    }
+
+    /// How many more queries the limits allow, on top of `pending` answers not submitted yet:
+    /// `None` without a query budget, and `Some(0)` once the attack is out of queries or time
+    pub(crate) fn queries_left(&self, pending: usize) -> Option<usize> {
+        #[cfg(feature = "std")]
+        if self
+            .options
+            .timeout
+            .is_some_and(|timeout| self.started.elapsed() >= timeout)
+        {
+            return Some(0);
+        }
+
+        self.options
+            .max_queries
+            .map(|max| max.saturating_sub(self.stats.queries + pending))
+    }
+
+    /// Whether the attack is over, either because it succeeded or because it failed.
+    pub fn is_finished(&self) -> bool {
+        self.block.is_none()
+    }
+
+    /// The number of oracle answers submitted so far.
+    pub fn queries(&self) -> usize {
+        self.stats.queries
+    }
+
+    /// The blocksize of the attacked cipher.
+    pub fn blocksize(&self) -> usize {
+        self.blocksize
+    }
+
+    /// The intermediate decryption state of a ciphertext block, counting the IV as block `0`,
+    /// if it was fully recovered.
+    ///
+    /// This is the block decrypted by the cipher, before being xored with the previous block.
+    pub fn intermediate_block(&self, block: usize) -> Option<&[u8]> {
+        (block > 0 && *self.recovered.get(block - 1)?).then(|| self.intermediate(block))
+    }
+
+    /// The plaintext of a ciphertext block, counting the IV as block `0`, if it was fully
+    /// recovered.
+    pub fn plaintext_block(&self, block: usize) -> Option<Vec<u8>> {
+        let bs = self.blocksize;
+        let intermediate = self.intermediate_block(block)?;
+
+        Some(
+            intermediate
+                .iter()
+                .zip(&self.ciphertext[(block - 1) * bs..block * bs])
+                .map(|(i, c)| i ^ c)
+                .collect(),
+        )
+    }
+
+    /// Only attack `block`, counting the IV as block `0`
+    #[cfg(feature = "rayon")]
+    pub(crate) fn focus(&mut self, block: usize) {
+        self.pending.clear();
+        self.pending.push(block);
+        self.next_block();
+    }
+
+    /// The blocks to recover, counting the IV as block `0`
+    #[cfg(feature = "rayon")]
+    pub(crate) fn selected_blocks(&self) -> Range<usize> {
+        self.selected.clone()
+    }
+
+    /// The block currently attacked
+    pub(crate) fn current_block(&self) -> Option<usize> {
+        self.block
+    }
+
+    /// The block currently attacked, and the index in it of the byte being recovered
+    pub(crate) fn position(&self) -> Option<(usize, usize)> {
+        Some((self.block?, self.blocksize - self.pad.min(self.blocksize)))
+    }
+
+    pub(crate) fn ciphertext(&self) -> &[u8] {
+        &self.ciphertext
+    }
+
+    /// The blocks left to attack, the current one included
+    pub(crate) fn remaining_blocks(&self) -> impl Iterator<Item = usize> + '_ {
+        self.block
+            .into_iter()
+            .chain(self.pending.iter().rev().copied())
+    }
+
+    /// The options the attack was created with.
+    pub fn options(&self) -> &DecryptOptions {
+        &self.options
+    }
+
+    /// Statistics about the attack so far.
+    pub fn stats(&self) -> &AttackStats {
+        &self.stats
+    }
+
+    /// Summarize the attack so far, whether it succeeded or not.
+    pub fn report(&self) -> AttackReport {
+        let plaintext = self.recovered_plaintext();
+
+        AttackReport {
+            blocksize: self.blocksize,
+            #[cfg(feature = "std")]
+            entropy: plaintext
+                .chunks(self.blocksize)
+                .map(crate::util::entropy)
+                .collect(),
+            plaintext,
+            stats: self.stats.clone(),
+            error: self.error.as_ref().map(|e| e.to_string()),
+        }
+    }
+
+    /// Consume the attack and return the plaintext.
+    ///
+    /// Returns [Error::Unfinished] if the attack isn't over yet.
+    pub fn finish(self) -> Result<Vec<u8>> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        if !self.is_finished() {
+            return Err(Error::Unfinished);
+        }
+
+        Ok(self.recovered_plaintext())
+    }
+
+    /// Same as [finish](Self::finish), but writes the plaintext at the start of `output`, and
+    /// returns its length.
+    ///
+    /// Returns [Error::LengthMismatch] if `output` is too short for the plaintext.
+    pub fn finish_into(self, output: &mut [u8]) -> Result<usize> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        if !self.is_finished() {
+            return Err(Error::Unfinished);
+        }
+
+        let range = self.recovered_range();
+        let Some(output) = output.get_mut(..range.len()) else {
+            return Err(Error::LengthMismatch {
+                expected: range.len(),
+                found: output.len(),
+            });
+        };
+
+        for ((o, i), c) in output
+            .iter_mut()
+            .zip(&self.intermediates[range.clone()])
+            .zip(&self.ciphertext[range.clone()])
+        {
+            *o = i ^ c;
+        }
+
+        Ok(range.len())
+    }
+
+    /// The selected blocks that are fully recovered, counting the IV as block `0`, with their
+    /// plaintext, in the order of the ciphertext
+    pub(crate) fn recovered_blocks(&self) -> Vec<(usize, Vec<u8>)> {
+        self.selected
+            .clone()
+            .filter_map(|block| Some((block, self.plaintext_block(block)?)))
+            .collect()
+    }
+
+    /// Plaintext of the trailing selected blocks that are fully recovered
+    pub(crate) fn recovered_plaintext(&self) -> Vec<u8> {
+        let range = self.recovered_range();
+
+        self.intermediates[range.clone()]
+            .iter()
+            .zip(&self.ciphertext[range])
+            .map(|(i, c)| i ^ c)
+            .collect()
+    }
+
+    /// Where the plaintext of the trailing selected blocks that are fully recovered is, in the
+    /// intermediate state
+    fn recovered_range(&self) -> Range<usize> {
+        let bs = self.blocksize;
+        let Range { start, end } = self.selected;
+
+        let trailing = self.recovered[start - 1..end - 1]
+            .iter()
+            .rev()
+            .take_while(|r| **r)
+            .count();
+
+        (end - 1 - trailing) * bs..(end - 1) * bs
+    }
+
+    fn intermediate(&self, block: usize) -> &[u8] {
+        &self.intermediates[(block - 1) * self.blocksize..block * self.blocksize]
+    }
+
+    #[cfg(feature = "std")]
+    fn ciphertext_block(&self, block: usize) -> &[u8] {
+        &self.ciphertext[block * self.blocksize..(block + 1) * self.blocksize]
+    }
+
+    fn has_valid_padding(&self, block: usize) -> bool {
+        self.plaintext_block(block)
+            .is_some_and(|plaintext| self.options.padding.is_valid(&plaintext))
+    }
+
+    /// The value of the byte at `offset` in a padding of `length` bytes
+    fn padding_byte(&self, length: usize, offset: usize) -> u8 {
+        self.options.padding.byte(length, offset)
+    }
+
+    fn count_queries(&mut self, block: usize, queries: usize) {
+        self.stats.queries += queries;
+        self.stats.block_queries[block - 1] += queries;
+
+        #[cfg(feature = "std")]
+        {
+            self.stats.duration = self.started.elapsed();
+        }
+    }
+
+    /// Stop the attack if it ran out of queries or time
+    fn check_limits(&mut self) {
+        if self.block.is_none() {
+            return;
+        }
+
+        if self
+            .options
+            .max_queries
+            .is_some_and(|max| self.stats.queries >= max)
+        {
+            self.error = Some(Error::QueryBudgetExceeded {
+                partial: self.recovered_blocks(),
+                stats: self.stats.clone(),
+            });
+            self.block = None;
+            return;
+        }
+
+        #[cfg(feature = "std")]
+        if self
+            .options
+            .timeout
+            .is_some_and(|timeout| self.stats.duration >= timeout)
+        {
+            self.error = Some(Error::TimedOut {
+                partial: self.recovered_blocks(),
+                stats: self.stats.clone(),
+            });
+            self.block = None;
+        }
+    }
+
+    fn next_candidate(&mut self) {
+        self.candidate += 1;
+        self.skip_disallowed();
+    }
+
+    /// Move to the next allowed candidate, including the current one
+    fn skip_disallowed(&mut self) {
+        if let Some(allowed) = &self.options.allowed_bytes {
+            while self.candidate <= 255 && !allowed.contains(self.candidate_value(self.candidate)) {
+                self.candidate += 1;
+            }
+        }
+
+        if self.candidate > 255 {
+            self.error = Some(Error::InvalidPadding {
+                block: self.block.expect("no block is being attacked"),
+                byte: self.blocksize - self.pad,
+                queries: self.stats.queries,
+                false_positives: self.rejected,
+            });
+            self.block = None;
+        }
+    }
+
+    /// The value at position `i` of the candidate order, for the byte being recovered
+    fn candidate_value(&self, i: usize) -> u8 {
+        if self.guesses.is_empty() {
+            return i as u8;
+        }
+
+        // The value forging the padding if the plaintext byte is the guessed one
+        self.guesses[i] ^ self.ciphertext[self.byte_index()] ^ self.padding_byte(self.pad, 0)
+    }
+
+    /// Index of the byte being recovered in the intermediate state, and of the byte of the
+    /// previous block it is xored with in the ciphertext
+    fn byte_index(&self) -> usize {
+        let block = self.block.expect("no block is being attacked");
+        (block - 1) * self.blocksize + self.blocksize - self.pad
+    }
+
+    /// The plaintext values to guess for the byte being recovered, in order, or nothing to
+    /// guess the tampered byte in increasing order
+    fn byte_guesses(&self) -> Vec<u8> {
+        let mut guesses = self.ordered_guesses();
+        let hint = self
+            .options
+            .known_plaintext
+            .get(self.byte_index())
+            .copied()
+            .flatten();
+
+        if self.options.charset.is_none() && hint.is_none() {
+            return guesses;
+        }
+
+        if guesses.is_empty() {
+            guesses = (0..=255).collect();
+        }
+
+        if let Some(charset) = &self.options.charset {
+            // The padding is not part of the plaintext, but ends the last block
+            let block = self.block.expect("no block is being attacked");
+            let likely = |value: u8| {
+                charset.contains(value)
+                    || (block == self.recovered.len()
+                        && (1..=self.blocksize).contains(&(value as usize)))
+            };
+
+            // Stable, so the candidate order still applies within the charset and out of it
+            guesses.sort_by_key(|&value| !likely(value));
+        }
+
+        if let Some(hint) = hint {
+            guesses.sort_by_key(|&value| value != hint);
+        }
+
+        guesses
+    }
+
+    /// The plaintext values to guess following the candidate order, ignoring the charset
+    fn ordered_guesses(&self) -> Vec<u8> {
+        match &self.options.candidate_order {
+            CandidateOrder::Sequential => Vec::new(),
+            CandidateOrder::LikelyText => LIKELY_BYTES.to_vec(),
+            CandidateOrder::Custom(strategy) => {
+                let block = self.block.expect("no block is being attacked");
+                let index = self.byte_index();
+                let end = block * self.blocksize;
+
+                let following: Vec<u8> = (index + 1..end)
+                    .map(|j| self.intermediates[j] ^ self.ciphertext[j])
+                    .collect();
+
+                guess::complete(strategy.guesses(&GuessContext {
+                    block,
+                    byte: self.blocksize - self.pad,
+                    blocksize: self.blocksize,
+                    last_block: block == self.recovered.len(),
+                    following: &following,
+                }))
+            }
+        }
+    }
+
+    /// Start recovering the next byte of the block
+    fn start_byte(&mut self) {
+        self.candidate = 0;
+        self.rejected = 0;
+        self.guesses = self.byte_guesses();
+        self.skip_disallowed();
+    }
+
+    /// Record `candidate` as the value forging the padding for the byte being recovered
+    fn accept(&mut self, candidate: u8) {
+        let block = self.block.expect("no block is being attacked");
+        let index = (block - 1) * self.blocksize + self.blocksize - self.pad;
+
+        self.intermediates[index] = candidate ^ self.padding_byte(self.pad, 0);
+
+        if self.options.progress.is_some() {
+            self.report_progress(block, index);
+        }
+
+        self.pad += 1;
+        self.candidate = 0;
+
+        // A last byte equal to the blocksize means the whole block is padding
+        if self.options.final_block == FinalBlock::DetectPadding
+            && block == self.recovered.len()
+            && self.pad == 2
+            && self.intermediates[index] ^ self.ciphertext[index] == self.blocksize as u8
+            && self.padding_byte(self.blocksize, self.blocksize - 1) == self.blocksize as u8
+        {
+            self.fill_padding_block(block);
+            self.pad = self.blocksize + 1;
+        }
+
+        if self.pad > self.blocksize && self.options.confirm_blocks {
+            self.confirming = true;
+        } else if self.pad > self.blocksize {
+            self.complete_block(block);
+        } else {
+            self.start_byte();
+        }
+    }
+
+    /// Report the byte at `index` in the intermediate state as recovered
+    fn report_progress(&mut self, block: usize, index: usize) {
+        let bs = self.blocksize;
+        let done = self
+            .selected
+            .clone()
+            .filter(|&b| b != block && self.recovered[b - 1])
+            .count();
+        let recovered = done * bs + self.pad;
+        let total = (done + self.remaining_blocks().count()) * bs;
+
+        #[cfg(feature = "std")]
+        let (queries_per_second, eta) =
+            self.throughput.sample(self.stats.queries, recovered, total);
+        #[cfg(not(feature = "std"))]
+        let (queries_per_second, eta) = (None, None);
+
+        let progress = self
+            .options
+            .progress
+            .as_ref()
+            .expect("no progress callback");
+        progress.report(Progress {
+            block,
+            byte: bs - self.pad,
+            value: self.intermediates[index] ^ self.ciphertext[index],
+            queries: self.stats.queries,
+            recovered,
+            total,
+            queries_per_second,
+            eta,
+        });
+    }
+
+    /// A different value for a byte, allowed if possible
+    fn flip(&self, byte: u8) -> u8 {
+        match &self.options.allowed_bytes {
+            Some(allowed) if !allowed.contains(!byte) => {
+                allowed.iter().find(|&b| b != byte).unwrap_or(!byte)
+            }
+            _ => !byte,
+        }
+    }
+
+    /// Fill a block with padding, without querying the oracle
+    fn fill_padding_block(&mut self, block: usize) {
+        let bs = self.blocksize;
+
+        for j in 0..bs {
+            let index = (block - 1) * bs + j;
+            self.intermediates[index] = self.ciphertext[index] ^ self.padding_byte(bs, j);
+        }
+
+        self.recovered[block - 1] = true;
+    }
+
+    fn complete_block(&mut self, block: usize) {
+        self.recovered[block - 1] = true;
+
+        if self.options.check_padding
+            && block == self.recovered.len()
+            && !self.has_valid_padding(block)
+        {
+            self.error = Some(Error::InconsistentPadding);
+            self.block = None;
+            return;
+        }
+
+        // Only once the block passed every check, since later attacks trust the cache
+        #[cfg(feature = "std")]
+        if let Some(cache) = &self.options.intermediate_cache {
+            cache.insert(self.ciphertext_block(block), self.intermediate(block));
+        }
+
+        self.next_block();
+    }
+
+    fn next_block(&mut self) {
+        self.block = self.pending.pop();
+        self.pad = 1;
+        self.candidate = 0;
+
+        if self.block.is_some() {
+            self.start_byte();
+        }
+    }
+}
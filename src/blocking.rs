@@ -0,0 +1,34 @@
+//! Run the attack against an asynchronous oracle from synchronous code.
+//!
+//! Unlike [decrypt_async](crate::decrypt_async), this needs to spin up a runtime to drive the
+//! oracle, so each runtime has its own feature flag.
+
+use alloc::vec::Vec;
+
+use crate::{decrypt_async, AsyncPaddingOracle, Result};
+
+/// Run [decrypt_async](crate::decrypt_async) to completion on a new single-threaded tokio
+/// runtime.
+///
+/// This must not be called from within a tokio runtime.
+#[cfg(feature = "tokio")]
+pub fn decrypt_tokio(
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: impl AsyncPaddingOracle,
+) -> Result<Vec<u8>> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(decrypt_async(ciphertext, blocksize, oracle))
+}
+
+/// Run [decrypt_async](crate::decrypt_async) to completion with smol.
+#[cfg(feature = "smol")]
+pub fn decrypt_smol(
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: impl AsyncPaddingOracle,
+) -> Result<Vec<u8>> {
+    smol::block_on(decrypt_async(ciphertext, blocksize, oracle))
+}
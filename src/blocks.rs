@@ -0,0 +1,150 @@
+//! Consume the plaintext as it is recovered.
+
+use alloc::vec::Vec;
+
+use crate::verify;
+use crate::{BlockOrder, DecryptOptions, PaddingOracle, PaddingOracleAttack, Result};
+
+/// An iterator over the plaintext blocks, as they are recovered.
+///
+/// Get one from [PaddingOracleAttack::into_blocks].
+#[derive(Debug)]
+pub struct Blocks<O> {
+    attack: Option<PaddingOracleAttack>,
+    oracle: O,
+
+    /// Whether each ciphertext block, excluding the IV, was already yielded
+    yielded: Vec<bool>,
+
+    /// The buffer of the probes
+    probe: Vec<u8>,
+}
+
+/// Same as [decrypt](crate::decrypt), but lazily: yields each plaintext block once it is
+/// recovered, from the first to the last, along with its index in the ciphertext.
+///
+/// This shows the secret as it comes, and stopping the iteration stops the attack, such as once
+/// the interesting part of the plaintext is known. See [PaddingOracleAttack::into_blocks] to
+/// use custom options.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 48];
+/// for block in padding_oracle::decrypt_iter(&ciphertext, 16, oracle).unwrap() {
+///     let Ok((_, plaintext)) = block else {
+///         break;
+///     };
+///
+///     print!("{}", String::from_utf8_lossy(&plaintext));
+///
+///     if plaintext.contains(&b';') {
+///         break;
+///     }
+/// }
+/// ```
+pub fn decrypt_iter<O: PaddingOracle>(
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: O,
+) -> Result<Blocks<O>> {
+    let options = DecryptOptions {
+        block_order: BlockOrder::Forward,
+        ..Default::default()
+    };
+
+    Ok(PaddingOracleAttack::with_options(ciphertext, blocksize, &options)?.into_blocks(oracle))
+}
+
+impl PaddingOracleAttack {
+    /// Run the attack lazily, yielding each plaintext block once it is recovered, along with its
+    /// index in the ciphertext.
+    ///
+    /// Blocks come in the order they are attacked, use [BlockOrder::Forward](crate::BlockOrder::Forward)
+    /// to get the plaintext in order. Blocks filled without querying the oracle, such as with
+    /// [known intermediates](crate::DecryptOptions::known_intermediates) or
+    /// [FinalBlock::AssumePadding](crate::FinalBlock::AssumePadding), come right before the first
+    /// block attacked after them in the ciphertext, or once the attack is over. If the attack
+    /// fails, the error is the last item.
+    ///
+    /// # Example
+    /// ```
+    /// # fn oracle(_: &[u8]) -> bool { false }
+    /// # let ciphertext = [0u8; 48];
+    /// use padding_oracle::{BlockOrder, DecryptOptions, PaddingOracleAttack};
+    ///
+    /// let options = DecryptOptions {
+    ///     block_order: BlockOrder::Forward,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let attack = PaddingOracleAttack::with_options(&ciphertext, 16, &options).unwrap();
+    ///
+    /// for block in attack.into_blocks(oracle) {
+    ///     match block {
+    ///         Ok((index, plaintext)) => println!("Block {index}: {plaintext:?}"),
+    ///         Err(e) => eprintln!("The attack failed: {e}"),
+    ///     }
+    /// }
+    /// ```
+    pub fn into_blocks<O: PaddingOracle>(self, oracle: O) -> Blocks<O> {
+        let blocks = self.ciphertext().len() / self.blocksize();
+
+        Blocks {
+            attack: Some(self),
+            oracle,
+            yielded: alloc::vec![false; blocks.saturating_sub(1)],
+            probe: Vec::new(),
+        }
+    }
+}
+
+impl<O: PaddingOracle> Iterator for Blocks<O> {
+    type Item = Result<(usize, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let attack = self.attack.as_mut()?;
+        let current = attack.current_block();
+
+        // Yield the blocks which were filled without attacking them, before the attacked ones
+        // after them
+        if let Some(i) = (0..self.yielded.len()).find(|&i| {
+            !self.yielded[i]
+                && current.is_none_or(|block| i + 1 < block)
+                && attack.plaintext_block(i + 1).is_some()
+        }) {
+            self.yielded[i] = true;
+            return Some(Ok((i + 1, attack.plaintext_block(i + 1)?)));
+        }
+
+        let Some(block) = current else {
+            return self.attack.take()?.finish().err().map(Err);
+        };
+
+        if verify::needs_precheck(attack) {
+            if let Err(e) = verify::precheck(attack, self.oracle.by_ref()) {
+                self.attack = None;
+                return Some(Err(e));
+            }
+        }
+
+        while attack.current_block() == Some(block) {
+            if !attack.next_probe_into(&mut self.probe) {
+                return None;
+            }
+
+            if let Err(e) = crate::query(attack, &self.probe, &mut self.oracle) {
+                self.attack = None;
+                return Some(Err(e));
+            }
+        }
+
+        match attack.plaintext_block(block) {
+            Some(plaintext) => {
+                self.yielded[block - 1] = true;
+                Some(Ok((block, plaintext)))
+            }
+            None => self.next(),
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! Configure an attack step by step.
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::{
+    BlockOrder, ByteSet, CandidateOrder, DecryptOptions, FinalBlock, GuessStrategy, Layout,
+    PaddingOracle, PaddingOracleAttack, PaddingScheme, Progress, ProgressCallback, Result,
+    Verification,
+};
+
+/// Where the IV of the ciphertexts is.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IvLocation {
+    /// The IV is the first block of the ciphertext.
+    #[default]
+    Prepended,
+
+    /// The IV is known, but sent separately, as with
+    /// [decrypt_with_iv](crate::decrypt_with_iv).
+    Separate(Vec<u8>),
+}
+
+/// A configured attack, ready to decrypt ciphertexts. See [Attack::builder].
+///
+/// The same attack can decrypt any number of ciphertexts.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 32];
+/// use padding_oracle::{Attack, EnglishText, IvLocation, PaddingScheme};
+///
+/// let attack = Attack::builder()
+///     .blocksize(16)
+///     .iv(IvLocation::Prepended)
+///     .padding(PaddingScheme::Pkcs7)
+///     .strategy(EnglishText)
+///     .progress(|progress| eprintln!("block {}, byte {}", progress.block, progress.byte))
+///     .build();
+///
+/// let plaintext = attack.decrypt(&ciphertext, oracle);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Attack {
+    blocksize: usize,
+    iv: IvLocation,
+    options: DecryptOptions,
+}
+
+impl Attack {
+    /// Start configuring an attack, for a blocksize of 16 bytes, the IV prepended and the
+    /// [default options](DecryptOptions).
+    pub fn builder() -> AttackBuilder {
+        AttackBuilder {
+            attack: Self {
+                blocksize: 16,
+                iv: IvLocation::Prepended,
+                options: DecryptOptions::default(),
+            },
+        }
+    }
+
+    /// Decrypt a ciphertext.
+    pub fn decrypt(&self, ciphertext: &[u8], oracle: impl PaddingOracle) -> Result<Vec<u8>> {
+        crate::run(self.prepare(ciphertext)?, oracle)
+    }
+
+    /// Prepare the attack of a ciphertext, to drive it yourself.
+    pub fn prepare(&self, ciphertext: &[u8]) -> Result<PaddingOracleAttack> {
+        match &self.iv {
+            IvLocation::Prepended => {
+                PaddingOracleAttack::with_options(ciphertext, self.blocksize, &self.options)
+            }
+            IvLocation::Separate(iv) => {
+                PaddingOracleAttack::with_iv(iv, ciphertext, self.blocksize, &self.options)
+            }
+        }
+    }
+
+    /// The blocksize.
+    pub fn blocksize(&self) -> usize {
+        self.blocksize
+    }
+
+    /// The options, for the other functions taking [DecryptOptions].
+    pub fn options(&self) -> &DecryptOptions {
+        &self.options
+    }
+}
+
+/// Builds an [Attack], see [Attack::builder].
+///
+/// Each method sets the [DecryptOptions] field of the same name, unless documented otherwise.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct AttackBuilder {
+    attack: Attack,
+}
+
+impl AttackBuilder {
+    /// Start from these options, overriding the ones already set.
+    pub fn options(mut self, options: DecryptOptions) -> Self {
+        self.attack.options = options;
+        self
+    }
+
+    /// The blocksize of the cipher, `16` by default.
+    pub fn blocksize(mut self, blocksize: usize) -> Self {
+        self.attack.blocksize = blocksize;
+        self
+    }
+
+    /// Where the IV is, prepended by default.
+    pub fn iv(mut self, iv: IvLocation) -> Self {
+        self.attack.iv = iv;
+        self
+    }
+
+    /// See [DecryptOptions::layout].
+    pub fn layout(mut self, layout: Layout) -> Self {
+        self.attack.options.layout = layout;
+        self
+    }
+
+    /// See [DecryptOptions::padding].
+    pub fn padding(mut self, padding: PaddingScheme) -> Self {
+        self.attack.options.padding = padding;
+        self
+    }
+
+    /// See [DecryptOptions::check_padding].
+    pub fn check_padding(mut self, check_padding: bool) -> Self {
+        self.attack.options.check_padding = check_padding;
+        self
+    }
+
+    /// See [DecryptOptions::precheck].
+    pub fn precheck(mut self, precheck: bool) -> Self {
+        self.attack.options.precheck = precheck;
+        self
+    }
+
+    /// See [DecryptOptions::final_block].
+    pub fn final_block(mut self, final_block: FinalBlock) -> Self {
+        self.attack.options.final_block = final_block;
+        self
+    }
+
+    /// See [DecryptOptions::prefix].
+    pub fn prefix(mut self, prefix: Vec<u8>) -> Self {
+        self.attack.options.prefix = prefix;
+        self
+    }
+
+    /// See [DecryptOptions::suffix].
+    pub fn suffix(mut self, suffix: Vec<u8>) -> Self {
+        self.attack.options.suffix = suffix;
+        self
+    }
+
+    /// See [DecryptOptions::confirm_blocks].
+    pub fn confirm_blocks(mut self, confirm_blocks: bool) -> Self {
+        self.attack.options.confirm_blocks = confirm_blocks;
+        self
+    }
+
+    /// See [DecryptOptions::verification].
+    pub fn verification(mut self, verification: Verification) -> Self {
+        self.attack.options.verification = verification;
+        self
+    }
+
+    /// See [DecryptOptions::block_order].
+    pub fn block_order(mut self, block_order: BlockOrder) -> Self {
+        self.attack.options.block_order = block_order;
+        self
+    }
+
+    /// See [DecryptOptions::candidate_order].
+    pub fn candidate_order(mut self, candidate_order: CandidateOrder) -> Self {
+        self.attack.options.candidate_order = candidate_order;
+        self
+    }
+
+    /// Guess the bytes with a custom strategy, as with [CandidateOrder::Custom].
+    pub fn strategy(self, strategy: impl GuessStrategy + 'static) -> Self {
+        self.candidate_order(CandidateOrder::Custom(Arc::new(strategy)))
+    }
+
+    /// See [DecryptOptions::charset].
+    pub fn charset(mut self, charset: ByteSet) -> Self {
+        self.attack.options.charset = Some(charset);
+        self
+    }
+
+    /// See [DecryptOptions::known_plaintext].
+    pub fn known_plaintext(mut self, known_plaintext: Vec<Option<u8>>) -> Self {
+        self.attack.options.known_plaintext = known_plaintext;
+        self
+    }
+
+    /// See [DecryptOptions::known_intermediates].
+    pub fn known_intermediates(mut self, known_intermediates: BTreeMap<usize, Vec<u8>>) -> Self {
+        self.attack.options.known_intermediates = known_intermediates;
+        self
+    }
+
+    /// See [DecryptOptions::intermediate_cache].
+    #[cfg(feature = "std")]
+    pub fn intermediate_cache(mut self, cache: Arc<crate::IntermediateCache>) -> Self {
+        self.attack.options.intermediate_cache = Some(cache);
+        self
+    }
+
+    /// See [DecryptOptions::allowed_bytes].
+    pub fn allowed_bytes(mut self, allowed_bytes: ByteSet) -> Self {
+        self.attack.options.allowed_bytes = Some(allowed_bytes);
+        self
+    }
+
+    /// See [DecryptOptions::retries].
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.attack.options.retries = retries;
+        self
+    }
+
+    /// See [DecryptOptions::max_queries].
+    pub fn max_queries(mut self, max_queries: usize) -> Self {
+        self.attack.options.max_queries = Some(max_queries);
+        self
+    }
+
+    /// See [DecryptOptions::rate_limiter].
+    #[cfg(feature = "std")]
+    pub fn rate_limiter(mut self, rate_limiter: Arc<crate::RateLimiter>) -> Self {
+        self.attack.options.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// See [DecryptOptions::timeout].
+    #[cfg(feature = "std")]
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.attack.options.timeout = Some(timeout);
+        self
+    }
+
+    /// See [DecryptOptions::progress].
+    pub fn progress(mut self, progress: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        self.attack.options.progress = Some(ProgressCallback::new(progress));
+        self
+    }
+
+    /// See [DecryptOptions::blocks].
+    pub fn blocks(mut self, blocks: core::ops::Range<usize>) -> Self {
+        self.attack.options.blocks = Some(blocks);
+        self
+    }
+
+    /// Finish configuring the attack.
+    pub fn build(self) -> Attack {
+        self.attack
+    }
+}
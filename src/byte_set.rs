@@ -0,0 +1,67 @@
+//! A set of byte values.
+
+/// A set of byte values, see [DecryptOptions::allowed_bytes](crate::DecryptOptions::allowed_bytes).
+///
+/// # Example
+/// ```
+/// use padding_oracle::ByteSet;
+///
+/// let mut set = ByteSet::all();
+/// set.remove(b'\n');
+///
+/// assert!(set.contains(b'a'));
+/// assert!(!set.contains(b'\n'));
+/// assert_eq!(set.len(), 255);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ByteSet([u64; 4]);
+
+impl ByteSet {
+    /// An empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A set containing every byte value.
+    pub fn all() -> Self {
+        Self([u64::MAX; 4])
+    }
+
+    pub fn insert(&mut self, byte: u8) {
+        self.0[byte as usize / 64] |= 1 << (byte % 64);
+    }
+
+    pub fn remove(&mut self, byte: u8) {
+        self.0[byte as usize / 64] &= !(1 << (byte % 64));
+    }
+
+    pub fn contains(&self, byte: u8) -> bool {
+        self.0[byte as usize / 64] & (1 << (byte % 64)) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The bytes in the set, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..=255).filter(|&b| self.contains(b))
+    }
+}
+
+impl FromIterator<u8> for ByteSet {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        let mut set = Self::new();
+
+        for byte in iter {
+            set.insert(byte);
+        }
+
+        set
+    }
+}
@@ -0,0 +1,74 @@
+//! Reuse the intermediate states recovered from other ciphertexts.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::vec::Vec;
+
+/// The intermediate decryption states of ciphertext blocks, shared between attacks.
+///
+/// Ciphertexts encrypted under the same key, such as many captured cookies, often share blocks,
+/// whose intermediate state only has to be recovered once. Set the cache in
+/// [DecryptOptions::intermediate_cache](crate::DecryptOptions::intermediate_cache), through an
+/// [Arc](std::sync::Arc): blocks found in it are decrypted without querying the oracle, and every
+/// block recovered from the oracle is added to it.
+///
+/// Only share a cache between ciphertexts encrypted under the same key, or the blocks they have
+/// in common decrypt to garbage.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+///
+/// use padding_oracle::{DecryptOptions, IntermediateCache};
+///
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let cookies: Vec<Vec<u8>> = Vec::new();
+/// let options = DecryptOptions {
+///     intermediate_cache: Some(Arc::new(IntermediateCache::new())),
+///     ..Default::default()
+/// };
+///
+/// for cookie in &cookies {
+///     let plaintext = padding_oracle::decrypt_with_options(cookie, 16, oracle, &options);
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct IntermediateCache {
+    blocks: Mutex<HashMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl IntermediateCache {
+    /// An empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The intermediate state of a ciphertext block, if it is known.
+    pub fn get(&self, block: &[u8]) -> Option<Vec<u8>> {
+        self.lock().get(block).cloned()
+    }
+
+    /// Remember the intermediate state of a ciphertext block.
+    pub fn insert(&self, block: &[u8], intermediate: &[u8]) {
+        self.lock().insert(block.to_vec(), intermediate.to_vec());
+    }
+
+    /// How many blocks are known.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no block is known.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forget every block.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Vec<u8>, Vec<u8>>> {
+        self.blocks.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
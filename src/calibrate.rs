@@ -0,0 +1,65 @@
+//! Make sure the transport doesn't mangle the probes.
+
+use crate::oracle::answer;
+use crate::{ByteSet, Error, PaddingOracle, Result};
+
+/// Find which byte values reach the oracle intact.
+///
+/// Proxies and WAFs sometimes re-encode or strip some bytes, which breaks the attack in
+/// mysterious ways. This sends the ciphertext, with the IV prepended, with its first byte set to
+/// every possible value. That byte only changes the first byte of plaintext, so a sane transport
+/// keeps the padding valid: the values for which the oracle reports an invalid padding are the
+/// ones the transport mangles.
+///
+/// The result can be used as [DecryptOptions::allowed_bytes](crate::DecryptOptions::allowed_bytes).
+/// The ciphertext needs at least two blocks after the IV, and must be accepted by the oracle.
+///
+/// # Example
+/// ```no_run
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 48];
+/// let allowed = padding_oracle::calibrate_transport(&ciphertext, 16, oracle).unwrap();
+///
+/// if allowed.len() < 256 {
+///     eprintln!("The transport mangles {} byte values", 256 - allowed.len());
+/// }
+/// ```
+pub fn calibrate_transport(
+    ciphertext: &[u8],
+    blocksize: usize,
+    mut oracle: impl PaddingOracle,
+) -> Result<ByteSet> {
+    crate::check_blocksize(blocksize)?;
+
+    if !ciphertext.len().is_multiple_of(blocksize) {
+        return Err(Error::WrongSize {
+            blocksize,
+            found: ciphertext.len(),
+        });
+    }
+
+    if ciphertext.len() < 3 * blocksize {
+        return Err(Error::TooShort {
+            min: 3 * blocksize,
+            found: ciphertext.len(),
+        });
+    }
+
+    if !answer(oracle.query(ciphertext))? {
+        return Err(Error::RejectedCiphertext);
+    }
+
+    let mut probe = ciphertext.to_vec();
+
+    let mut allowed = ByteSet::new();
+
+    for byte in 0..=255 {
+        probe[0] = byte;
+
+        if answer(oracle.query(&probe))? {
+            allowed.insert(byte);
+        }
+    }
+
+    Ok(allowed)
+}
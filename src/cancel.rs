@@ -0,0 +1,102 @@
+//! Cooperative cancellation of the attack.
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::verify;
+use crate::{Error, PaddingOracle, PaddingOracleAttack, Result};
+
+/// Something telling whether the attack should stop.
+///
+/// This is checked before every oracle query. It is implemented for `Fn() -> bool` closures and
+/// [AtomicBool], as well as tokio's `CancellationToken` with the `tokio-util` feature.
+pub trait Cancellation {
+    fn is_cancelled(&self) -> bool;
+}
+
+/// Same as [decrypt](crate::decrypt), but stops when `cancel` says so.
+///
+/// On cancellation, this returns [Error::Cancelled] with each block recovered so far and its index.
+/// To be able to pick up where the attack left off, use [PaddingOracleAttack::run] instead.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 16];
+/// use std::sync::atomic::AtomicBool;
+///
+/// // Set this from a Ctrl-C handler or a supervising thread
+/// let cancelled = AtomicBool::new(false);
+///
+/// let plaintext = padding_oracle::decrypt_cancellable(&ciphertext, 16, oracle, &cancelled);
+/// ```
+pub fn decrypt_cancellable(
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: impl PaddingOracle,
+    cancel: impl Cancellation,
+) -> Result<Vec<u8>> {
+    let mut attack = PaddingOracleAttack::new(ciphertext, blocksize)?;
+
+    attack.run(oracle, cancel)?;
+    attack.finish()
+}
+
+impl PaddingOracleAttack {
+    /// Drive the attack with an oracle until it is over or `cancel` says so.
+    ///
+    /// Cancellation is checked before sending each probe. When cancelled, this returns
+    /// [Error::Cancelled] with each block recovered so far and its index, and the attack can be
+    /// resumed by calling this again.
+    pub fn run(&mut self, mut oracle: impl PaddingOracle, cancel: impl Cancellation) -> Result<()> {
+        if verify::needs_precheck(self) {
+            verify::precheck(self, oracle.by_ref())?;
+        }
+
+        let mut probe = Vec::new();
+
+        while self.next_probe_into(&mut probe) {
+            if cancel.is_cancelled() {
+                return Err(Error::Cancelled {
+                    partial: self.recovered_blocks(),
+                });
+            }
+
+            crate::query(self, &probe, &mut oracle)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<F: Fn() -> bool> Cancellation for F {
+    fn is_cancelled(&self) -> bool {
+        self()
+    }
+}
+
+impl Cancellation for AtomicBool {
+    fn is_cancelled(&self) -> bool {
+        self.load(Ordering::Relaxed)
+    }
+}
+
+impl Cancellation for &AtomicBool {
+    fn is_cancelled(&self) -> bool {
+        self.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(feature = "tokio-util")]
+impl Cancellation for tokio_util::sync::CancellationToken {
+    fn is_cancelled(&self) -> bool {
+        tokio_util::sync::CancellationToken::is_cancelled(self)
+    }
+}
+
+#[cfg(feature = "tokio-util")]
+impl Cancellation for &tokio_util::sync::CancellationToken {
+    fn is_cancelled(&self) -> bool {
+        tokio_util::sync::CancellationToken::is_cancelled(self)
+    }
+}
@@ -0,0 +1,119 @@
+//! Save an attack midway, to resume it later.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::verify;
+use crate::{AttackStats, PaddingOracle, PaddingOracleAttack, Result};
+
+/// A snapshot of a running attack, to pick it up where it left off.
+///
+/// It holds the ciphertext, the intermediate state recovered so far and the position of the
+/// byte being recovered, but not the [options](crate::DecryptOptions), which are given again on
+/// resume. Save it (with the `serde` feature) so an interrupted attack can be continued later,
+/// without sending the same queries again.
+///
+/// Get one from [PaddingOracleAttack::checkpoint] or [decrypt_resumable].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttackState {
+    pub(crate) ciphertext: Vec<u8>,
+    pub(crate) blocksize: usize,
+    pub(crate) selected: Range<usize>,
+    pub(crate) pending: Vec<usize>,
+    pub(crate) block: Option<usize>,
+    pub(crate) pad: usize,
+    pub(crate) candidate: usize,
+    pub(crate) verifying: bool,
+    pub(crate) confirming: bool,
+    pub(crate) intermediates: Vec<u8>,
+    pub(crate) recovered: Vec<bool>,
+    pub(crate) stats: AttackStats,
+}
+
+/// Same as [decrypt](crate::decrypt), but `checkpoint` gets a snapshot of the attack every time a
+/// block is recovered, and when the oracle fails.
+///
+/// Persist the last snapshot, and pass it to [resume] to continue an interrupted attack.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> std::io::Result<bool> { Ok(false) }
+/// # fn save(_: &padding_oracle::AttackState) {}
+/// # let ciphertext = [0u8; 16];
+/// let plaintext = padding_oracle::decrypt_resumable(&ciphertext, 16, oracle, |state| {
+///     // Write it to disk, with serde
+///     save(&state);
+/// });
+/// ```
+pub fn decrypt_resumable(
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: impl PaddingOracle,
+    checkpoint: impl FnMut(AttackState),
+) -> Result<Vec<u8>> {
+    run_resumable(
+        PaddingOracleAttack::new(ciphertext, blocksize)?,
+        oracle,
+        checkpoint,
+    )
+}
+
+/// Resume an attack from a snapshot taken by [decrypt_resumable], with the default options.
+///
+/// Fails with [Error::InvalidCheckpoint](crate::Error::InvalidCheckpoint) if the snapshot is
+/// inconsistent. See [PaddingOracleAttack::resume] to use other drivers or options.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # fn load() -> padding_oracle::AttackState {
+/// #     padding_oracle::PaddingOracleAttack::new(&[0; 16], 16).unwrap().checkpoint().unwrap()
+/// # }
+/// let state = load();
+///
+/// let plaintext = padding_oracle::resume(state, oracle, |state| {
+///     // Keep saving it, in case it is interrupted again
+///     # let _ = state;
+/// });
+/// ```
+pub fn resume(
+    state: AttackState,
+    oracle: impl PaddingOracle,
+    checkpoint: impl FnMut(AttackState),
+) -> Result<Vec<u8>> {
+    run_resumable(
+        PaddingOracleAttack::resume(state, &Default::default())?,
+        oracle,
+        checkpoint,
+    )
+}
+
+fn run_resumable(
+    mut attack: PaddingOracleAttack,
+    mut oracle: impl PaddingOracle,
+    mut checkpoint: impl FnMut(AttackState),
+) -> Result<Vec<u8>> {
+    if verify::needs_precheck(&attack) {
+        verify::precheck(&attack, oracle.by_ref())?;
+    }
+
+    let mut probe = Vec::new();
+
+    while attack.next_probe_into(&mut probe) {
+        let block = attack.current_block();
+
+        if let Err(e) = crate::query(&mut attack, &probe, &mut oracle) {
+            checkpoint(attack.checkpoint().expect("the attack is running"));
+            return Err(e);
+        }
+
+        if attack.current_block() != block {
+            if let Some(state) = attack.checkpoint() {
+                checkpoint(state);
+            }
+        }
+    }
+
+    attack.finish()
+}
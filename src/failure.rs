@@ -0,0 +1,103 @@
+//! Salvage what a failed attack recovered.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use thiserror::Error;
+
+use crate::{verify, DecryptOptions, PaddingOracle, PaddingOracleAttack};
+
+/// Why an attack failed, along with the plaintext it recovered until then.
+///
+/// See [decrypt_with_failure].
+#[derive(Debug, Error)]
+#[error("the attack failed after recovering {} blocks: {source}", .partial.len())]
+pub struct DecryptionFailure {
+    /// Each block which was fully recovered, counting the IV as block `0`, with its plaintext,
+    /// in the order of the ciphertext.
+    pub partial: Vec<(usize, Vec<u8>)>,
+
+    /// The block which was attacked, counting the IV as block `0`, or `None` if the attack
+    /// failed before attacking any block, such as on a ciphertext of the wrong size.
+    pub block: Option<usize>,
+
+    /// The index in the block of the byte which was being recovered, or `None` along with the
+    /// block.
+    pub byte: Option<usize>,
+
+    /// What went wrong.
+    #[source]
+    pub source: Box<crate::Error>,
+}
+
+/// Same as [decrypt_with_options](crate::decrypt_with_options), but on failure, returns the
+/// plaintext recovered so far and where the attack stopped.
+///
+/// To pick up where the attack left off instead, see
+/// [decrypt_resumable](crate::decrypt_resumable).
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 48];
+/// use padding_oracle::DecryptOptions;
+///
+/// match padding_oracle::decrypt_with_failure(&ciphertext, 16, oracle, &DecryptOptions::default()) {
+///     Ok(plaintext) => println!("{plaintext:?}"),
+///     Err(failure) => {
+///         eprintln!("Stopped at block {:?}, byte {:?}", failure.block, failure.byte);
+///         for (block, plaintext) in failure.partial {
+///             println!("Block {block}: {plaintext:?}");
+///         }
+///     }
+/// }
+/// ```
+pub fn decrypt_with_failure(
+    ciphertext: &[u8],
+    blocksize: usize,
+    mut oracle: impl PaddingOracle,
+    options: &DecryptOptions,
+) -> Result<Vec<u8>, DecryptionFailure> {
+    let mut attack =
+        PaddingOracleAttack::with_options(ciphertext, blocksize, options).map_err(|source| {
+            DecryptionFailure {
+                partial: Vec::new(),
+                block: None,
+                byte: None,
+                source: Box::new(source),
+            }
+        })?;
+
+    let mut position = attack.position();
+    let fail = |attack: &PaddingOracleAttack, position: Option<(usize, usize)>, source| {
+        DecryptionFailure {
+            partial: attack.recovered_blocks(),
+            block: position.map(|(block, _)| block),
+            byte: position.map(|(_, byte)| byte),
+            source: Box::new(source),
+        }
+    };
+
+    if options.precheck {
+        verify::precheck(&attack, oracle.by_ref()).map_err(|e| fail(&attack, position, e))?;
+    }
+
+    let mut probe = Vec::new();
+
+    while attack.next_probe_into(&mut probe) {
+        position = attack.position();
+
+        if let Err(e) = crate::query(&mut attack, &probe, &mut oracle) {
+            return Err(fail(&attack, position, e));
+        }
+    }
+
+    let partial = attack.recovered_blocks();
+
+    attack.finish().map_err(|source| DecryptionFailure {
+        partial,
+        block: position.map(|(block, _)| block),
+        byte: position.map(|(_, byte)| byte),
+        source: Box::new(source),
+    })
+}
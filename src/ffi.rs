@@ -0,0 +1,174 @@
+//! C bindings, to embed the attack in tools written in other languages.
+//!
+//! The oracle is a C callback, called with each probe and the `context` pointer given to the
+//! function running the attack. It returns a positive value when the padding is valid, `0` when
+//! it isn't, and a negative value when it fails, which stops the attack with [PoError::Oracle].
+//!
+//! ```c
+//! typedef int (*po_oracle)(const uint8_t *ciphertext, size_t len, void *context);
+//!
+//! int po_decrypt(const uint8_t *ciphertext, size_t ciphertext_len, size_t blocksize,
+//!                po_oracle oracle, void *context, uint8_t *output, size_t *output_len);
+//! int po_encrypt(const uint8_t *plaintext, size_t plaintext_len, size_t blocksize,
+//!                po_oracle oracle, void *context, uint8_t *output, size_t *output_len);
+//! ```
+//!
+//! Build the library with `cargo rustc --release --features ffi --crate-type staticlib`, or
+//! `cdylib` for a shared library.
+
+use alloc::boxed::Box;
+use core::error::Error as StdError;
+use core::ffi::{c_int, c_void};
+use core::slice;
+
+use crate::Error;
+
+/// The oracle callback, see the [module documentation](self).
+pub type PoOracle = extern "C" fn(ciphertext: *const u8, len: usize, context: *mut c_void) -> c_int;
+
+/// What the C functions return.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoError {
+    /// The attack succeeded.
+    Ok = 0,
+
+    /// A pointer is null.
+    NullPointer = 1,
+
+    /// The length of the input doesn't fit the blocksize, or the blocksize is invalid, see
+    /// [Error::WrongSize] and [Error::InvalidBlocksize].
+    WrongSize = 2,
+
+    /// The output buffer is too small. The length it needs is written to `output_len`.
+    BufferTooSmall = 3,
+
+    /// No candidate was found for a byte, see [Error::InvalidPadding].
+    InvalidPadding = 4,
+
+    /// The oracle callback returned a negative value.
+    Oracle = 5,
+
+    /// Any other error.
+    Other = 6,
+}
+
+impl From<&Error> for PoError {
+    fn from(error: &Error) -> Self {
+        match error {
+            Error::WrongSize { .. } | Error::InvalidBlocksize { .. } | Error::TooShort { .. } => {
+                Self::WrongSize
+            }
+            Error::InvalidPadding { .. } => Self::InvalidPadding,
+            Error::LengthMismatch { .. } => Self::BufferTooSmall,
+            Error::Oracle(_) => Self::Oracle,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Decrypt a ciphertext, with the IV prepended, as with [decrypt](crate::decrypt).
+///
+/// `output_len` holds the size of `output`, and receives the length of the plaintext, which is
+/// the length of the ciphertext without its IV. If `output` is too small, this returns
+/// [PoError::BufferTooSmall] without querying the oracle.
+///
+/// # Safety
+/// `ciphertext` must point to `ciphertext_len` readable bytes, `output_len` to a writable
+/// `size_t`, and `output` to `*output_len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn po_decrypt(
+    ciphertext: *const u8,
+    ciphertext_len: usize,
+    blocksize: usize,
+    oracle: Option<PoOracle>,
+    context: *mut c_void,
+    output: *mut u8,
+    output_len: *mut usize,
+) -> PoError {
+    let (Some(oracle), false, false, false) = (
+        oracle,
+        ciphertext.is_null(),
+        output.is_null(),
+        output_len.is_null(),
+    ) else {
+        return PoError::NullPointer;
+    };
+
+    let ciphertext = slice::from_raw_parts(ciphertext, ciphertext_len);
+    let needed = ciphertext_len.saturating_sub(blocksize);
+    if *output_len < needed {
+        *output_len = needed;
+        return PoError::BufferTooSmall;
+    }
+
+    let output = slice::from_raw_parts_mut(output, *output_len);
+    match crate::decrypt_into(ciphertext, blocksize, callback(oracle, context), output) {
+        Ok(len) => {
+            *output_len = len;
+            PoError::Ok
+        }
+        Err(e) => PoError::from(&e),
+    }
+}
+
+/// Forge a ciphertext, with the IV prepended, which decrypts to `plaintext` once PKCS7 padded,
+/// as with [encrypt](crate::encrypt).
+///
+/// `output_len` holds the size of `output`, and receives the length of the ciphertext, which is
+/// the padded plaintext and one block for the IV. If `output` is too small, this returns
+/// [PoError::BufferTooSmall] without querying the oracle.
+///
+/// # Safety
+/// `plaintext` must point to `plaintext_len` readable bytes, `output_len` to a writable `size_t`,
+/// and `output` to `*output_len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn po_encrypt(
+    plaintext: *const u8,
+    plaintext_len: usize,
+    blocksize: usize,
+    oracle: Option<PoOracle>,
+    context: *mut c_void,
+    output: *mut u8,
+    output_len: *mut usize,
+) -> PoError {
+    let (Some(oracle), false, false, false) = (
+        oracle,
+        plaintext.is_null(),
+        output.is_null(),
+        output_len.is_null(),
+    ) else {
+        return PoError::NullPointer;
+    };
+
+    if blocksize == 0 {
+        return PoError::WrongSize;
+    }
+
+    let plaintext = slice::from_raw_parts(plaintext, plaintext_len);
+    let needed = (plaintext_len / blocksize + 2) * blocksize;
+    if *output_len < needed {
+        *output_len = needed;
+        return PoError::BufferTooSmall;
+    }
+
+    match crate::encrypt(plaintext, blocksize, callback(oracle, context)) {
+        Ok(ciphertext) => {
+            slice::from_raw_parts_mut(output, ciphertext.len()).copy_from_slice(&ciphertext);
+            *output_len = ciphertext.len();
+            PoError::Ok
+        }
+        Err(e) => PoError::from(&e),
+    }
+}
+
+/// The oracle calling a C callback
+fn callback(
+    oracle: PoOracle,
+    context: *mut c_void,
+) -> impl FnMut(&[u8]) -> Result<bool, Box<dyn StdError + Send + Sync>> {
+    move |probe| match oracle(probe.as_ptr(), probe.len(), context) {
+        answer if answer < 0 => Err(alloc::format!("the callback returned {answer}").into()),
+        answer => Ok(answer > 0),
+    }
+}
@@ -0,0 +1,79 @@
+//! Forge ciphertexts from recovered intermediate states, without the oracle.
+
+use alloc::vec::Vec;
+
+use crate::{Error, PaddingOracleAttack, Result};
+
+/// Everything needed to forge ciphertexts once the attack is done.
+///
+/// The intermediate state of a ciphertext block is what it decrypts to before being xored with
+/// the previous block. Knowing it for a block `C`, the IV can be chosen so `[IV][C]` decrypts to
+/// any single-block plaintext. Save the kit (with the `serde` feature) to keep forging after the
+/// oracle went away.
+///
+/// Forging longer plaintexts requires new intermediate states for every block, so it can't be
+/// done without the oracle.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ForgeKit {
+    pub blocksize: usize,
+
+    /// Ciphertext blocks whose intermediate state is known.
+    pub blocks: Vec<Vec<u8>>,
+
+    /// The intermediate state of each block in `blocks`.
+    pub intermediates: Vec<Vec<u8>>,
+}
+
+impl ForgeKit {
+    /// Build a kit out of every block an attack recovered.
+    pub fn from_attack(attack: &PaddingOracleAttack) -> Self {
+        let bs = attack.blocksize();
+        let ciphertext = attack.ciphertext();
+
+        let (blocks, intermediates) = (1..ciphertext.len() / bs)
+            .filter_map(|block| {
+                attack.intermediate_block(block).map(|intermediate| {
+                    (
+                        ciphertext[block * bs..(block + 1) * bs].to_vec(),
+                        intermediate.to_vec(),
+                    )
+                })
+            })
+            .unzip();
+
+        Self {
+            blocksize: bs,
+            blocks,
+            intermediates,
+        }
+    }
+
+    /// Forge a ciphertext, with the IV prepended, which decrypts to `plaintext` once PKCS7 padded.
+    ///
+    /// The plaintext must be shorter than a block, since it has to fit with its padding.
+    pub fn forge(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let bs = self.blocksize;
+
+        let (Some(block), Some(intermediate)) = (self.blocks.first(), self.intermediates.first())
+        else {
+            return Err(Error::EmptyForgeKit);
+        };
+
+        if plaintext.len() >= bs {
+            return Err(Error::PlaintextTooLong {
+                max: bs - 1,
+                found: plaintext.len(),
+            });
+        }
+
+        let pad = (bs - plaintext.len()) as u8;
+
+        let iv = intermediate
+            .iter()
+            .enumerate()
+            .map(|(i, b)| b ^ plaintext.get(i).copied().unwrap_or(pad));
+
+        Ok(iv.chain(block.iter().copied()).collect())
+    }
+}
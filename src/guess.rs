@@ -0,0 +1,157 @@
+//! Choose in which order the values of each byte are guessed.
+
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use crate::text::{BIGRAMS, LIKELY_BYTES, WORD_END, WORD_START};
+
+/// Decides in which order the plaintext values of each byte are guessed, likeliest first.
+///
+/// Use it with [CandidateOrder::Custom](crate::CandidateOrder::Custom) for domain-specific
+/// orderings, such as base64 alphabets or JSON tokens. The attack recovers each block from its
+/// last byte to its first one, so the plaintext right after the byte is known.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+///
+/// use padding_oracle::{CandidateOrder, DecryptOptions, GuessContext, GuessStrategy};
+///
+/// /// JSON, where a `"` is usually followed by a `:` or a `,`
+/// #[derive(Debug)]
+/// struct Json;
+///
+/// impl GuessStrategy for Json {
+///     fn guesses(&self, context: &GuessContext<'_>) -> Vec<u8> {
+///         match context.following.first() {
+///             Some(b':' | b',') => b"\"".to_vec(),
+///             _ => b"\"{}[]:, abcdefghijklmnopqrstuvwxyz0123456789".to_vec(),
+///         }
+///     }
+/// }
+///
+/// let options = DecryptOptions {
+///     candidate_order: CandidateOrder::Custom(Arc::new(Json)),
+///     ..Default::default()
+/// };
+/// ```
+pub trait GuessStrategy: Debug + Send + Sync {
+    /// The plaintext values to try first for a byte, likeliest first. The values left out are
+    /// tried afterwards, in increasing order.
+    fn guesses(&self, context: &GuessContext<'_>) -> Vec<u8>;
+}
+
+/// What is known about a byte, when guessing its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct GuessContext<'a> {
+    /// Index of the block, counting the IV as block `0`.
+    pub block: usize,
+
+    /// Index of the byte in the block.
+    pub byte: usize,
+
+    pub blocksize: usize,
+
+    /// Whether this is the last block, which ends with the padding.
+    pub last_block: bool,
+
+    /// The plaintext already recovered after the byte, up to the end of the block.
+    pub following: &'a [u8],
+}
+
+/// Every byte value, `guesses` first, without duplicates
+pub(crate) fn complete(guesses: Vec<u8>) -> Vec<u8> {
+    let mut seen = [false; 256];
+    let mut order = Vec::with_capacity(256);
+
+    for guess in guesses.into_iter().chain(0..=255) {
+        if !seen[guess as usize] {
+            seen[guess as usize] = true;
+            order.push(guess);
+        }
+    }
+
+    order
+}
+
+/// A [GuessStrategy] for English prose, guessing each byte from the letter after it.
+///
+/// Letters are guessed from the most common pairs of letters in English, spaces and punctuation
+/// from the letters usually ending a word, and the padding from the bytes already recovered. This
+/// takes a few dozen queries per byte of English text, a fraction of the 128 queries it takes on
+/// average in [Sequential](crate::CandidateOrder::Sequential) order.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+///
+/// use padding_oracle::{CandidateOrder, DecryptOptions, EnglishText};
+///
+/// let options = DecryptOptions {
+///     candidate_order: CandidateOrder::Custom(Arc::new(EnglishText)),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnglishText;
+
+impl GuessStrategy for EnglishText {
+    fn guesses(&self, context: &GuessContext<'_>) -> Vec<u8> {
+        let mut guesses = Vec::new();
+        let mut following = context.following;
+
+        if context.last_block {
+            match following.first() {
+                // The padding byte
+                None => guesses.extend((1..=context.blocksize.min(255)).map(|pad| pad as u8)),
+                Some(&pad) if following.iter().all(|&byte| byte == pad) => {
+                    if following.len() < pad as usize {
+                        guesses.push(pad);
+                    } else {
+                        // The end of the text
+                        following = &[];
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+
+        match following.first() {
+            Some(&next) if next.is_ascii_alphabetic() => {
+                let next = next.to_ascii_lowercase();
+                let before: Vec<u8> = BIGRAMS
+                    .split_whitespace()
+                    .map(str::as_bytes)
+                    .filter(|pair| pair[1] == next)
+                    .map(|pair| pair[0])
+                    .collect();
+
+                // Words starting with this letter compete with the most common pairs
+                let space = match WORD_START.iter().position(|&start| start == next) {
+                    Some(rank) => (rank / 4).min(before.len()),
+                    None => before.len(),
+                };
+
+                guesses.extend_from_slice(&before[..space]);
+                guesses.push(b' ');
+                guesses.extend_from_slice(&before[space..]);
+                guesses.extend(before.iter().map(u8::to_ascii_uppercase));
+            }
+            Some(next) if next.is_ascii_digit() => guesses.extend_from_slice(b"0123456789"),
+            Some(next) if next.is_ascii_punctuation() || next.is_ascii_whitespace() => {
+                guesses.extend_from_slice(WORD_END);
+                guesses.extend_from_slice(b".,!?)\"'");
+            }
+            // Nothing after, or the end of the text
+            _ if context.last_block => {
+                guesses.extend_from_slice(WORD_END);
+                guesses.extend_from_slice(b".!?\n");
+            }
+            _ => {}
+        }
+
+        guesses.extend_from_slice(&LIKELY_BYTES);
+        guesses
+    }
+}
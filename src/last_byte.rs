@@ -0,0 +1,114 @@
+//! Recover what an oracle only checking the length byte of the padding tells.
+
+use alloc::vec::Vec;
+use core::cell::Cell;
+
+use crate::oracle::answer;
+use crate::{Error, PaddingOracle, Result};
+
+/// Recover the last plaintext byte of every block, from an oracle which only checks that the last
+/// byte is a valid padding length, such as an [ISO 10126](crate::PaddingScheme::Iso10126) one.
+///
+/// Such an oracle doesn't tell anything about the other bytes, so this is all a padding oracle
+/// attack can recover. A tampered last byte is valid for `blocksize` values out of 256, so a
+/// valid one is found within about `256 / blocksize` queries, and a few more queries tell which
+/// length it was valid for.
+///
+/// Like [decrypt](crate::decrypt), this assumes the IV is prepended to the ciphertext and
+/// returns a byte for every block after it. Fails with
+/// [Error::InvalidPadding] if a block has no valid candidate, or if the oracle answers
+/// inconsistently.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 48];
+/// if let Ok(last_bytes) = padding_oracle::recover_last_bytes(&ciphertext, 16, oracle) {
+///     println!("{last_bytes:?}");
+/// }
+/// ```
+pub fn recover_last_bytes(
+    ciphertext: &[u8],
+    blocksize: usize,
+    mut oracle: impl PaddingOracle,
+) -> Result<Vec<u8>> {
+    crate::check_blocksize(blocksize)?;
+
+    if !ciphertext.len().is_multiple_of(blocksize) {
+        return Err(Error::WrongSize {
+            blocksize,
+            found: ciphertext.len(),
+        });
+    }
+
+    (1..ciphertext.len() / blocksize)
+        .map(|block| last_byte(ciphertext, blocksize, block, &mut oracle))
+        .collect()
+}
+
+/// The last plaintext byte of a block
+fn last_byte(
+    ciphertext: &[u8],
+    blocksize: usize,
+    block: usize,
+    oracle: &mut impl PaddingOracle,
+) -> Result<u8> {
+    let mut probe = ciphertext[..(block + 1) * blocksize].to_vec();
+    let tampered = block * blocksize - 1;
+    let original = ciphertext[tampered];
+
+    let queries = Cell::new(0);
+    let mut valid = |mask: u8| {
+        probe[tampered] = original ^ mask;
+        queries.set(queries.get() + 1);
+        answer(oracle.query(&probe))
+    };
+
+    // The last byte xored with the mask is a valid length. Each multiple of the blocksize, rounded
+    // up to a power of two, covers its own range of values, so one of them is valid.
+    let step = blocksize.next_power_of_two();
+    let mut candidates: Vec<u8> = (0..=255).collect();
+    candidates.sort_by_key(|&candidate| !(candidate as usize).is_multiple_of(step));
+
+    let mut mask = None;
+    for candidate in candidates {
+        if valid(candidate)? {
+            mask = Some(candidate);
+            break;
+        }
+    }
+    let invalid = || Error::InvalidPadding {
+        block,
+        byte: blocksize - 1,
+        queries: queries.get(),
+        false_positives: 0,
+    };
+    let mask = mask.ok_or_else(invalid)?;
+
+    // Narrow down which length it was valid for
+    let is_length = |value: u8| (1..=blocksize).contains(&(value as usize));
+    let mut lengths: Vec<u8> = (1..=blocksize as u8).collect();
+
+    while lengths.len() > 1 {
+        // The flip which splits the possible lengths the most evenly
+        let split = |flip: u8| {
+            let valid = lengths.iter().filter(|&&l| is_length(l ^ flip)).count();
+            valid.abs_diff(lengths.len() - valid)
+        };
+        let flip = (1..=255)
+            .min_by_key(|&flip| split(flip))
+            .expect("there are flips");
+
+        if split(flip) == lengths.len() {
+            break;
+        }
+
+        let still_valid = valid(mask ^ flip)?;
+        lengths.retain(|&l| is_length(l ^ flip) == still_valid);
+    }
+
+    match lengths[..] {
+        [length] => Ok(length ^ mask),
+        _ => Err(invalid()),
+    }
+}
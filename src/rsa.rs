@@ -0,0 +1,142 @@
+//! What the attacks on RSA have in common: multiplying the message through the ciphertext.
+
+use alloc::vec::Vec;
+
+use crate::bigint::BigUint;
+use crate::oracle::answer;
+use crate::rng::SplitMix64;
+use crate::{AttackStats, Error, PaddingOracle, Result};
+
+/// Statistics about an attack on RSA.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RsaStats {
+    /// Total number of oracle queries.
+    pub queries: usize,
+
+    /// Number of oracle queries spent on blinding the ciphertext, when it wasn't conforming.
+    pub blinding_queries: usize,
+
+    /// Number of times the range of the message was narrowed down.
+    pub iterations: usize,
+}
+
+/// Options for the attacks on RSA, [rsa_pkcs1](crate::rsa_pkcs1) and [rsa_oaep](crate::rsa_oaep).
+///
+/// Use `..Default::default()` when building it, so new options don't break your code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RsaOptions {
+    /// Give up after this many queries, with [Error::QueryBudgetExceeded] holding the number of
+    /// queries in its stats. `None` never gives up.
+    ///
+    /// Defaults to 100 million queries, far more than either attack takes with an oracle that
+    /// works. This stops the attack when the oracle never accepts anything, such as with the
+    /// wrong key or an endpoint that went down, which would otherwise search forever.
+    pub max_queries: Option<usize>,
+}
+
+impl Default for RsaOptions {
+    fn default() -> Self {
+        Self {
+            max_queries: Some(100_000_000),
+        }
+    }
+}
+
+/// A ciphertext, and the oracle telling something about the message multiplied by any number
+pub(crate) struct Target<O> {
+    pub(crate) n: BigUint,
+    e: BigUint,
+    /// The length of the modulus in bytes
+    pub(crate) k: usize,
+    /// The ciphertext, blinded if needed
+    c: BigUint,
+    oracle: O,
+    max_queries: Option<usize>,
+    pub(crate) stats: RsaStats,
+}
+
+impl<O: PaddingOracle> Target<O> {
+    /// Fails if the modulus is shorter than `min` bytes, or the ciphertext isn't as long as it
+    pub(crate) fn new(
+        modulus: &[u8],
+        public_exponent: &[u8],
+        ciphertext: &[u8],
+        oracle: O,
+        min: usize,
+        options: &RsaOptions,
+    ) -> Result<Self> {
+        let n = BigUint::from_bytes_be(modulus);
+        let k = n.bits().div_ceil(8);
+
+        if k < min {
+            return Err(Error::TooShort { min, found: k });
+        }
+
+        if ciphertext.len() != k {
+            return Err(Error::LengthMismatch {
+                expected: k,
+                found: ciphertext.len(),
+            });
+        }
+
+        Ok(Self {
+            c: &BigUint::from_bytes_be(ciphertext) % &n,
+            e: BigUint::from_bytes_be(public_exponent),
+            n,
+            k,
+            oracle,
+            max_queries: options.max_queries,
+            stats: RsaStats::default(),
+        })
+    }
+
+    /// Whether the oracle accepts the ciphertext for the message multiplied by `s`, unless the
+    /// attack ran out of queries
+    pub(crate) fn valid(&mut self, s: &BigUint) -> Result<bool> {
+        if self
+            .max_queries
+            .is_some_and(|max| self.stats.queries >= max)
+        {
+            return Err(Error::QueryBudgetExceeded {
+                partial: Vec::new(),
+                stats: AttackStats {
+                    queries: self.stats.queries,
+                    ..Default::default()
+                },
+            });
+        }
+
+        let probe = &(&self.c * &s.pow_mod(&self.e, &self.n)) % &self.n;
+
+        self.stats.queries += 1;
+        answer(self.oracle.query(&probe.to_bytes_be(self.k)))
+    }
+
+    /// Blind the ciphertext until the oracle accepts it, and return the number to multiply the
+    /// message found by to unblind it
+    pub(crate) fn blind(&mut self) -> Result<BigUint> {
+        let mut rng = SplitMix64::new(0);
+        let (mut s, mut inverse) = (BigUint::from_u64(1), BigUint::from_u64(1));
+
+        while !self.valid(&s)? {
+            (s, inverse) = loop {
+                let random: Vec<u8> = (0..self.k.div_ceil(8))
+                    .flat_map(|_| rng.next_u64().to_be_bytes())
+                    .collect();
+                let s = &BigUint::from_bytes_be(&random) % &self.n;
+
+                if let Some(inverse) = s.inverse_mod(&self.n) {
+                    break (s, inverse);
+                }
+            };
+        }
+
+        self.stats.blinding_queries = self.stats.queries;
+        self.c = &(&self.c * &s.pow_mod(&self.e, &self.n)) % &self.n;
+
+        Ok(inverse)
+    }
+}
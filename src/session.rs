@@ -0,0 +1,214 @@
+//! Keep the session of an oracle alive, such as a login cookie or a CSRF token.
+
+use alloc::boxed::Box;
+use core::error::Error;
+
+use thiserror::Error;
+
+use crate::{OracleResponse, OracleResult, PaddingOracle};
+
+/// An oracle which must hold a session to answer, such as a web application behind a login.
+///
+/// Wrap it in a [Stateful] oracle to attack it: the session is set up before the first query,
+/// refreshed when it expires or on a [schedule](RefreshSchedule), and torn down once the oracle
+/// is dropped.
+///
+/// # Example
+/// ```
+/// use std::boxed::Box;
+/// use std::error::Error;
+///
+/// use padding_oracle::{SessionExpired, Stateful, StatefulOracle};
+///
+/// # fn login() -> Result<String, Box<dyn Error + Send + Sync>> { Ok(String::new()) }
+/// # fn send(_cookie: &str, _: &[u8]) -> u16 { 200 }
+/// struct Target {
+///     cookie: String,
+/// }
+///
+/// impl StatefulOracle for Target {
+///     type Response = bool;
+///
+///     fn setup(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+///         self.cookie = login()?;
+///         Ok(())
+///     }
+///
+///     fn query(&mut self, ciphertext: &[u8]) -> Result<bool, SessionExpired> {
+///         match send(&self.cookie, ciphertext) {
+///             // Logged out
+///             302 => Err(SessionExpired),
+///             status => Ok(status != 500),
+///         }
+///     }
+/// }
+///
+/// # let ciphertext = [0u8; 32];
+/// let oracle = Stateful::new(Target { cookie: String::new() });
+/// let plaintext = padding_oracle::decrypt(&ciphertext, 16, oracle);
+/// ```
+pub trait StatefulOracle {
+    /// What the oracle answers, usually a `bool`.
+    type Response: OracleResponse;
+
+    /// Open a session, such as by logging in.
+    fn setup(&mut self) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    /// Whether `ciphertext` decrypts to a valid padding, or [SessionExpired] if the session was
+    /// rejected.
+    fn query(&mut self, ciphertext: &[u8]) -> Result<Self::Response, SessionExpired>;
+
+    /// Renew the session, such as by fetching a new CSRF token. Opens a new one by default.
+    fn refresh(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.setup()
+    }
+
+    /// Close the session, such as by logging out. Does nothing by default.
+    fn teardown(&mut self) {}
+}
+
+/// The answer of a [StatefulOracle] whose session was rejected, and the error of a [Stateful]
+/// oracle when it was rejected right after being refreshed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("the session expired")]
+pub struct SessionExpired;
+
+/// When a [Stateful] oracle refreshes its session, besides when it expires.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RefreshSchedule {
+    /// Refresh the session after this many queries.
+    pub queries: Option<usize>,
+
+    /// Refresh the session once it is this old.
+    #[cfg(feature = "std")]
+    pub interval: Option<core::time::Duration>,
+}
+
+/// A [PaddingOracle] managing the session of a [StatefulOracle].
+///
+/// The session is set up before the first query and refreshed following the schedule. When a
+/// query finds it expired, it is refreshed and the query is sent again. The query fails with
+/// [SessionExpired] if it expires again right away, and with the error of the setup or the refresh
+/// if they fail, which the attack returns in [Error::Oracle](crate::Error::Oracle). The session is
+/// torn down when the oracle is dropped.
+#[derive(Debug)]
+pub struct Stateful<O: StatefulOracle> {
+    /// The oracle, until it is taken back by [into_inner](Self::into_inner)
+    inner: Option<O>,
+    schedule: RefreshSchedule,
+
+    /// Whether the session is open
+    open: bool,
+
+    /// The queries sent since the session was set up or refreshed
+    queries: usize,
+
+    #[cfg(feature = "std")]
+    refreshed: Option<std::time::Instant>,
+}
+
+impl<O: StatefulOracle> Stateful<O> {
+    /// Manage the session of `inner`, only refreshing it when it expires.
+    pub fn new(inner: O) -> Self {
+        Self::with_schedule(inner, RefreshSchedule::default())
+    }
+
+    /// Same as [Stateful::new], also refreshing the session following `schedule`.
+    pub fn with_schedule(inner: O, schedule: RefreshSchedule) -> Self {
+        Self {
+            inner: Some(inner),
+            schedule,
+            open: false,
+            queries: 0,
+            #[cfg(feature = "std")]
+            refreshed: None,
+        }
+    }
+
+    /// Whether the session is open.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Tear the session down now. The next query sets up a new one.
+    pub fn teardown(&mut self) {
+        if core::mem::take(&mut self.open) {
+            self.oracle().teardown();
+        }
+    }
+
+    /// The inner oracle, once its session is torn down.
+    pub fn into_inner(mut self) -> O {
+        self.teardown();
+        self.inner.take().expect("the oracle is only taken once")
+    }
+
+    fn oracle(&mut self) -> &mut O {
+        self.inner.as_mut().expect("the oracle is only taken once")
+    }
+
+    /// Whether the schedule asks for a refresh
+    fn refresh_due(&self) -> bool {
+        if self.schedule.queries.is_some_and(|max| self.queries >= max) {
+            return true;
+        }
+
+        #[cfg(feature = "std")]
+        if let (Some(interval), Some(refreshed)) = (self.schedule.interval, self.refreshed) {
+            return refreshed.elapsed() >= interval;
+        }
+
+        false
+    }
+
+    /// Set up the session if it isn't open, or refresh it
+    fn renew(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.open {
+            self.oracle().refresh()?;
+        } else {
+            self.oracle().setup()?;
+            self.open = true;
+        }
+
+        self.queries = 0;
+
+        #[cfg(feature = "std")]
+        {
+            self.refreshed = Some(std::time::Instant::now());
+        }
+
+        Ok(())
+    }
+}
+
+impl<O: StatefulOracle> PaddingOracle for Stateful<O> {
+    type Response = Result<OracleResult, Box<dyn Error + Send + Sync>>;
+
+    fn query(&mut self, ciphertext: &[u8]) -> Self::Response {
+        if !self.open || self.refresh_due() {
+            self.renew()?;
+        }
+
+        self.queries += 1;
+        if let Ok(response) = self.oracle().query(ciphertext) {
+            return response.into_result();
+        }
+
+        self.renew()?;
+
+        self.queries += 1;
+        match self.oracle().query(ciphertext) {
+            Ok(response) => response.into_result(),
+            Err(expired) => Err(Box::new(expired)),
+        }
+    }
+}
+
+impl<O: StatefulOracle> Drop for Stateful<O> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            self.teardown();
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! An oracle sending the probes over a socket, for custom binary protocols.
+//!
+//! The probes are framed into messages by a closure, and a matcher tells from each response
+//! whether the padding was valid. The connection is kept open between queries when the
+//! [ResponseFraming] tells where responses end, and opened again whenever it breaks, so servers
+//! closing the connection after a padding error are fine too.
+//!
+//! The connection is a [TcpStream] by default. For TLS, or anything else, open it with
+//! [SocketOracle::with_connector], wrapping the stream in the TLS library of your choice.
+//!
+//! # Example
+//! ```no_run
+//! use padding_oracle::socket::{ResponseFraming, SocketOracle};
+//!
+//! let oracle = SocketOracle::new(
+//!     "127.0.0.1:4000",
+//!     // A command byte, then the ciphertext length and the ciphertext
+//!     |probe: &[u8]| [&[0x01, probe.len() as u8][..], probe].concat(),
+//!     // One status byte per response
+//!     ResponseFraming::Exact(1),
+//!     |response: &[u8]| response != [0xff],
+//! );
+//!
+//! # let ciphertext = [0u8; 32];
+//! let plaintext = padding_oracle::decrypt(&ciphertext, 16, oracle)?;
+//! # Ok::<(), padding_oracle::Error>(())
+//! ```
+
+use std::boxed::Box;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::vec::Vec;
+
+use crate::PaddingOracle;
+
+/// Where the responses of the server end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFraming {
+    /// The server closes the connection after each response, so a new one is opened for every
+    /// query.
+    UntilClose,
+
+    /// Every response is this many bytes long.
+    Exact(usize),
+
+    /// Every response starts with its length, as a big-endian integer of this many bytes, which
+    /// is not passed to the matcher.
+    LengthPrefixed(usize),
+
+    /// Every response ends with this byte, which is passed to the matcher.
+    Delimiter(u8),
+}
+
+/// An oracle sending every probe over a socket.
+pub struct SocketOracle<C, S, F, M> {
+    connect: C,
+    connection: Option<BufReader<S>>,
+    frame: F,
+    framing: ResponseFraming,
+    matches: M,
+}
+
+/// Opens TCP connections, see [SocketOracle::new]
+pub type TcpConnector = Box<dyn FnMut() -> io::Result<TcpStream> + Send>;
+
+impl<F, M> SocketOracle<TcpConnector, TcpStream, F, M>
+where
+    F: FnMut(&[u8]) -> Vec<u8>,
+    M: FnMut(&[u8]) -> bool,
+{
+    /// Connect to `address` over TCP. Each probe is written as `frame` makes it, and the padding
+    /// is valid if `matches` returns `true` for the response.
+    pub fn new(
+        address: impl ToSocketAddrs + Clone + Send + 'static,
+        frame: F,
+        framing: ResponseFraming,
+        matches: M,
+    ) -> Self {
+        let connect: TcpConnector = Box::new(move || TcpStream::connect(address.clone()));
+
+        Self::with_connector(connect, frame, framing, matches)
+    }
+}
+
+impl<C, S, F, M> SocketOracle<C, S, F, M>
+where
+    C: FnMut() -> io::Result<S>,
+    S: Read + Write,
+    F: FnMut(&[u8]) -> Vec<u8>,
+    M: FnMut(&[u8]) -> bool,
+{
+    /// Same as [SocketOracle::new], but opening the connections with `connect`, such as a TLS
+    /// session over a [TcpStream].
+    pub fn with_connector(connect: C, frame: F, framing: ResponseFraming, matches: M) -> Self {
+        Self {
+            connect,
+            connection: None,
+            frame,
+            framing,
+            matches,
+        }
+    }
+
+    /// Send a probe and read the response, over the current connection
+    fn exchange(&mut self, message: &[u8]) -> io::Result<Vec<u8>> {
+        let connection = match &mut self.connection {
+            Some(connection) => connection,
+            None => self.connection.insert(BufReader::new((self.connect)()?)),
+        };
+
+        connection.get_mut().write_all(message)?;
+        connection.get_mut().flush()?;
+
+        let mut response = Vec::new();
+        match self.framing {
+            ResponseFraming::UntilClose => {
+                connection.read_to_end(&mut response)?;
+                self.connection = None;
+            }
+            ResponseFraming::Exact(len) => {
+                response.resize(len, 0);
+                connection.read_exact(&mut response)?;
+            }
+            ResponseFraming::LengthPrefixed(size) => {
+                let mut prefix = std::vec![0u8; size];
+                connection.read_exact(&mut prefix)?;
+
+                let len = prefix
+                    .iter()
+                    .fold(0usize, |len, &b| (len << 8) | b as usize);
+                response.resize(len, 0);
+                connection.read_exact(&mut response)?;
+            }
+            ResponseFraming::Delimiter(delimiter) => {
+                connection.read_until(delimiter, &mut response)?;
+
+                if response.last() != Some(&delimiter) {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+            }
+        }
+
+        Ok(response)
+    }
+}
+
+impl<C, S, F, M> PaddingOracle for SocketOracle<C, S, F, M>
+where
+    C: FnMut() -> io::Result<S>,
+    S: Read + Write,
+    F: FnMut(&[u8]) -> Vec<u8>,
+    M: FnMut(&[u8]) -> bool,
+{
+    type Response = io::Result<bool>;
+
+    fn query(&mut self, ciphertext: &[u8]) -> io::Result<bool> {
+        let message = (self.frame)(ciphertext);
+
+        // A connection kept open may have been closed by the server since, so try a new one
+        let reused = self.connection.is_some();
+        let response = match self.exchange(&message) {
+            Err(_) if reused => {
+                self.connection = None;
+                self.exchange(&message)
+            }
+            response => response,
+        };
+
+        if response.is_err() {
+            self.connection = None;
+        }
+
+        response.map(|response| (self.matches)(&response))
+    }
+}
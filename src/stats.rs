@@ -0,0 +1,44 @@
+//! Statistics about an attack.
+
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+/// Statistics about an attack.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttackStats {
+    /// Total number of oracle queries.
+    pub queries: usize,
+
+    /// Number of oracle queries spent on each ciphertext block, excluding the IV.
+    pub block_queries: Vec<usize>,
+
+    /// Number of valid candidates rejected by their double check, which would have been wrong
+    /// bytes without [verification](crate::DecryptOptions::verification).
+    pub false_positives: usize,
+
+    /// Time elapsed between the start of the attack and the last answer, serialized as seconds.
+    #[cfg(feature = "std")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "seconds", deserialize_with = "from_seconds")
+    )]
+    pub duration: Duration,
+}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+fn seconds<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> core::result::Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+fn from_seconds<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> core::result::Result<Duration, D::Error> {
+    let seconds = <f64 as serde::Deserialize>::deserialize(deserializer)?;
+    Duration::try_from_secs_f64(seconds).map_err(serde::de::Error::custom)
+}
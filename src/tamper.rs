@@ -0,0 +1,142 @@
+//! Tamper with a ciphertext whose plaintext is known.
+
+use alloc::vec::Vec;
+
+use crate::{decrypt, Error, PaddingOracle, Result};
+
+/// Flip bits of a ciphertext so that `known`, found at `offset` in its plaintext, decrypts to
+/// `desired` instead.
+///
+/// This is the classic CBC bitflipping attack: flipping a bit of a ciphertext block flips the
+/// same bit in the plaintext of the next block. The ciphertext has its IV prepended, and `offset`
+/// is a position in the plaintext, as returned by [decrypt](crate::decrypt).
+///
+/// Note that the plaintext of the block preceding the modified bytes gets garbled, unless it is
+/// the IV.
+///
+/// # Example
+/// ```
+/// # let ciphertext = [0u8; 48];
+/// // The plaintext contains ";role=guest;" at offset 16
+/// let forged = padding_oracle::flip_plaintext(&ciphertext, 16, 16, b"guest", b"admin").unwrap();
+/// ```
+pub fn flip_plaintext(
+    ciphertext: &[u8],
+    blocksize: usize,
+    offset: usize,
+    known: &[u8],
+    desired: &[u8],
+) -> Result<Vec<u8>> {
+    if known.len() != desired.len() {
+        return Err(Error::LengthMismatch {
+            expected: known.len(),
+            found: desired.len(),
+        });
+    }
+
+    let plaintext_len = ciphertext.len().saturating_sub(blocksize);
+
+    if offset + known.len() > plaintext_len {
+        return Err(Error::InvalidRange {
+            start: offset,
+            end: offset + known.len(),
+            len: plaintext_len,
+        });
+    }
+
+    let mut forged = ciphertext.to_vec();
+
+    // The plaintext at `offset` is xored with the ciphertext at the same offset, since the
+    // ciphertext starts with the IV
+    for (i, (k, d)) in known.iter().zip(desired).enumerate() {
+        forged[offset + i] ^= k ^ d;
+    }
+
+    Ok(forged)
+}
+
+/// Rewrite a ciphertext so that it decrypts to `desired`, given its recovered `plaintext`.
+///
+/// `plaintext` is as returned by [decrypt](crate::decrypt), and `desired` must be as long. Blocks
+/// are rewritten from the end: the block before a modified plaintext block is bitflipped, like
+/// [flip_plaintext] does. This garbles its own plaintext, so it is fixed in turn by recovering
+/// its new intermediate decryption state through the oracle, up to the IV. Only the blocks
+/// before the first difference are attacked, so keep the changes close to the end to save
+/// queries.
+///
+/// # Example
+/// ```no_run
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 48];
+/// let plaintext = padding_oracle::decrypt(&ciphertext, 16, oracle).unwrap();
+/// let desired = String::from_utf8(plaintext.clone()).unwrap().replace("user=guest", "user=admin");
+///
+/// let forged =
+///     padding_oracle::rewrite_plaintext(&ciphertext, 16, &plaintext, desired.as_bytes(), oracle);
+/// ```
+pub fn rewrite_plaintext(
+    ciphertext: &[u8],
+    blocksize: usize,
+    plaintext: &[u8],
+    desired: &[u8],
+    mut oracle: impl PaddingOracle,
+) -> Result<Vec<u8>> {
+    crate::check_blocksize(blocksize)?;
+
+    if !ciphertext.len().is_multiple_of(blocksize) {
+        return Err(Error::WrongSize {
+            blocksize,
+            found: ciphertext.len(),
+        });
+    }
+
+    let plaintext_len = ciphertext.len().saturating_sub(blocksize);
+
+    for len in [plaintext.len(), desired.len()] {
+        if len != plaintext_len {
+            return Err(Error::LengthMismatch {
+                expected: plaintext_len,
+                found: len,
+            });
+        }
+    }
+
+    let bs = blocksize;
+    let mut forged = ciphertext.to_vec();
+
+    // Whether the ciphertext block being fixed was modified
+    let mut modified = false;
+
+    for block in (1..ciphertext.len() / bs).rev() {
+        let range = (block - 1) * bs..block * bs;
+
+        if !modified && plaintext[range.clone()] == desired[range.clone()] {
+            continue;
+        }
+
+        let intermediate = if modified {
+            // Behind a block of zeros, the plaintext is the intermediate state
+            let mut probe = alloc::vec![0u8; bs];
+            probe.extend_from_slice(&forged[block * bs..(block + 1) * bs]);
+
+            decrypt(&probe, bs, oracle.by_ref())?
+        } else {
+            plaintext[range.clone()]
+                .iter()
+                .zip(&ciphertext[range.clone()])
+                .map(|(p, c)| p ^ c)
+                .collect()
+        };
+
+        let prev: Vec<u8> = intermediate
+            .iter()
+            .zip(&desired[range.clone()])
+            .map(|(i, d)| i ^ d)
+            .collect();
+
+        modified = forged[range.clone()] != prev[..];
+        forged[range].copy_from_slice(&prev);
+    }
+
+    Ok(forged)
+}
@@ -0,0 +1,102 @@
+//! Helpers to stand up oracles locally, to test the attack against any cipher.
+
+use alloc::vec::Vec;
+
+use crate::bigint::BigUint;
+use crate::PaddingScheme;
+
+/// Build an oracle out of the decryption function of a block cipher.
+///
+/// The oracle decrypts the last block of the ciphertext in CBC mode and checks its padding, which
+/// is all a real padding oracle does. It rejects ciphertexts that are not made of at least two
+/// blocks, counting the IV.
+///
+/// # Example
+/// ```
+/// use padding_oracle::{testing::local_oracle, PaddingScheme};
+///
+/// // A toy cipher, xoring the block with a key
+/// let oracle = local_oracle(|block: &[u8; 8]| block.map(|b| b ^ 0x42), PaddingScheme::Pkcs7);
+///
+/// let iv = [0u8; 8];
+/// let plaintext = *b"SECRET\x02\x02";
+/// let ciphertext: Vec<u8> = iv
+///     .iter()
+///     .chain(&plaintext.map(|b| b ^ 0x42))
+///     .copied()
+///     .collect();
+///
+/// let recovered = padding_oracle::PaddingOracleAttack::new(&ciphertext, 8)
+///     .unwrap()
+///     .into_blocks(oracle)
+///     .next()
+///     .unwrap()
+///     .unwrap();
+///
+/// assert_eq!(recovered.1, plaintext);
+/// ```
+pub fn local_oracle<const B: usize, D>(
+    mut decrypt: D,
+    scheme: PaddingScheme,
+) -> impl FnMut(&[u8]) -> bool
+where
+    D: FnMut(&[u8; B]) -> [u8; B],
+{
+    move |ciphertext| {
+        if ciphertext.len() < 2 * B || !ciphertext.len().is_multiple_of(B) {
+            return false;
+        }
+
+        let (prev, last) = ciphertext[ciphertext.len() - 2 * B..].split_at(B);
+        let block: &[u8; B] = last.try_into().expect("the block has the right size");
+
+        let plaintext: Vec<u8> = decrypt(block)
+            .iter()
+            .zip(prev)
+            .map(|(d, p)| d ^ p)
+            .collect();
+
+        scheme.is_valid(&plaintext)
+    }
+}
+
+/// Build an RSA oracle out of a private key, for the attacks of [rsa_pkcs1](crate::rsa_pkcs1)
+/// and [rsa_oaep](crate::rsa_oaep).
+///
+/// The oracle decrypts the ciphertext with the private exponent, and passes the message to
+/// `check`, as big-endian bytes as long as the modulus. It rejects ciphertexts which are not as
+/// long as the modulus.
+///
+/// # Example
+/// ```
+/// use padding_oracle::{rsa_pkcs1, testing::local_rsa_oracle};
+///
+/// # let (modulus, private_exponent) = ([0xffu8; 16], [1u8]);
+/// // An oracle only checking that the message starts with `0x00 0x02`
+/// let oracle = local_rsa_oracle(&modulus, &private_exponent, |message| {
+///     message.starts_with(&[0x00, 0x02])
+/// });
+///
+/// // Or a strict one
+/// let oracle = local_rsa_oracle(&modulus, &private_exponent, |message| {
+///     rsa_pkcs1::unpad(message).is_some()
+/// });
+/// ```
+pub fn local_rsa_oracle(
+    modulus: &[u8],
+    private_exponent: &[u8],
+    mut check: impl FnMut(&[u8]) -> bool,
+) -> impl FnMut(&[u8]) -> bool {
+    let n = BigUint::from_bytes_be(modulus);
+    let d = BigUint::from_bytes_be(private_exponent);
+    let k = n.bits().div_ceil(8);
+
+    move |ciphertext| {
+        if ciphertext.len() != k {
+            return false;
+        }
+
+        let message = BigUint::from_bytes_be(ciphertext).pow_mod(&d, &n);
+        check(&message.to_bytes_be(k))
+    }
+}
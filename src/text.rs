@@ -0,0 +1,63 @@
+//! What text looks like, to guess plaintext bytes in a smarter order.
+
+/// The most common bytes of English text and source code, most common first
+const COMMON: &[u8] =
+    b" etaoinsrhldcumfpgwybvkxjqzETAOINSRHLDCUMFPGWYBVKXJQZ0123456789.,'\"-:;!?()/\n\r\t_=&@#$%*+<>[]{}|\\^`~";
+
+/// The most common pairs of letters in English, most common first
+pub(crate) const BIGRAMS: &str = "\
+    th he in er an re on at en nd ti es or te of ed is it al ar st to nt ng se ha as ou io le ve \
+    co me de hi ri ro ic ne ea ra ce li ch ll be ma si om ur ca el ta la ns di fo ho pe ec pr no \
+    ct us ac ot il tr ly nc et ut ss so rs un lo wa ge ie wh ee wi em ad ol rt po we na ul ni ts \
+    mo ow pa im mi ai sh ir su id os iv ia am fi ci vi pl ig tu ev ld ry mp fe bl ab gh ty op wo \
+    sa ay ex ke fr oo av ag if ap gr od bo sp rd do uc bu ei ov by rm ep tt oc fa ef cu rn sc gi \
+    da yo cr cl du ga qu ue ff ba ey ls va um pp ua up lu go ht ru ug ds lt pi rc rr eg au ck ew \
+    mu br bi pt ak pu ui rg ib tl ny ki rk ys ob mm fu ph og ms ye ud mb ip ub oi rl gu dr hr cc \
+    tw ft wn nu af hu nn eo vo rv nf xp gn sm fl iz ok nl my gl aw ju oa eq sy sl ps jo lf nv je \
+    nk kn gs dy hy ze ks xt bs ik dd cy rp sk xi oe oy ws lv dl rf eu dg wr xa yi nm eb rb tm xc \
+    eh tc gy ja hn yp za";
+
+/// Letters often starting a word, most common first
+pub(crate) const WORD_START: &[u8] = b"taoswcbpihfmdrelngu";
+
+/// Letters often ending a word, most common first
+pub(crate) const WORD_END: &[u8] = b"esdtnyrolfhagkmwpcxiubv";
+
+/// Every byte value, the ones most common in text first, then padding bytes, then the others
+pub(crate) const LIKELY_BYTES: [u8; 256] = likely_bytes();
+
+const fn likely_bytes() -> [u8; 256] {
+    let mut order = [0u8; 256];
+    let mut seen = [false; 256];
+    let mut n = 0;
+
+    let mut i = 0;
+    while i < COMMON.len() {
+        order[n] = COMMON[i];
+        seen[COMMON[i] as usize] = true;
+        n += 1;
+        i += 1;
+    }
+
+    // Padding bytes, in the last block
+    let mut byte = 1;
+    while byte <= 16 {
+        if !seen[byte] {
+            order[n] = byte as u8;
+            seen[byte] = true;
+            n += 1;
+        }
+        byte += 1;
+    }
+
+    let mut byte = 0;
+    while byte < 256 {
+        if !seen[byte] {
+            order[n] = byte as u8;
+            n += 1;
+        }
+        byte += 1;
+    }
+
+    order
+}
@@ -0,0 +1,251 @@
+//! Turn response times into a padding oracle.
+
+use std::time::Duration;
+use std::vec::Vec;
+
+use crate::{OracleResult, PaddingOracle};
+
+/// A padding oracle leaking through response times, such as a server taking longer to answer
+/// when the padding is valid and the MAC gets checked.
+///
+/// The oracle is calibrated against the response times of ciphertexts known to have a valid and
+/// an invalid padding. Every query then measures the probe several times, and compares these
+/// times to both references with Welch's t-test: the padding is valid if the times differ from
+/// the invalid reference but not from the valid one, and invalid the other way around. When the
+/// test can't tell, the answer is [OracleResult::Inconclusive], so set
+/// [DecryptOptions::retries](crate::DecryptOptions::retries) to measure again.
+///
+/// # Example
+/// ```no_run
+/// use std::time::{Duration, Instant};
+///
+/// use padding_oracle::{DecryptOptions, TimingOptions, TimingOracle};
+///
+/// # fn send(_: &[u8]) {}
+/// # let (ciphertext, invalid) = ([0u8; 48], [0u8; 48]);
+/// let measure = |probe: &[u8]| {
+///     let start = Instant::now();
+///     send(probe);
+///     start.elapsed()
+/// };
+///
+/// let options = TimingOptions::default();
+/// let oracle = TimingOracle::calibrate(measure, &ciphertext, &invalid, &options);
+///
+/// let decrypt_options = DecryptOptions {
+///     retries: 5,
+///     ..Default::default()
+/// };
+///
+/// let plaintext = padding_oracle::decrypt_with_options(&ciphertext, 16, oracle, &decrypt_options);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TimingOracle<F> {
+    measure: F,
+    samples: usize,
+    confidence: f64,
+    valid: Sample,
+    invalid: Sample,
+}
+
+/// Options of a [TimingOracle].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimingOptions {
+    /// How many times each probe is measured.
+    ///
+    /// Defaults to `10`.
+    pub samples: usize,
+
+    /// How many times each reference ciphertext is measured, when calibrating.
+    ///
+    /// Defaults to `100`.
+    pub calibration_samples: usize,
+
+    /// How confident the test has to be that times differ from a reference, between `0` and `1`.
+    /// Higher values make wrong answers rarer, and inconclusive ones more common.
+    ///
+    /// Defaults to `0.99`.
+    pub confidence: f64,
+}
+
+impl Default for TimingOptions {
+    fn default() -> Self {
+        Self {
+            samples: 10,
+            calibration_samples: 100,
+            confidence: 0.99,
+        }
+    }
+}
+
+impl<F: FnMut(&[u8]) -> Duration> TimingOracle<F> {
+    /// Calibrate the oracle by measuring a ciphertext with a valid padding and one with an
+    /// invalid padding.
+    ///
+    /// # Panics
+    /// If [calibration_samples](TimingOptions::calibration_samples) or
+    /// [samples](TimingOptions::samples) is less than `2`.
+    pub fn calibrate(
+        mut measure: F,
+        valid: &[u8],
+        invalid: &[u8],
+        options: &TimingOptions,
+    ) -> Self {
+        let mut reference = |ciphertext: &[u8]| -> Vec<Duration> {
+            (0..options.calibration_samples)
+                .map(|_| measure(ciphertext))
+                .collect()
+        };
+        let (valid, invalid) = (reference(valid), reference(invalid));
+
+        Self::from_samples(measure, &valid, &invalid, options)
+    }
+
+    /// Use response times already measured for valid and invalid paddings as references.
+    ///
+    /// # Panics
+    /// If `valid` or `invalid` has less than two samples, or
+    /// [samples](TimingOptions::samples) is less than `2`.
+    pub fn from_samples(
+        measure: F,
+        valid: &[Duration],
+        invalid: &[Duration],
+        options: &TimingOptions,
+    ) -> Self {
+        assert!(
+            valid.len() > 1 && invalid.len() > 1,
+            "the references need at least two samples each"
+        );
+        assert!(options.samples > 1, "probes need at least two samples each");
+
+        Self {
+            measure,
+            samples: options.samples,
+            confidence: options.confidence,
+            valid: Sample::new(valid),
+            invalid: Sample::new(invalid),
+        }
+    }
+}
+
+impl<F: FnMut(&[u8]) -> Duration> PaddingOracle for TimingOracle<F> {
+    type Response = OracleResult;
+
+    fn query(&mut self, ciphertext: &[u8]) -> OracleResult {
+        let times: Vec<Duration> = (0..self.samples)
+            .map(|_| (self.measure)(ciphertext))
+            .collect();
+        let probe = Sample::new(&times);
+
+        let like_valid = !probe.differs(&self.valid, self.confidence);
+        let like_invalid = !probe.differs(&self.invalid, self.confidence);
+
+        match (like_valid, like_invalid) {
+            (true, false) => OracleResult::PaddingValid,
+            (false, true) => OracleResult::PaddingInvalid,
+            _ => OracleResult::Inconclusive,
+        }
+    }
+}
+
+/// The mean and variance of response times, in seconds
+#[derive(Debug, Clone)]
+struct Sample {
+    len: f64,
+    mean: f64,
+    variance: f64,
+}
+
+impl Sample {
+    fn new(times: &[Duration]) -> Self {
+        let len = times.len() as f64;
+        let mean = times.iter().map(Duration::as_secs_f64).sum::<f64>() / len;
+        let variance = times
+            .iter()
+            .map(|time| (time.as_secs_f64() - mean).powi(2))
+            .sum::<f64>()
+            / (len - 1.0);
+
+        Self {
+            len,
+            mean,
+            variance,
+        }
+    }
+
+    /// Whether Welch's t-test tells the means apart, at the given confidence
+    fn differs(&self, other: &Self, confidence: f64) -> bool {
+        let (a, b) = (self.variance / self.len, other.variance / other.len);
+        let difference = (self.mean - other.mean).abs();
+
+        if a + b == 0.0 {
+            return difference > 0.0;
+        }
+
+        // Welch-Satterthwaite degrees of freedom
+        let freedom =
+            (a + b).powi(2) / (a.powi(2) / (self.len - 1.0) + b.powi(2) / (other.len - 1.0));
+
+        difference / (a + b).sqrt() > student_quantile(0.5 + confidence / 2.0, freedom)
+    }
+}
+
+/// The quantile of Student's t-distribution, from the normal one with Cornish-Fisher's expansion
+fn student_quantile(p: f64, freedom: f64) -> f64 {
+    let z = normal_quantile(p);
+    let (z3, z5) = (z.powi(3), z.powi(5));
+
+    z + (z3 + z) / (4.0 * freedom)
+        + (5.0 * z5 + 16.0 * z3 + 3.0 * z) / (96.0 * freedom.powi(2))
+        + (3.0 * z.powi(7) + 19.0 * z5 + 17.0 * z3 - 15.0 * z) / (384.0 * freedom.powi(3))
+}
+
+/// The quantile of the standard normal distribution, see "An algorithm for computing the inverse
+/// normal cumulative distribution function", P. J. Acklam
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+
+    let p = p.clamp(1e-300, 1.0 - 1e-16);
+    let polynomial = |coefficients: &[f64], x: f64| coefficients.iter().fold(0.0, |y, c| y * x + c);
+
+    if p < 0.02425 {
+        let q = (-2.0 * p.ln()).sqrt();
+        polynomial(&C, q) / (polynomial(&D, q) * q + 1.0)
+    } else if p <= 1.0 - 0.02425 {
+        let q = p - 0.5;
+        let r = q * q;
+        polynomial(&A, r) * q / (polynomial(&B, r) * r + 1.0)
+    } else {
+        let q = (-2.0 * (1.0 - p).ln()).sqrt();
+        -polynomial(&C, q) / (polynomial(&D, q) * q + 1.0)
+    }
+}
@@ -0,0 +1,78 @@
+//! Encode or frame the probes before they reach the oracle.
+
+use alloc::vec::Vec;
+
+use crate::PaddingOracle;
+
+/// An oracle passing every probe through a transform before sending it to another oracle.
+///
+/// The transform turns the raw ciphertext into what the target expects, such as base64 in a
+/// cookie, a JSON envelope or a length field, so the inner oracle can stay a thin transport.
+/// Unlike [decrypt_with_hook](crate::decrypt_with_hook), this works with every way of running
+/// the attack, and with asynchronous oracles when the `async` feature is enabled.
+///
+/// # Example
+/// ```
+/// use padding_oracle::Transformed;
+///
+/// # fn send(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 32];
+/// // Prepend the length of the ciphertext, as a big-endian u16
+/// let oracle = Transformed::new(send, |probe: &[u8]| {
+///     [&(probe.len() as u16).to_be_bytes()[..], probe].concat()
+/// });
+///
+/// let plaintext = padding_oracle::decrypt(&ciphertext, 16, oracle);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Transformed<O, F> {
+    inner: O,
+    transform: F,
+}
+
+impl<O, F> Transformed<O, F>
+where
+    F: FnMut(&[u8]) -> Vec<u8>,
+{
+    /// Send `transform(probe)` to `inner` for every probe.
+    pub fn new(inner: O, transform: F) -> Self {
+        Self { inner, transform }
+    }
+
+    /// The inner oracle.
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+}
+
+impl<O, F> PaddingOracle for Transformed<O, F>
+where
+    O: PaddingOracle,
+    F: FnMut(&[u8]) -> Vec<u8>,
+{
+    type Response = O::Response;
+
+    fn query(&mut self, ciphertext: &[u8]) -> O::Response {
+        let message = (self.transform)(ciphertext);
+
+        self.inner.query(&message)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<O, F> crate::AsyncPaddingOracle for Transformed<O, F>
+where
+    O: crate::AsyncPaddingOracle,
+    F: FnMut(&[u8]) -> Vec<u8>,
+{
+    type Response = O::Response;
+
+    fn query(
+        &mut self,
+        ciphertext: Vec<u8>,
+    ) -> impl core::future::Future<Output = O::Response> + use<O, F> {
+        let message = (self.transform)(&ciphertext);
+
+        self.inner.query(message)
+    }
+}
@@ -0,0 +1,212 @@
+//! Small helpers to work with the recovered plaintexts.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Compare two plaintexts and return the positions of the bytes that differ.
+///
+/// This is useful to cross-validate plaintexts recovered from the same ciphertext through two
+/// different oracles: any mismatch means at least one of them lied.
+/// If the lengths differ, every position past the end of the shortest plaintext is a mismatch.
+///
+/// # Example
+/// ```
+/// use padding_oracle::util::diff_plaintexts;
+///
+/// assert_eq!(diff_plaintexts(b"user=admin", b"user=guest"), vec![5, 6, 7, 8, 9]);
+/// assert!(diff_plaintexts(b"same", b"same").is_empty());
+/// ```
+pub fn diff_plaintexts(a: &[u8], b: &[u8]) -> Vec<usize> {
+    (0..a.len().max(b.len()))
+        .filter(|&i| a.get(i) != b.get(i))
+        .collect()
+}
+
+/// Encode bytes as lowercase hexadecimal.
+///
+/// # Example
+/// ```
+/// assert_eq!(padding_oracle::util::to_hex(b"\x00\xffA"), "00ff41");
+/// ```
+pub fn to_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    bytes
+        .iter()
+        .flat_map(|b| {
+            [
+                DIGITS[(b >> 4) as usize] as char,
+                DIGITS[(b & 0xf) as usize] as char,
+            ]
+        })
+        .collect()
+}
+
+/// Decode hexadecimal, in either case, or `None` if it isn't valid.
+///
+/// # Example
+/// ```
+/// use padding_oracle::util::from_hex;
+///
+/// assert_eq!(from_hex("00ffA1"), Some(b"\x00\xff\xa1".to_vec()));
+/// assert_eq!(from_hex("abc"), None);
+/// ```
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Render partially recovered plaintext as text, without ever panicking.
+///
+/// A recovered prefix or suffix can start or end in the middle of a multibyte character. An
+/// incomplete sequence at either end is shown as `…`, and any other invalid sequence as `�`.
+///
+/// # Example
+/// ```
+/// use padding_oracle::util::display_partial;
+///
+/// // "é" is encoded as "\xc3\xa9"
+/// assert_eq!(display_partial(b"caf\xc3"), "caf…");
+/// assert_eq!(display_partial(b"\xa9 au lait"), "… au lait");
+/// assert_eq!(display_partial(b"a\xffb"), "a�b");
+/// assert_eq!(display_partial(b"caf\xc3 au lait"), "caf� au lait");
+/// ```
+pub fn display_partial(bytes: &[u8]) -> String {
+    let mut text = String::new();
+
+    // Continuation bytes at the start belong to a character cut in half
+    let start = bytes
+        .iter()
+        .take(3)
+        .take_while(|&&b| b & 0xc0 == 0x80)
+        .count();
+
+    if start > 0 {
+        text.push('…');
+    }
+
+    let mut chunks = bytes[start..].utf8_chunks().peekable();
+
+    while let Some(chunk) = chunks.next() {
+        text.push_str(chunk.valid());
+
+        if !chunk.invalid().is_empty() {
+            // Only the last chunk can be an incomplete, rather than invalid, sequence
+            let incomplete = chunks.peek().is_none()
+                && core::str::from_utf8(chunk.invalid())
+                    .err()
+                    .is_some_and(|e| e.error_len().is_none());
+
+            text.push(if incomplete { '…' } else { '�' });
+        }
+    }
+
+    text
+}
+
+/// Shannon entropy of some bytes, in bits per byte.
+///
+/// Text sits well below 5 bits per byte, while random-looking bytes get close to the maximum for
+/// their length. A high entropy block amid text is likely a failed recovery.
+///
+/// # Example
+/// ```
+/// use padding_oracle::util::entropy;
+///
+/// assert_eq!(entropy(b"aaaa"), 0.0);
+/// assert_eq!(entropy(b"abcd"), 2.0);
+/// ```
+#[cfg(feature = "std")]
+pub fn entropy(bytes: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+
+    for &b in bytes {
+        counts[b as usize] += 1;
+    }
+
+    let len = bytes.len() as f64;
+
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / len;
+            -p * p.log2()
+        })
+        .sum::<f64>()
+        .max(0.0)
+}
+
+/// Blocks that two recovered plaintexts have in common, see [compare_recoveries].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockComparison {
+    /// Pairs of plaintext block indices `(a, b)` holding the same plaintext.
+    pub shared: Vec<(usize, usize)>,
+
+    /// Pairs of plaintext block indices `(a, b)` which must decrypt the same way, because they
+    /// come from the same ciphertext block, but which were recovered differently.
+    pub inconsistent: Vec<(usize, usize)>,
+}
+
+/// Compare the plaintexts recovered from two ciphertexts encrypted under the same key.
+///
+/// The ciphertexts have their IV prepended, and the plaintexts are as returned by
+/// [decrypt](crate::decrypt). Identical ciphertext blocks always have the same intermediate
+/// decryption state, so their recoveries must agree: when they don't, at least one of the
+/// attacks went wrong.
+///
+/// Only the blocks found in both a plaintext and its ciphertext are compared, so a plaintext
+/// longer than its ciphertext without the IV, or a blocksize of `0`, compare fewer blocks rather
+/// than failing.
+pub fn compare_recoveries(
+    ciphertext_a: &[u8],
+    plaintext_a: &[u8],
+    ciphertext_b: &[u8],
+    plaintext_b: &[u8],
+    blocksize: usize,
+) -> BlockComparison {
+    let mut comparison = BlockComparison::default();
+    if blocksize == 0 {
+        return comparison;
+    }
+
+    // Only the plaintext blocks with a ciphertext block, the IV excluded
+    let blocks_a = ciphertext_a.len() / blocksize;
+    let blocks_b = ciphertext_b.len() / blocksize;
+    let plaintext_a = plaintext_a
+        .chunks_exact(blocksize)
+        .take(blocks_a.saturating_sub(1));
+
+    for (i, block_a) in plaintext_a.enumerate() {
+        let plaintext_b = plaintext_b.chunks_exact(blocksize);
+
+        for (j, block_b) in plaintext_b.take(blocks_b.saturating_sub(1)).enumerate() {
+            if block_a == block_b {
+                comparison.shared.push((i, j));
+            }
+
+            let cipher_a = &ciphertext_a[(i + 1) * blocksize..(i + 2) * blocksize];
+            let cipher_b = &ciphertext_b[(j + 1) * blocksize..(j + 2) * blocksize];
+
+            if cipher_a == cipher_b {
+                let prev_a = &ciphertext_a[i * blocksize..(i + 1) * blocksize];
+                let prev_b = &ciphertext_b[j * blocksize..(j + 1) * blocksize];
+
+                let consistent =
+                    (0..blocksize).all(|k| block_a[k] ^ prev_a[k] == block_b[k] ^ prev_b[k]);
+
+                if !consistent {
+                    comparison.inconsistent.push((i, j));
+                }
+            }
+        }
+    }
+
+    comparison
+}
@@ -0,0 +1,158 @@
+//! Sanity checks on the oracle, to fail fast on setup mistakes.
+
+use alloc::vec::Vec;
+
+use crate::oracle::answer;
+use crate::{Error, Layout, PaddingOracle, PaddingOracleAttack, PaddingScheme, Result};
+
+/// Send a few probes to make sure the oracle, the blocksize and the encoding are right before
+/// running the whole attack.
+///
+/// This checks that:
+/// - The oracle accepts the ciphertext, with the IV prepended.
+/// - It rejects the ciphertext once its last byte of plaintext is garbled, so it really reports
+///   padding errors.
+/// - It still accepts the ciphertext without its first block, when there are at least two blocks
+///   after the IV, so the blocksize matches the cipher. Skip this check by truncating the
+///   ciphertext to the IV and a block if the oracle rejects shorter ciphertexts.
+///
+/// This costs at most 3 queries. Use [DecryptOptions::precheck](crate::DecryptOptions::precheck)
+/// to run it before the attack.
+///
+/// # Example
+/// ```no_run
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 48];
+/// if let Err(e) = padding_oracle::verify_oracle(&ciphertext, 16, oracle) {
+///     eprintln!("The oracle doesn't look right: {e}");
+/// }
+/// ```
+pub fn verify_oracle(
+    ciphertext: &[u8],
+    blocksize: usize,
+    oracle: impl PaddingOracle,
+) -> Result<()> {
+    check_oracle(ciphertext, blocksize, oracle, PaddingScheme::Pkcs7, true)
+}
+
+/// The checks of [DecryptOptions::precheck](crate::DecryptOptions::precheck), on probes laid out
+/// the way the attack sends them
+pub(crate) fn precheck(attack: &PaddingOracleAttack, mut oracle: impl PaddingOracle) -> Result<()> {
+    for check in precheck_probes(attack)? {
+        check.expect(answer(oracle.query(&check.probe))?)?;
+    }
+
+    Ok(())
+}
+
+/// Whether the attack must run the checks of
+/// [DecryptOptions::precheck](crate::DecryptOptions::precheck) before its first query
+pub(crate) fn needs_precheck(attack: &PaddingOracleAttack) -> bool {
+    attack.options().precheck && attack.queries() == 0
+}
+
+/// The probes of [precheck], framed the way the attack sends them
+pub(crate) fn precheck_probes(attack: &PaddingOracleAttack) -> Result<Vec<Check>> {
+    let (ciphertext, bs) = (attack.ciphertext(), attack.blocksize());
+    let options = attack.options();
+
+    let (ciphertext, alignment) = match options.layout {
+        Layout::Truncated => (ciphertext, true),
+        // Only the last two blocks are ever sent together
+        Layout::TwoBlocks => (
+            &ciphertext[ciphertext.len().saturating_sub(2 * bs)..],
+            false,
+        ),
+        Layout::FixedLength { .. } => (ciphertext, false),
+    };
+
+    let mut checks = checks(ciphertext, bs, options.padding, alignment)?;
+
+    if !options.prefix.is_empty() || !options.suffix.is_empty() {
+        for check in &mut checks {
+            check.probe = [&options.prefix[..], &check.probe, &options.suffix].concat();
+        }
+    }
+
+    Ok(checks)
+}
+
+pub(crate) fn check_oracle(
+    ciphertext: &[u8],
+    blocksize: usize,
+    mut oracle: impl PaddingOracle,
+    padding: PaddingScheme,
+    alignment: bool,
+) -> Result<()> {
+    for check in checks(ciphertext, blocksize, padding, alignment)? {
+        check.expect(answer(oracle.query(&check.probe))?)?;
+    }
+
+    Ok(())
+}
+
+/// A probe checking the oracle
+pub(crate) struct Check {
+    pub(crate) probe: Vec<u8>,
+
+    /// What a working oracle answers
+    valid: bool,
+
+    /// The error if it doesn't
+    failure: fn(usize) -> Error,
+    blocksize: usize,
+}
+
+impl Check {
+    /// Fail if the oracle gave the wrong answer
+    pub(crate) fn expect(&self, valid: bool) -> Result<()> {
+        if valid == self.valid {
+            Ok(())
+        } else {
+            Err((self.failure)(self.blocksize))
+        }
+    }
+}
+
+/// The probes checking the oracle, in the order to send them
+fn checks(
+    ciphertext: &[u8],
+    blocksize: usize,
+    padding: PaddingScheme,
+    alignment: bool,
+) -> Result<Vec<Check>> {
+    crate::check_blocksize(blocksize)?;
+
+    if !ciphertext.len().is_multiple_of(blocksize) || ciphertext.len() < 2 * blocksize {
+        return Err(Error::WrongSize {
+            blocksize,
+            found: ciphertext.len(),
+        });
+    }
+
+    let check = |probe: Vec<u8>, valid, failure| Check {
+        probe,
+        valid,
+        failure,
+        blocksize,
+    };
+
+    let mut checks = alloc::vec![check(ciphertext.to_vec(), true, |_| {
+        Error::RejectedCiphertext
+    })];
+
+    // The last byte of plaintext is part of a valid padding, so it can't be valid anymore with a
+    // bit flipped
+    let mut probe = ciphertext.to_vec();
+    probe[ciphertext.len() - blocksize - 1] ^= padding.breaking_mask();
+    checks.push(check(probe, false, |_| Error::OracleAlwaysValid));
+
+    // The first block becomes the IV, which leaves the padding untouched
+    if alignment && ciphertext.len() >= 3 * blocksize {
+        checks.push(check(ciphertext[blocksize..].to_vec(), true, |blocksize| {
+            Error::Misaligned { blocksize }
+        }));
+    }
+
+    Ok(checks)
+}
@@ -0,0 +1,73 @@
+//! Outvote the spurious answers of noisy oracles.
+
+use alloc::boxed::Box;
+use core::error::Error;
+
+use crate::{OracleResponse, OracleResult, PaddingOracle};
+
+/// An oracle asking another one several times, and answering what most of its answers were.
+///
+/// Each query is repeated up to `votes` times, and stops as soon as one answer has the majority,
+/// so when the oracle is consistent, an odd number of votes only costs `votes / 2 + 1` queries.
+/// The inconclusive answers of the inner oracle don't count as votes, and the answer is
+/// [OracleResult::Inconclusive] if no answer gets the majority of the votes. Errors of the
+/// inner oracle are returned right away.
+///
+/// # Example
+/// ```
+/// use padding_oracle::MajorityVote;
+///
+/// # fn oracle(_: &[u8]) -> bool { false }
+/// # let ciphertext = [0u8; 32];
+/// // Asks up to 5 times, 3 when the first answers agree
+/// let plaintext = padding_oracle::decrypt(&ciphertext, 16, MajorityVote::new(oracle, 5));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MajorityVote<O> {
+    inner: O,
+    votes: usize,
+}
+
+impl<O: PaddingOracle> MajorityVote<O> {
+    /// Ask `inner` up to `votes` times for each query.
+    ///
+    /// # Panics
+    /// If `votes` is `0`.
+    pub fn new(inner: O, votes: usize) -> Self {
+        assert!(votes > 0, "there must be at least one vote");
+
+        Self { inner, votes }
+    }
+
+    /// The inner oracle.
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+}
+
+impl<O: PaddingOracle> PaddingOracle for MajorityVote<O> {
+    type Response = Result<OracleResult, Box<dyn Error + Send + Sync>>;
+
+    fn query(&mut self, ciphertext: &[u8]) -> Self::Response {
+        let majority = self.votes / 2 + 1;
+        let (mut valid, mut invalid) = (0, 0);
+
+        for _ in 0..self.votes {
+            match self.inner.query(ciphertext).into_result()? {
+                OracleResult::PaddingValid => valid += 1,
+                OracleResult::PaddingInvalid => invalid += 1,
+                OracleResult::Inconclusive => {}
+            }
+
+            if valid >= majority {
+                return Ok(OracleResult::PaddingValid);
+            }
+
+            if invalid >= majority {
+                return Ok(OracleResult::PaddingInvalid);
+            }
+        }
+
+        Ok(OracleResult::Inconclusive)
+    }
+}
@@ -3,6 +3,7 @@ use aes::cipher::{
     block_padding::{Pkcs7, RawPadding},
     BlockDecryptMut, BlockEncryptMut, KeyIvInit,
 };
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// We're using predictable values here to avoid having random behaviors in our tests
 const KEY: [u8; 16] = [0u8; 16];
@@ -112,3 +113,96 @@ fn it_can_decrypt_aes_cbc_9() {
 
     test_aes_cbc(plaintext);
 }
+
+/// Two full blocks and a single byte: the last block carries 15 bytes of padding
+#[test]
+fn it_can_decrypt_aes_cbc_near_full_padding() {
+    let plaintext = b"000010Will it ever stop? Yo, I do";
+
+    assert_eq!(plaintext.len(), 2 * 16 + 1);
+    test_aes_cbc(plaintext);
+}
+
+/// Two full blocks and 15 bytes: the last block carries a single byte of padding
+#[test]
+fn it_can_decrypt_aes_cbc_near_empty_padding() {
+    let plaintext = b"000011Ice Ice Baby, too cold, too cold, Vanilla";
+
+    assert_eq!(plaintext.len(), 2 * 16 + 15);
+    test_aes_cbc(plaintext);
+}
+
+static LAST_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// An oracle rejecting replayed requests, which must start with an increasing counter
+fn anti_replay_oracle(request: &[u8]) -> bool {
+    let (counter, ciphertext) = request.split_at(8);
+    let counter = u64::from_be_bytes(counter.try_into().unwrap());
+
+    if LAST_COUNTER.fetch_max(counter, Ordering::SeqCst) >= counter {
+        return false;
+    }
+
+    oracle(ciphertext)
+}
+
+#[test]
+fn it_can_decrypt_aes_cbc_with_a_probe_hook() {
+    let plaintext = b"000000Now that the party is jumping";
+
+    let mut ciphertext = vec![0u8; (plaintext.len() / 16 + 1) * 16];
+
+    ciphertext[..plaintext.len()].copy_from_slice(plaintext);
+
+    let ciphertext = Aes128CbcEnc::new(&KEY.into(), &IV.into())
+        .encrypt_padded_mut::<Pkcs7>(&mut ciphertext, plaintext.len())
+        .unwrap();
+
+    let mut iv = IV.to_vec();
+
+    iv.extend_from_slice(ciphertext);
+
+    let mut counter = 0u64;
+
+    let plaintext2 = padding_oracle::decrypt_with_hook(&iv, 16, anti_replay_oracle, |probe| {
+        counter += 1;
+        probe.splice(0..0, counter.to_be_bytes());
+    })
+    .unwrap();
+
+    assert_eq!(plaintext, Pkcs7::raw_unpad(plaintext2.as_slice()).unwrap());
+}
+
+#[test]
+fn it_can_decrypt_aes_cbc_with_a_stateful_closure() {
+    let plaintext = b"000001With the bass kicked in and the Vega's are pumpin'";
+    let key = [0x42u8; 16];
+
+    let mut ciphertext = vec![0u8; (plaintext.len() / 16 + 1) * 16];
+
+    ciphertext[..plaintext.len()].copy_from_slice(plaintext);
+
+    let ciphertext = Aes128CbcEnc::new(&key.into(), &IV.into())
+        .encrypt_padded_mut::<Pkcs7>(&mut ciphertext, plaintext.len())
+        .unwrap();
+
+    let mut iv = IV.to_vec();
+
+    iv.extend_from_slice(ciphertext);
+
+    // The oracle captures its key, and counts the queries
+    let mut queries = 0;
+
+    let plaintext2 = padding_oracle::decrypt(&iv, 16, |ciphertext: &[u8]| {
+        queries += 1;
+
+        let mut buf = ciphertext.to_vec();
+        Aes128CbcDec::new(&key.into(), &IV.into())
+            .decrypt_padded_mut::<Pkcs7>(&mut buf)
+            .is_ok()
+    })
+    .unwrap();
+
+    assert!(queries > 0);
+    assert_eq!(plaintext, Pkcs7::raw_unpad(plaintext2.as_slice()).unwrap());
+}
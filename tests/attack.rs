@@ -0,0 +1,369 @@
+mod common;
+
+use common::{blocks, encrypt, oracle, pad};
+use padding_oracle::{Error, PaddingOracleAttack};
+
+#[test]
+fn it_can_be_driven_manually() {
+    let plaintext = b"000000Now that the party is jumping";
+    let mut attack = PaddingOracleAttack::new(&encrypt(plaintext), 16).unwrap();
+
+    while let Some(probe) = attack.next_probe() {
+        attack.submit(oracle(&probe));
+    }
+
+    assert!(attack.is_finished());
+    assert_eq!(attack.finish().unwrap(), pad(plaintext));
+}
+
+#[test]
+fn it_can_decrypt_into_a_buffer() {
+    let plaintext = b"000000Now that the party is jumping";
+    let ciphertext = encrypt(plaintext);
+
+    let mut output = [0u8; 64];
+    let len = padding_oracle::decrypt_into(&ciphertext, 16, oracle, &mut output).unwrap();
+    assert_eq!(output[..len], pad(plaintext));
+
+    let mut queries = 0;
+    let result = padding_oracle::decrypt_into(
+        &ciphertext,
+        16,
+        |probe: &[u8]| {
+            queries += 1;
+            oracle(probe)
+        },
+        &mut output[..32],
+    );
+
+    assert!(matches!(
+        result,
+        Err(Error::LengthMismatch {
+            expected: 48,
+            found: 32
+        })
+    ));
+    assert_eq!(queries, 0);
+}
+
+#[test]
+fn it_returns_the_same_probe_until_submitted() {
+    let attack = PaddingOracleAttack::new(&encrypt(b"YELLOW SUBMARINE"), 16).unwrap();
+
+    assert_eq!(attack.next_probe(), attack.next_probe());
+}
+
+#[test]
+fn it_can_reuse_a_probe_buffer() {
+    let plaintext = b"000000Now that the party is jumping";
+    let mut attack = PaddingOracleAttack::new(&encrypt(plaintext), 16).unwrap();
+    let mut probe = vec![0xff; 100];
+
+    while attack.next_probe_into(&mut probe) {
+        assert_eq!(attack.next_probe().as_ref(), Some(&probe));
+        attack.submit(oracle(&probe));
+    }
+
+    assert_eq!(attack.next_probe(), None);
+    assert_eq!(attack.finish().unwrap(), pad(plaintext));
+}
+
+#[test]
+fn it_can_be_cancelled_and_resumed() {
+    use std::cell::Cell;
+    use std::sync::atomic::AtomicBool;
+
+    let plaintext = b"000000Now that the party is jumping";
+    let ciphertext = encrypt(plaintext);
+
+    let cancelled = AtomicBool::new(true);
+    let result = padding_oracle::decrypt_cancellable(&ciphertext, 16, oracle, &cancelled);
+    assert!(matches!(result, Err(Error::Cancelled { partial }) if partial.is_empty()));
+
+    // Cancel halfway through, then resume
+    let mut attack = PaddingOracleAttack::new(&ciphertext, 16).unwrap();
+    let queries = Cell::new(0);
+    let cancel = || queries.get() >= 300;
+    let error = attack.run(
+        |probe: &[u8]| {
+            queries.set(queries.get() + 1);
+            oracle(probe)
+        },
+        cancel,
+    );
+
+    assert!(matches!(error, Err(Error::Cancelled { .. })));
+    assert_eq!(queries.get(), 300);
+
+    attack.run(oracle, || false).unwrap();
+    assert_eq!(attack.finish().unwrap(), pad(plaintext));
+}
+
+#[test]
+fn it_returns_the_blocks_recovered_before_cancellation_in_any_order() {
+    use std::cell::Cell;
+
+    let plaintext = b"000000Now that the party is jumping";
+    let options = padding_oracle::DecryptOptions {
+        block_order: padding_oracle::BlockOrder::Forward,
+        ..Default::default()
+    };
+    let mut attack = PaddingOracleAttack::with_options(&encrypt(plaintext), 16, &options).unwrap();
+
+    // Cancel once the first block is recovered
+    let second_block = Cell::new(false);
+    let error = attack.run(
+        |probe: &[u8]| {
+            second_block.set(probe.len() > 32);
+            oracle(probe)
+        },
+        || second_block.get(),
+    );
+
+    let Err(Error::Cancelled { partial }) = error else {
+        panic!("unexpected result: {error:?}");
+    };
+    assert_eq!(partial, blocks(plaintext)[..1]);
+}
+
+#[test]
+fn it_tells_where_the_attack_failed() {
+    let ciphertext = encrypt(b"000000Now that the party is jumping");
+
+    let error = padding_oracle::decrypt(&ciphertext, 16, |_: &[u8]| false).unwrap_err();
+    assert!(matches!(
+        error,
+        Error::InvalidPadding {
+            block: 3,
+            byte: 15,
+            queries: 256,
+            false_positives: 0,
+        }
+    ));
+
+    // Every candidate is valid, until its double check flips the byte before it
+    let error =
+        padding_oracle::decrypt(&ciphertext, 16, |probe: &[u8]| probe[46] == ciphertext[46])
+            .unwrap_err();
+    assert!(matches!(
+        error,
+        Error::InvalidPadding {
+            block: 3,
+            byte: 15,
+            queries: 512,
+            false_positives: 256,
+        }
+    ));
+}
+
+#[test]
+fn it_salvages_the_plaintext_of_a_failed_attack() {
+    let plaintext = b"000000Now that the party is jumping";
+    let ciphertext = encrypt(plaintext);
+
+    // The server goes down once the last block is recovered
+    let failing_oracle = |probe: &[u8]| match probe.len() {
+        64 => Ok(oracle(probe)),
+        _ => Err("connection refused"),
+    };
+
+    let failure =
+        padding_oracle::decrypt_with_failure(&ciphertext, 16, failing_oracle, &Default::default())
+            .unwrap_err();
+
+    assert_eq!(failure.partial, blocks(plaintext)[2..]);
+    assert_eq!((failure.block, failure.byte), (Some(2), Some(15)));
+    assert!(matches!(*failure.source, Error::Oracle(_)));
+
+    let failure =
+        padding_oracle::decrypt_with_failure(&ciphertext[1..], 16, oracle, &Default::default())
+            .unwrap_err();
+    assert_eq!((failure.block, failure.byte), (None, None));
+}
+
+#[test]
+fn it_salvages_the_blocks_recovered_in_any_order() {
+    let plaintext = b"000000Now that the party is jumping";
+    let options = padding_oracle::DecryptOptions {
+        block_order: padding_oracle::BlockOrder::Forward,
+        ..Default::default()
+    };
+
+    // The server goes down once the first block is recovered
+    let failing_oracle = |probe: &[u8]| match probe.len() {
+        32 => Ok(oracle(probe)),
+        _ => Err("connection refused"),
+    };
+
+    let failure =
+        padding_oracle::decrypt_with_failure(&encrypt(plaintext), 16, failing_oracle, &options)
+            .unwrap_err();
+
+    assert_eq!(failure.partial, blocks(plaintext)[..1]);
+    assert_eq!(failure.block, Some(2));
+    assert!(matches!(*failure.source, Error::Oracle(_)));
+}
+
+#[test]
+fn it_refuses_to_finish_early() {
+    let mut attack = PaddingOracleAttack::new(&encrypt(b"YELLOW SUBMARINE"), 16).unwrap();
+
+    let probe = attack.next_probe().unwrap();
+    attack.submit(oracle(&probe));
+
+    assert!(matches!(attack.finish(), Err(Error::Unfinished)));
+}
+
+#[test]
+fn it_handles_an_iv_only_ciphertext() {
+    let mut attack = PaddingOracleAttack::new(&[0u8; 16], 16).unwrap();
+
+    assert!(attack.next_probe().is_none());
+    attack.submit(true);
+    assert_eq!(attack.finish().unwrap(), b"");
+}
+
+#[test]
+fn it_reports_a_failed_attack() {
+    let ciphertext = encrypt(b"000000Now that the party is jumping");
+    let mut attack = PaddingOracleAttack::new(&ciphertext, 16).unwrap();
+
+    // Answer truthfully for the last block only
+    while let Some(probe) = attack.next_probe() {
+        attack.submit(probe.len() == ciphertext.len() && oracle(&probe));
+    }
+
+    let report = attack.report();
+
+    assert_eq!(
+        report.plaintext,
+        pad(b"000000Now that the party is jumping")[32..]
+    );
+    assert_eq!(report.stats.block_queries.len(), 3);
+    assert_eq!(report.stats.block_queries[0], 0);
+    assert_eq!(report.stats.block_queries[1], 256);
+    assert_eq!(
+        report.stats.queries,
+        report.stats.block_queries.iter().sum::<usize>()
+    );
+    assert!(report.error.is_some());
+}
+
+#[test]
+fn it_reports_the_entropy_of_each_block() {
+    let mut attack =
+        PaddingOracleAttack::new(&encrypt(b"000000Now that the party is jumping"), 16).unwrap();
+
+    while let Some(probe) = attack.next_probe() {
+        attack.submit(oracle(&probe));
+    }
+
+    let entropy = attack.report().entropy;
+
+    assert_eq!(entropy.len(), 3);
+    // The last block is mostly padding
+    assert!(entropy[2] < entropy[0]);
+    assert!(entropy.iter().all(|e| (0.0..=4.0).contains(e)));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn it_serializes_the_report_as_json() {
+    let mut attack = PaddingOracleAttack::new(&encrypt(b"YELLOW SUBMARINE"), 16).unwrap();
+
+    while let Some(probe) = attack.next_probe() {
+        attack.submit(oracle(&probe));
+    }
+
+    let json = attack.report().to_json();
+
+    assert!(json
+        .starts_with(r#"{"blocksize":16,"plaintext":"59454c4c4f57205355424d4152494e4510101010"#));
+    assert!(json.ends_with(r#""error":null}"#));
+
+    let report: padding_oracle::AttackReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(report, attack.report());
+}
+
+#[test]
+fn it_returns_stats_with_the_plaintext() {
+    let plaintext = b"Hello world!!!";
+    let mut queries = 0;
+
+    let (recovered, stats) =
+        padding_oracle::decrypt_with_stats(&encrypt(plaintext), 16, |p: &[u8]| {
+            queries += 1;
+            oracle(p)
+        })
+        .unwrap();
+
+    assert_eq!(recovered, pad(plaintext));
+    assert_eq!(stats.queries, queries);
+    assert_eq!(stats.block_queries.iter().sum::<usize>(), queries);
+    assert!(stats.duration > std::time::Duration::ZERO);
+
+    // The IV is all zeros, so the first candidate for the last byte is the original one, which
+    // leaves the `\x02\x02` padding intact
+    assert_eq!(stats.false_positives, 1);
+}
+
+#[test]
+fn it_returns_the_intermediates_with_the_plaintext() {
+    let plaintext = b"000000Now that the party is jumping";
+    let ciphertext = encrypt(plaintext);
+
+    let (recovered, intermediates) =
+        padding_oracle::decrypt_with_intermediates(&ciphertext, 16, oracle).unwrap();
+    assert_eq!(recovered, pad(plaintext));
+    assert_eq!(intermediates.len(), 3);
+
+    for (block, intermediate) in intermediates.iter().enumerate() {
+        let xored: Vec<u8> = intermediate
+            .iter()
+            .zip(&ciphertext[block * 16..])
+            .map(|(i, c)| i ^ c)
+            .collect();
+        assert_eq!(xored, recovered[block * 16..(block + 1) * 16]);
+    }
+
+    // Known intermediates don't need the oracle anymore
+    let options = padding_oracle::DecryptOptions {
+        known_intermediates: intermediates
+            .into_iter()
+            .enumerate()
+            .map(|(i, d)| (i + 1, d))
+            .collect(),
+        ..Default::default()
+    };
+    let again = padding_oracle::decrypt_with_options(
+        &ciphertext,
+        16,
+        |_: &[u8]| -> bool { unreachable!() },
+        &options,
+    );
+    assert_eq!(again.unwrap(), recovered);
+}
+
+#[test]
+fn it_rejects_invalid_blocksizes() {
+    fn invalid<T>(result: Result<T, Error>, blocksize: usize) -> bool {
+        matches!(result, Err(Error::InvalidBlocksize { blocksize: b }) if b == blocksize)
+    }
+
+    for (ciphertext, bs) in [(&[][..], 0), (&[0; 512][..], 256)] {
+        assert!(invalid(PaddingOracleAttack::new(ciphertext, bs), bs));
+        assert!(invalid(padding_oracle::decrypt(ciphertext, bs, oracle), bs));
+        assert!(invalid(
+            padding_oracle::decrypt_block(ciphertext, bs, 1, oracle),
+            bs
+        ));
+        assert!(invalid(
+            padding_oracle::decrypt_with_intermediates(ciphertext, bs, oracle),
+            bs
+        ));
+        assert!(invalid(
+            padding_oracle::rewrite_plaintext(ciphertext, bs, &[], &[], oracle),
+            bs
+        ));
+    }
+}
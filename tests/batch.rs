@@ -0,0 +1,103 @@
+mod common;
+
+use common::{encrypt, oracle, pad};
+use padding_oracle::{DecryptOptions, Error, OracleResult};
+
+const PLAINTEXT: &[u8] = b"000002I'm killing your brain like a poisonous mushroom";
+
+#[test]
+fn it_can_decrypt_in_batches() {
+    let mut batches = Vec::new();
+    let batch_oracle = |probes: &[Vec<u8>]| {
+        batches.push(probes.len());
+        probes.iter().map(|probe| oracle(probe)).collect()
+    };
+
+    let plaintext = padding_oracle::decrypt_batched(&encrypt(PLAINTEXT), 16, batch_oracle, 64);
+    assert_eq!(plaintext.unwrap(), pad(PLAINTEXT));
+    assert!(batches.iter().all(|&len| len <= 64));
+
+    // At most 4 batches of candidates per byte, and a few double checks
+    let bytes = pad(PLAINTEXT).len();
+    assert!(batches.len() < 6 * bytes);
+
+    // Two equally valid paddings for the last byte
+    let aligned = b"000010Will it ever stop? Yo, I do";
+    let plaintext = padding_oracle::decrypt_batched(
+        &encrypt(aligned),
+        16,
+        |probes: &[Vec<u8>]| probes.iter().map(|p| oracle(p)).collect::<Vec<_>>(),
+        256,
+    );
+    assert_eq!(plaintext.unwrap(), pad(aligned));
+}
+
+#[test]
+fn it_retries_the_failed_probes_of_a_batch() {
+    let mut flaky = true;
+    let batch_oracle = |probes: &[Vec<u8>]| {
+        flaky = !flaky;
+        probes
+            .iter()
+            .enumerate()
+            .map(|(i, probe)| match (flaky, i) {
+                (true, 0) => OracleResult::Inconclusive,
+                _ => oracle(probe).into(),
+            })
+            .collect()
+    };
+
+    let options = DecryptOptions {
+        retries: 1,
+        ..Default::default()
+    };
+    let plaintext = padding_oracle::decrypt_batched_with_options(
+        &encrypt(PLAINTEXT),
+        16,
+        batch_oracle,
+        32,
+        &options,
+    );
+    assert_eq!(plaintext.unwrap(), pad(PLAINTEXT));
+}
+
+#[test]
+fn it_rejects_missing_answers() {
+    let result = padding_oracle::decrypt_batched(
+        &encrypt(PLAINTEXT),
+        16,
+        |probes: &[Vec<u8>]| vec![false; probes.len() - 1],
+        16,
+    );
+
+    assert!(matches!(result, Err(Error::Oracle(_))));
+}
+
+#[test]
+fn it_keeps_to_the_query_budget_in_batches() {
+    for budget in [5, 300] {
+        let mut queries = 0;
+        let batch_oracle = |probes: &[Vec<u8>]| {
+            queries += probes.len();
+            probes.iter().map(|probe| oracle(probe)).collect()
+        };
+        let options = DecryptOptions {
+            max_queries: Some(budget),
+            ..Default::default()
+        };
+
+        let error = padding_oracle::decrypt_batched_with_options(
+            &encrypt(PLAINTEXT),
+            16,
+            batch_oracle,
+            64,
+            &options,
+        );
+        let Err(Error::QueryBudgetExceeded { stats, .. }) = error else {
+            panic!("the budget isn't enforced: {error:?}");
+        };
+
+        assert_eq!(queries, budget);
+        assert_eq!(stats.queries, budget);
+    }
+}
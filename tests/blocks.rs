@@ -0,0 +1,134 @@
+mod common;
+
+use std::io::BufWriter;
+
+use common::{encrypt, oracle, pad};
+use padding_oracle::io::{write_blocks, FlushPolicy};
+use padding_oracle::{BlockOrder, DecryptOptions, Error, FinalBlock, PaddingOracleAttack};
+
+const PLAINTEXT: &[u8] = b"000008Cooking MC's like a pound of bacon";
+
+fn forward(final_block: FinalBlock) -> PaddingOracleAttack {
+    let options = DecryptOptions {
+        block_order: BlockOrder::Forward,
+        final_block,
+        ..Default::default()
+    };
+
+    PaddingOracleAttack::with_options(&encrypt(PLAINTEXT), 16, &options).unwrap()
+}
+
+#[test]
+fn it_yields_blocks_in_order() {
+    let blocks: Vec<_> = forward(FinalBlock::Attack)
+        .into_blocks(oracle)
+        .map(Result::unwrap)
+        .collect();
+
+    let indices: Vec<_> = blocks.iter().map(|(i, _)| *i).collect();
+    assert_eq!(indices, [1, 2, 3]);
+    assert_eq!(
+        blocks.into_iter().flat_map(|(_, b)| b).collect::<Vec<_>>(),
+        pad(PLAINTEXT)
+    );
+}
+
+#[test]
+fn it_yields_filled_blocks_last() {
+    let aligned = b"000009ith my rag-top down so my ";
+    let options = DecryptOptions {
+        final_block: FinalBlock::AssumePadding,
+        ..Default::default()
+    };
+
+    let attack = PaddingOracleAttack::with_options(&encrypt(aligned), 16, &options).unwrap();
+    let indices: Vec<_> = attack.into_blocks(oracle).map(|b| b.unwrap().0).collect();
+
+    assert_eq!(indices, [2, 1, 3]);
+}
+
+#[test]
+fn it_yields_known_blocks_in_order() {
+    let ciphertext = encrypt(PLAINTEXT);
+    let (_, intermediates) =
+        padding_oracle::decrypt_with_intermediates(&ciphertext, 16, oracle).unwrap();
+
+    // Only the middle block is attacked
+    let options = DecryptOptions {
+        block_order: BlockOrder::Forward,
+        known_intermediates: [(1, intermediates[0].clone()), (3, intermediates[2].clone())].into(),
+        ..Default::default()
+    };
+
+    let attack = PaddingOracleAttack::with_options(&ciphertext, 16, &options).unwrap();
+    let blocks: Vec<_> = attack.into_blocks(oracle).map(Result::unwrap).collect();
+
+    let indices: Vec<_> = blocks.iter().map(|(i, _)| *i).collect();
+    assert_eq!(indices, [1, 2, 3]);
+    assert_eq!(
+        blocks.into_iter().flat_map(|(_, b)| b).collect::<Vec<_>>(),
+        pad(PLAINTEXT)
+    );
+}
+
+#[test]
+fn it_yields_the_error_last() {
+    let mut blocks = forward(FinalBlock::Attack).into_blocks(|_: &[u8]| false);
+
+    assert!(matches!(
+        blocks.next(),
+        Some(Err(Error::InvalidPadding { .. }))
+    ));
+    assert!(blocks.next().is_none());
+}
+
+#[test]
+fn it_writes_blocks_as_they_come() {
+    let mut writer = BufWriter::new(Vec::new());
+
+    let written = write_blocks(
+        forward(FinalBlock::Attack).into_blocks(oracle),
+        &mut writer,
+        FlushPolicy::Every(2),
+    )
+    .unwrap();
+
+    assert_eq!(written, 48);
+    assert_eq!(writer.into_inner().unwrap(), pad(PLAINTEXT));
+}
+
+#[test]
+fn it_keeps_what_was_written_on_errors() {
+    let mut writer = BufWriter::new(Vec::new());
+    let ciphertext = encrypt(PLAINTEXT);
+
+    // Only answer for the first two blocks
+    let blocks = forward(FinalBlock::Attack)
+        .into_blocks(|probe: &[u8]| probe.len() < ciphertext.len() && oracle(probe));
+
+    assert!(write_blocks(blocks, &mut writer, FlushPolicy::AtEnd).is_err());
+    assert_eq!(writer.get_ref(), &pad(PLAINTEXT)[..32]);
+}
+
+#[test]
+fn it_decrypts_lazily() {
+    let ciphertext = encrypt(PLAINTEXT);
+    let blocks: Vec<_> = padding_oracle::decrypt_iter(&ciphertext, 16, oracle)
+        .unwrap()
+        .map(|block| block.unwrap().1)
+        .collect();
+    assert_eq!(blocks.concat(), pad(PLAINTEXT));
+
+    // Stopping early saves the queries of the remaining blocks
+    let mut queries = 0;
+    let first = padding_oracle::decrypt_iter(&ciphertext, 16, |probe: &[u8]| {
+        queries += 1;
+        probe.len() == 32 && oracle(probe)
+    })
+    .unwrap()
+    .next();
+
+    assert_eq!(first.unwrap().unwrap(), (1, PLAINTEXT[..16].to_vec()));
+    assert!(queries < 16 * 256);
+    assert!(padding_oracle::decrypt_iter(&ciphertext[1..], 16, oracle).is_err());
+}
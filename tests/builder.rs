@@ -0,0 +1,55 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use common::{encrypt, encrypt_with_iv, oracle, pad, IV};
+use padding_oracle::{Attack, BlockOrder, EnglishText, IvLocation, PaddingScheme};
+
+const PLAINTEXT: &[u8] = b"000004Burning 'em, if you ain't quick and nimble";
+
+#[test]
+fn it_builds_attacks() {
+    let bytes = Arc::new(AtomicUsize::new(0));
+    let counter = bytes.clone();
+
+    let attack = Attack::builder()
+        .blocksize(16)
+        .iv(IvLocation::Prepended)
+        .padding(PaddingScheme::Pkcs7)
+        .strategy(EnglishText)
+        .block_order(BlockOrder::Forward)
+        .check_padding(true)
+        .progress(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        })
+        .build();
+
+    assert_eq!(attack.blocksize(), 16);
+    assert!(attack.options().check_padding);
+
+    // The same attack decrypts several ciphertexts
+    for plaintext in [PLAINTEXT, b"YELLOW SUBMARINE"] {
+        let recovered = attack.decrypt(&encrypt(plaintext), oracle).unwrap();
+        assert_eq!(recovered, pad(plaintext));
+    }
+
+    assert_eq!(bytes.load(Ordering::Relaxed), 64 + 32);
+}
+
+#[test]
+fn it_takes_separate_ivs() {
+    let ciphertext = encrypt_with_iv(PLAINTEXT, &IV);
+    let attack = Attack::builder()
+        .iv(IvLocation::Separate(IV.to_vec()))
+        .build();
+
+    assert_eq!(attack.decrypt(&ciphertext, oracle).unwrap(), pad(PLAINTEXT));
+
+    let error = Attack::builder()
+        .iv(IvLocation::Separate(vec![0; 8]))
+        .build()
+        .decrypt(&ciphertext, oracle)
+        .unwrap_err();
+    assert!(matches!(error, padding_oracle::Error::WrongIvSize { .. }));
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::{encrypt, oracle, pad};
+use padding_oracle::{ByteSet, DecryptOptions, Error, PaddingOracleAttack};
+
+const PLAINTEXT: &[u8] = b"000007Rollin' in my 5.0 with my ragtop down";
+
+/// A transport which strips the null bytes
+fn stripping_oracle(ciphertext: &[u8]) -> bool {
+    let stripped: Vec<u8> = ciphertext.iter().copied().filter(|&b| b != 0).collect();
+    stripped.len().is_multiple_of(16) && oracle(&stripped)
+}
+
+#[test]
+fn it_finds_the_mangled_bytes() {
+    let mut ciphertext = encrypt(PLAINTEXT);
+    // The test IV is full of null bytes
+    ciphertext[..16].fill(1);
+
+    let allowed = padding_oracle::calibrate_transport(&ciphertext, 16, stripping_oracle).unwrap();
+
+    assert_eq!(allowed.len(), 255);
+    assert!(!allowed.contains(0));
+
+    assert!(matches!(
+        padding_oracle::calibrate_transport(&ciphertext[..32], 16, oracle),
+        Err(Error::TooShort { min: 48, found: 32 })
+    ));
+}
+
+#[test]
+fn it_only_tries_allowed_bytes() {
+    let ciphertext = encrypt(PLAINTEXT);
+
+    let options = DecryptOptions {
+        allowed_bytes: Some((1..=255).collect()),
+        ..Default::default()
+    };
+
+    let attack = PaddingOracleAttack::with_options(&ciphertext, 16, &options).unwrap();
+    let probe = attack.next_probe().unwrap();
+
+    assert_eq!(probe[probe.len() - 17], 1);
+
+    let options = DecryptOptions {
+        allowed_bytes: Some(ByteSet::all()),
+        ..Default::default()
+    };
+
+    let plaintext = padding_oracle::decrypt_with_options(&ciphertext, 16, oracle, &options);
+    assert_eq!(plaintext.unwrap(), pad(PLAINTEXT));
+
+    let options = DecryptOptions {
+        allowed_bytes: Some(ByteSet::new()),
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        padding_oracle::decrypt_with_options(&ciphertext, 16, oracle, &options),
+        Err(Error::InvalidPadding { .. })
+    ));
+}
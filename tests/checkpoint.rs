@@ -0,0 +1,136 @@
+mod common;
+
+use common::{encrypt, oracle, pad};
+use padding_oracle::AttackState;
+
+const PLAINTEXT: &[u8] = b"000008ollin' in my five point oh";
+
+/// An oracle going down for good after `queries` queries
+fn dying_oracle(mut queries: usize) -> impl FnMut(&[u8]) -> Result<bool, &'static str> {
+    move |ciphertext| {
+        if queries == 0 {
+            return Err("connection reset");
+        }
+
+        queries -= 1;
+        Ok(oracle(ciphertext))
+    }
+}
+
+#[test]
+fn it_resumes_interrupted_attacks() {
+    let ciphertext = encrypt(PLAINTEXT);
+    let mut saved: Option<AttackState> = None;
+
+    let interrupted =
+        padding_oracle::decrypt_resumable(&ciphertext, 16, dying_oracle(3000), |state| {
+            saved = Some(state)
+        });
+    assert!(matches!(interrupted, Err(padding_oracle::Error::Oracle(_))));
+
+    let mut queries = 0;
+    let plaintext = padding_oracle::resume(
+        saved.unwrap(),
+        |p: &[u8]| {
+            queries += 1;
+            oracle(p)
+        },
+        |_| {},
+    )
+    .unwrap();
+
+    assert_eq!(plaintext, pad(PLAINTEXT));
+    assert!(queries < 3000);
+}
+
+#[test]
+fn it_checkpoints_every_block() {
+    let mut checkpoints = 0;
+
+    padding_oracle::decrypt_resumable(&encrypt(PLAINTEXT), 16, oracle, |_| checkpoints += 1)
+        .unwrap();
+
+    assert_eq!(checkpoints, encrypt(PLAINTEXT).len() / 16 - 1);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn it_serializes_checkpoints() {
+    let ciphertext = encrypt(PLAINTEXT);
+    let mut attack = padding_oracle::PaddingOracleAttack::new(&ciphertext, 16).unwrap();
+
+    for _ in 0..1000 {
+        let probe = attack.next_probe().unwrap();
+        attack.submit(oracle(&probe));
+    }
+
+    let json = serde_json::to_string(&attack.checkpoint().unwrap()).unwrap();
+    let state: AttackState = serde_json::from_str(&json).unwrap();
+    let resumed = padding_oracle::PaddingOracleAttack::resume(state, &Default::default()).unwrap();
+
+    assert_eq!(resumed.queries(), 1000);
+    assert_eq!(resumed.next_probe(), attack.next_probe());
+
+    let corrupted: AttackState =
+        serde_json::from_str(&json.replace(r#""blocksize":16"#, r#""blocksize":12"#)).unwrap();
+    assert!(matches!(
+        padding_oracle::PaddingOracleAttack::resume(corrupted, &Default::default()),
+        Err(padding_oracle::Error::InvalidCheckpoint)
+    ));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn it_rejects_inconsistent_checkpoints() {
+    let ciphertext = encrypt(PLAINTEXT);
+    let mut attack = padding_oracle::PaddingOracleAttack::new(&ciphertext, 16).unwrap();
+    let probe = attack.next_probe().unwrap();
+    attack.submit(oracle(&probe));
+
+    let mut state = serde_json::to_value(attack.checkpoint().unwrap()).unwrap();
+    state["block"] = 1.into();
+
+    // Every byte forged without confirming the block
+    let mut past_the_block = state.clone();
+    past_the_block["pad"] = 17.into();
+    past_the_block["confirming"] = false.into();
+
+    // No byte to flip before the first one
+    let mut verifying_the_first_byte = state.clone();
+    verifying_the_first_byte["pad"] = 16.into();
+    verifying_the_first_byte["verifying"] = true.into();
+
+    for tampered in [past_the_block, verifying_the_first_byte] {
+        let tampered: AttackState = serde_json::from_value(tampered).unwrap();
+        assert!(matches!(
+            padding_oracle::PaddingOracleAttack::resume(tampered, &Default::default()),
+            Err(padding_oracle::Error::InvalidCheckpoint)
+        ));
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn it_rejects_checkpoints_past_the_last_candidate() {
+    use padding_oracle::{CandidateOrder, DecryptOptions};
+
+    let options = DecryptOptions {
+        candidate_order: CandidateOrder::LikelyText,
+        ..Default::default()
+    };
+    let ciphertext = encrypt(PLAINTEXT);
+    let mut attack =
+        padding_oracle::PaddingOracleAttack::with_options(&ciphertext, 16, &options).unwrap();
+    let probe = attack.next_probe().unwrap();
+    attack.submit(oracle(&probe));
+
+    // The block is still being attacked, with no candidate left to try
+    let mut state = serde_json::to_value(attack.checkpoint().unwrap()).unwrap();
+    state["candidate"] = 256.into();
+
+    let state: AttackState = serde_json::from_value(state).unwrap();
+    assert!(matches!(
+        padding_oracle::PaddingOracleAttack::resume(state, &options),
+        Err(padding_oracle::Error::InvalidCheckpoint)
+    ));
+}
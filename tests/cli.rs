@@ -0,0 +1,100 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::process::Command;
+use std::thread;
+
+use common::{encrypt, oracle};
+use padding_oracle::http::Encoding;
+
+/// A server reading the hex ciphertext from the `token` parameter, answering with a 500 when the
+/// padding is wrong. Returns its URL.
+fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!(
+        "http://{}/?token={{ciphertext}}",
+        listener.local_addr().unwrap()
+    );
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut lines = BufReader::new(&stream).lines().map(Result::unwrap);
+
+            let request = lines.next().unwrap();
+            let token = request
+                .split_once("token=")
+                .and_then(|(_, rest)| rest.split(' ').next())
+                .and_then(|token| Encoding::Hex.decode(token));
+            lines.take_while(|line| !line.is_empty()).for_each(drop);
+
+            let response = match token {
+                Some(ciphertext) if oracle(&ciphertext) => "HTTP/1.1 200 OK\r\n\r\n",
+                _ => "HTTP/1.1 500 Oops\r\n\r\n",
+            };
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+
+    url
+}
+
+fn cli(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_padding-oracle"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn it_decrypts_from_the_command_line() {
+    let url = serve();
+    let ciphertext = Encoding::Hex.encode(&encrypt(b"YELLOW SUBMARINE, yellow"));
+
+    let output = cli(&[
+        "decrypt",
+        "--url",
+        &url,
+        "--invalid-status",
+        "500",
+        "--encoding",
+        "hex",
+        "--ciphertext",
+        &ciphertext,
+        "--quiet",
+    ]);
+
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"YELLOW SUBMARINE, yellow\n");
+}
+
+#[test]
+fn it_explains_its_usage() {
+    let output = cli(&["decrypt", "--url", "http://127.0.0.1/"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("missing match rule"));
+}
+
+#[test]
+fn it_finds_out_the_padding_errors() {
+    let url = serve();
+    let ciphertext = Encoding::Hex.encode(&encrypt(b"YELLOW SUBMARINE, yellow"));
+
+    let output = cli(&[
+        "decrypt",
+        "--url",
+        &url,
+        "--encoding",
+        "hex",
+        "--ciphertext",
+        &ciphertext,
+    ]);
+
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"YELLOW SUBMARINE, yellow\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Padding errors: status 500"));
+}
@@ -0,0 +1,58 @@
+//! Fixtures shared by the integration tests
+#![allow(dead_code)]
+
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+
+/// We're using predictable values here to avoid having random behaviors in our tests
+pub const KEY: [u8; 16] = [0u8; 16];
+pub const IV: [u8; 16] = [0u8; 16];
+
+type Aes128CbcEnc = cbc::Encryptor<aes::Aes128>;
+type Aes128CbcDec = cbc::Decryptor<aes::Aes128>;
+
+pub fn oracle(ciphertext: &[u8]) -> bool {
+    let mut buf = ciphertext.to_vec();
+
+    Aes128CbcDec::new(&KEY.into(), &IV.into())
+        .decrypt_padded_mut::<Pkcs7>(&mut buf)
+        .is_ok()
+}
+
+/// Encrypt the plaintext and prepend the IV
+pub fn encrypt(plaintext: &[u8]) -> Vec<u8> {
+    let mut iv = IV.to_vec();
+
+    iv.extend_from_slice(&encrypt_with_iv(plaintext, &IV));
+    iv
+}
+
+/// Encrypt the plaintext under a custom IV, without prepending it
+pub fn encrypt_with_iv(plaintext: &[u8], iv: &[u8; 16]) -> Vec<u8> {
+    let mut ciphertext = vec![0u8; (plaintext.len() / 16 + 1) * 16];
+
+    ciphertext[..plaintext.len()].copy_from_slice(plaintext);
+
+    Aes128CbcEnc::new(&KEY.into(), iv.into())
+        .encrypt_padded_mut::<Pkcs7>(&mut ciphertext, plaintext.len())
+        .unwrap()
+        .to_vec()
+}
+
+/// Apply PKCS7 padding to the plaintext, as the attack recovers it
+pub fn pad(plaintext: &[u8]) -> Vec<u8> {
+    let pad = 16 - plaintext.len() % 16;
+    let mut padded = plaintext.to_vec();
+
+    padded.resize(plaintext.len() + pad, pad as u8);
+    padded
+}
+
+/// The padded plaintext split into blocks, with their index counting the IV as block `0`, as a
+/// failed attack returns them
+pub fn blocks(plaintext: &[u8]) -> Vec<(usize, Vec<u8>)> {
+    pad(plaintext)
+        .chunks(16)
+        .enumerate()
+        .map(|(i, block)| (i + 1, block.to_vec()))
+        .collect()
+}
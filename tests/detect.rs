@@ -0,0 +1,35 @@
+mod common;
+
+use common::oracle;
+use padding_oracle::{testing::local_oracle, PaddingScheme};
+
+#[test]
+fn it_detects_the_blocksize() {
+    assert_eq!(
+        padding_oracle::detect_blocksize(oracle, 32).unwrap(),
+        Some(16)
+    );
+
+    // A toy cipher with 8-byte blocks
+    let des_like = local_oracle(
+        |block: &[u8; 8]| block.map(|b| b ^ 0x42),
+        PaddingScheme::Pkcs7,
+    );
+    assert_eq!(
+        padding_oracle::detect_blocksize(des_like, 32).unwrap(),
+        Some(8)
+    );
+}
+
+#[test]
+fn it_gives_up_on_too_short_probes() {
+    assert_eq!(padding_oracle::detect_blocksize(oracle, 24).unwrap(), None);
+}
+
+#[test]
+fn it_rejects_oracles_always_valid() {
+    assert_eq!(
+        padding_oracle::detect_blocksize(|_: &[u8]| true, 32).unwrap(),
+        None
+    );
+}
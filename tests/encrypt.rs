@@ -0,0 +1,38 @@
+mod common;
+
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+use common::{oracle, KEY};
+
+type Aes128CbcDec = cbc::Decryptor<aes::Aes128>;
+
+#[test]
+fn it_can_forge_ciphertexts() {
+    for plaintext in [
+        &b""[..],
+        b"admin=true",
+        b"000000Now that the party is jumping",
+    ] {
+        let ciphertext = padding_oracle::encrypt(plaintext, 16, oracle).unwrap();
+        assert_eq!(ciphertext.len(), (plaintext.len() / 16 + 2) * 16);
+
+        // Decrypt it for real, with the forged IV
+        let (iv, ciphertext) = ciphertext.split_at(16);
+        let mut buf = ciphertext.to_vec();
+        let decrypted = Aes128CbcDec::new(&KEY.into(), iv.into())
+            .decrypt_padded_mut::<Pkcs7>(&mut buf)
+            .unwrap();
+
+        assert_eq!(decrypted, plaintext);
+    }
+}
+
+#[test]
+fn it_rejects_invalid_blocksizes() {
+    for blocksize in [0, 256] {
+        let error = padding_oracle::encrypt(b"admin=true", blocksize, oracle).unwrap_err();
+        assert!(matches!(
+            error,
+            padding_oracle::Error::InvalidBlocksize { blocksize: b } if b == blocksize
+        ));
+    }
+}
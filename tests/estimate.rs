@@ -0,0 +1,61 @@
+mod common;
+
+use std::cell::Cell;
+
+use common::{encrypt, oracle, pad};
+use padding_oracle::{estimate_queries, CandidateOrder, DecryptOptions, FinalBlock, Verification};
+
+const PLAINTEXT: &[u8] = b"000008I'm like Samson -- Samson to Delilah";
+
+#[test]
+fn it_estimates_the_queries() {
+    let ciphertext = encrypt(PLAINTEXT);
+
+    for options in [
+        DecryptOptions::default(),
+        DecryptOptions {
+            verification: Verification::Minimal,
+            candidate_order: CandidateOrder::LikelyText,
+            ..Default::default()
+        },
+        DecryptOptions {
+            charset: Some((b' '..=b'~').collect()),
+            final_block: FinalBlock::DetectPadding,
+            precheck: true,
+            ..Default::default()
+        },
+    ] {
+        let queries = Cell::new(0);
+        let counting = |probe: &[u8]| {
+            queries.set(queries.get() + 1);
+            oracle(probe)
+        };
+
+        let plaintext = padding_oracle::decrypt_with_options(&ciphertext, 16, counting, &options);
+        assert_eq!(plaintext.unwrap(), pad(PLAINTEXT));
+
+        let estimate = estimate_queries(ciphertext.len(), 16, &options).unwrap();
+        let queries = queries.get() as f64;
+        assert!(queries <= estimate.worst_case as f64);
+        assert!(queries > estimate.expected / 2.0 && queries < estimate.expected * 1.5);
+    }
+}
+
+#[test]
+fn it_doesnt_count_the_known_bytes() {
+    let known = DecryptOptions {
+        known_plaintext: pad(PLAINTEXT).into_iter().map(Some).collect(),
+        ..Default::default()
+    };
+    let estimate = estimate_queries(64, 16, &known).unwrap();
+    assert_eq!(estimate.expected, 3.0 * (16.0 + 15.0));
+
+    let partial = DecryptOptions {
+        blocks: Some(2..3),
+        ..Default::default()
+    };
+    let estimate = estimate_queries(64, 16, &partial).unwrap();
+    assert_eq!(estimate.worst_case, 16 * 256 + 15);
+
+    assert!(estimate_queries(65, 16, &DecryptOptions::default()).is_err());
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "ffi")]
+
+mod common;
+
+use std::ffi::{c_int, c_void};
+
+use common::{encrypt, oracle, pad};
+use padding_oracle::ffi::{po_decrypt, po_encrypt, PoError};
+
+/// The oracle, counting its queries in the context
+extern "C" fn callback(ciphertext: *const u8, len: usize, context: *mut c_void) -> c_int {
+    let queries = unsafe { &mut *(context as *mut usize) };
+    *queries += 1;
+
+    oracle(unsafe { std::slice::from_raw_parts(ciphertext, len) }) as c_int
+}
+
+extern "C" fn failing(_: *const u8, _: usize, _: *mut c_void) -> c_int {
+    -1
+}
+
+#[test]
+fn it_decrypts_through_c() {
+    let plaintext = b"YELLOW SUBMARINE is yellow";
+    let ciphertext = encrypt(plaintext);
+    let mut queries = 0usize;
+    let context = &mut queries as *mut usize as *mut c_void;
+
+    // Too small, the needed length is written back
+    let mut output = vec![0u8; 64];
+    let mut len = 16;
+    let result = unsafe {
+        po_decrypt(
+            ciphertext.as_ptr(),
+            ciphertext.len(),
+            16,
+            Some(callback),
+            context,
+            output.as_mut_ptr(),
+            &mut len,
+        )
+    };
+    assert_eq!(result, PoError::BufferTooSmall);
+    assert_eq!((len, queries), (32, 0));
+
+    len = output.len();
+    let result = unsafe {
+        po_decrypt(
+            ciphertext.as_ptr(),
+            ciphertext.len(),
+            16,
+            Some(callback),
+            context,
+            output.as_mut_ptr(),
+            &mut len,
+        )
+    };
+    assert_eq!(result, PoError::Ok);
+    assert_eq!(output[..len], pad(plaintext));
+    assert!(queries > 0);
+
+    let result = unsafe {
+        po_decrypt(
+            ciphertext.as_ptr(),
+            ciphertext.len(),
+            16,
+            Some(failing),
+            context,
+            output.as_mut_ptr(),
+            &mut len,
+        )
+    };
+    assert_eq!(result, PoError::Oracle);
+
+    let result = unsafe {
+        po_decrypt(
+            ciphertext.as_ptr(),
+            ciphertext.len(),
+            16,
+            None,
+            context,
+            output.as_mut_ptr(),
+            &mut len,
+        )
+    };
+    assert_eq!(result, PoError::NullPointer);
+}
+
+#[test]
+fn it_encrypts_through_c() {
+    let plaintext = b"{\"role\": \"admin\"}";
+    let mut queries = 0usize;
+    let mut output = vec![0u8; 64];
+    let mut len = output.len();
+
+    let result = unsafe {
+        po_encrypt(
+            plaintext.as_ptr(),
+            plaintext.len(),
+            16,
+            Some(callback),
+            &mut queries as *mut usize as *mut c_void,
+            output.as_mut_ptr(),
+            &mut len,
+        )
+    };
+    assert_eq!(result, PoError::Ok);
+    assert_eq!(len, 48);
+
+    let decrypted = padding_oracle::decrypt(&output[..len], 16, oracle).unwrap();
+    assert_eq!(decrypted, pad(plaintext));
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::{encrypt, oracle, pad};
+use padding_oracle::{Error, ForgeKit, PaddingOracleAttack};
+
+fn kit() -> ForgeKit {
+    let mut attack = PaddingOracleAttack::new(&encrypt(b"YELLOW SUBMARINE"), 16).unwrap();
+
+    while let Some(probe) = attack.next_probe() {
+        attack.submit(oracle(&probe));
+    }
+
+    ForgeKit::from_attack(&attack)
+}
+
+#[test]
+fn it_forges_without_the_oracle() {
+    let kit = kit();
+    assert_eq!(kit.blocks.len(), 2);
+
+    let forged = kit.forge(b"admin=true").unwrap();
+
+    // Check with the oracle that it decrypts as expected
+    let plaintext = padding_oracle::decrypt(&forged, 16, oracle).unwrap();
+    assert_eq!(plaintext, pad(b"admin=true"));
+}
+
+#[test]
+fn it_refuses_to_forge_long_plaintexts() {
+    assert!(matches!(
+        kit().forge(b"0123456789abcdef"),
+        Err(Error::PlaintextTooLong { max: 15, found: 16 })
+    ));
+    assert!(matches!(
+        ForgeKit::default().forge(b""),
+        Err(Error::EmptyForgeKit)
+    ));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn it_can_be_saved() {
+    let kit = kit();
+    let saved = serde_json::to_string(&kit).unwrap();
+
+    assert_eq!(serde_json::from_str::<ForgeKit>(&saved).unwrap(), kit);
+}
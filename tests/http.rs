@@ -0,0 +1,127 @@
+#![cfg(feature = "http")]
+
+mod common;
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+
+use common::{encrypt, oracle, pad};
+use padding_oracle::http::{self, Encoding, HttpOracle, Request, Rule};
+
+/// A server reading the ciphertext from the `session` cookie, answering with a chunked 500 when
+/// the padding is wrong. Returns its URL.
+fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/profile", listener.local_addr().unwrap());
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut cookie = None;
+
+            for line in BufReader::new(&stream).lines() {
+                let line = line.unwrap();
+                if line.is_empty() {
+                    break;
+                }
+
+                if let Some(value) = line.strip_prefix("Cookie: session=") {
+                    cookie = Encoding::Base64Url.decode(value);
+                }
+            }
+
+            let response = match cookie {
+                Some(ciphertext) if oracle(&ciphertext) => {
+                    "HTTP/1.1 200 OK\r\nContent-Length: 7\r\n\r\nwelcome"
+                }
+                _ => "HTTP/1.1 500 Oops\r\nTransfer-Encoding: chunked\r\n\r\n7\r\nInvalid\r\n8\r\n padding\r\n0\r\n\r\n",
+            };
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+
+    url
+}
+
+#[test]
+fn it_decrypts_through_http() {
+    let request = Request {
+        headers: vec![("Cookie".into(), "session={ciphertext}".into())],
+        ..Request::get(serve())
+    };
+    let rule = Rule::Not(Box::new(Rule::BodyContains("Invalid padding".into())));
+    let oracle = HttpOracle::new(request, Encoding::Base64Url, rule).unwrap();
+
+    let plaintext = b"YELLOW SUBMARINE";
+    let ciphertext = encrypt(plaintext);
+
+    let response = oracle.send(&ciphertext[..16]).unwrap();
+    assert_eq!(response.status, 500);
+    assert_eq!(response.body, b"Invalid padding");
+
+    let recovered = padding_oracle::decrypt(&ciphertext, 16, oracle).unwrap();
+    assert_eq!(recovered, pad(plaintext));
+}
+
+#[test]
+fn it_encodes_the_probes() {
+    for encoding in [
+        Encoding::Hex,
+        Encoding::UpperHex,
+        Encoding::Base64,
+        Encoding::Base64Url,
+        Encoding::UrlToken,
+    ] {
+        for len in 0..8 {
+            let bytes: Vec<u8> = (0..len).map(|i| 0xf0 + i).collect();
+            let encoded = encoding.encode(&bytes);
+
+            assert_eq!(encoding.decode(&encoded), Some(bytes), "{encoding:?}");
+        }
+    }
+
+    assert_eq!(Encoding::Base64.encode(b"hello"), "aGVsbG8=");
+    assert_eq!(Encoding::Hex.decode("zz"), None);
+    assert_eq!(Encoding::Base64.decode("a"), None);
+}
+
+#[test]
+fn it_rejects_other_urls() {
+    let https = HttpOracle::new(
+        Request::get("https://example.com"),
+        Encoding::Hex,
+        Rule::Status(200),
+    );
+    assert!(matches!(https, Err(padding_oracle::Error::Io(_))));
+}
+
+#[test]
+fn it_calibrates_the_rule() {
+    let request = Request {
+        headers: vec![("Cookie".into(), "session={ciphertext}".into())],
+        ..Request::get(serve())
+    };
+    let ciphertext = encrypt(b"YELLOW SUBMARINE");
+
+    let calibration = http::calibrate(&request, Encoding::Base64Url, &ciphertext, 16).unwrap();
+    assert_eq!(calibration.invalid().status, 500);
+    assert_eq!(calibration.invalid().length, 15);
+    assert_eq!(calibration.fingerprints()[0].1, 254);
+
+    let oracle = HttpOracle::new(request.clone(), Encoding::Base64Url, calibration.rule()).unwrap();
+    let recovered = padding_oracle::decrypt(&ciphertext, 16, oracle).unwrap();
+    assert_eq!(recovered, pad(b"YELLOW SUBMARINE"));
+
+    // Too short, or already a padding error
+    let short = http::calibrate(&request, Encoding::Base64Url, &ciphertext[32..], 16);
+    assert!(matches!(short, Err(padding_oracle::Error::TooShort { .. })));
+
+    let mut broken = ciphertext.clone();
+    broken[31] ^= 0x80;
+    let broken = http::calibrate(&request, Encoding::Base64Url, &broken, 16);
+    assert!(matches!(
+        broken,
+        Err(padding_oracle::Error::RejectedCiphertext)
+    ));
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::{encrypt, oracle, pad};
+use padding_oracle::jwe::{self, JweOracle, JweToken};
+
+/// `{"alg":"dir","enc":"A128CBC-HS256"}`
+const HEADER: &str = "eyJhbGciOiJkaXIiLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0";
+
+#[test]
+fn it_decrypts_jwe_tokens() {
+    let plaintext = br#"{"sub":"alice","admin":false}"#;
+    let ciphertext = encrypt(plaintext);
+
+    let captured = JweToken {
+        header: HEADER.into(),
+        encrypted_key: Vec::new(),
+        iv: ciphertext[..16].to_vec(),
+        ciphertext: ciphertext[16..].to_vec(),
+        tag: vec![0xaa; 16],
+    };
+    let token = JweToken::parse(&captured.encode()).unwrap();
+    assert_eq!(token, captured);
+    assert_eq!(token.encode().split('.').nth(1), Some(""));
+
+    // The server decrypts before checking the tag
+    let server = |token: &str| {
+        let token = JweToken::parse(token).unwrap();
+        assert_eq!(token.header, HEADER);
+        assert_eq!(token.tag, [0xbb; 16]);
+
+        oracle(&token.cbc_ciphertext())
+    };
+    let resign = |token: &mut JweToken| token.tag = vec![0xbb; 16];
+
+    let oracle = JweOracle::with_hook(token.clone(), server, resign);
+    let recovered = padding_oracle::decrypt(&token.cbc_ciphertext(), jwe::BLOCKSIZE, oracle);
+    assert_eq!(recovered.unwrap(), pad(plaintext));
+
+    assert_eq!(JweToken::parse("a.b.c"), None);
+    assert_eq!(JweToken::parse(&format!("{}.x", captured.encode())), None);
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use common::{encrypt, oracle, pad};
+use padding_oracle::{DecryptOptions, Error, Layout};
+
+const PLAINTEXT: &[u8] = b"000002Quick to the point, to the point, no faking";
+
+/// Only accepts ciphertexts as long as the test ciphertext
+fn fixed_length_oracle(ciphertext: &[u8]) -> bool {
+    ciphertext.len() == encrypt(PLAINTEXT).len() && oracle(ciphertext)
+}
+
+/// Only checks the padding of the second block, dropping the rest
+fn second_block_oracle(ciphertext: &[u8]) -> bool {
+    ciphertext.len() == encrypt(PLAINTEXT).len() && oracle(&ciphertext[..3 * 16])
+}
+
+#[test]
+fn truncated_layout_is_rejected_by_fixed_length_oracles() {
+    assert!(padding_oracle::decrypt(&encrypt(PLAINTEXT), 16, fixed_length_oracle).is_err());
+}
+
+#[test]
+fn it_can_decrypt_with_a_fixed_length() {
+    let options = DecryptOptions {
+        layout: Layout::FixedLength {
+            checked_block: None,
+        },
+        ..Default::default()
+    };
+
+    let plaintext = padding_oracle::decrypt_with_options(
+        &encrypt(PLAINTEXT),
+        16,
+        fixed_length_oracle,
+        &options,
+    )
+    .unwrap();
+
+    assert_eq!(plaintext, pad(PLAINTEXT));
+}
+
+#[test]
+fn it_can_decrypt_with_a_fixed_checked_block() {
+    let options = DecryptOptions {
+        layout: Layout::FixedLength {
+            checked_block: Some(2),
+        },
+        ..Default::default()
+    };
+
+    let plaintext = padding_oracle::decrypt_with_options(
+        &encrypt(PLAINTEXT),
+        16,
+        second_block_oracle,
+        &options,
+    )
+    .unwrap();
+
+    assert_eq!(plaintext, pad(PLAINTEXT));
+}
+
+#[test]
+fn it_rejects_an_out_of_range_checked_block() {
+    let options = DecryptOptions {
+        layout: Layout::FixedLength {
+            checked_block: Some(0),
+        },
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        padding_oracle::decrypt_with_options(&encrypt(PLAINTEXT), 16, oracle, &options),
+        Err(Error::InvalidBlock {
+            block: 0,
+            blocks: 5
+        })
+    ));
+}
+
+/// Only accepts the minimal two-block probes
+fn two_block_oracle(ciphertext: &[u8]) -> bool {
+    ciphertext.len() == 2 * 16 && oracle(ciphertext)
+}
+
+#[test]
+fn it_can_decrypt_a_single_block() {
+    let ciphertext = encrypt(PLAINTEXT);
+
+    for block in 1..5 {
+        let plaintext = padding_oracle::decrypt_block(&ciphertext, 16, block, two_block_oracle);
+        assert_eq!(
+            plaintext.unwrap(),
+            pad(PLAINTEXT)[(block - 1) * 16..block * 16]
+        );
+    }
+
+    assert!(matches!(
+        padding_oracle::decrypt_block(&ciphertext, 16, 5, two_block_oracle),
+        Err(Error::InvalidBlock {
+            block: 5,
+            blocks: 5
+        })
+    ));
+}
+
+#[test]
+fn it_can_decrypt_a_block_given_the_previous_one() {
+    let ciphertext = encrypt(PLAINTEXT);
+
+    let plaintext = padding_oracle::decrypt_single_block(
+        &ciphertext[32..48],
+        &ciphertext[48..64],
+        16,
+        two_block_oracle,
+    );
+    assert_eq!(plaintext.unwrap(), pad(PLAINTEXT)[32..48]);
+
+    assert!(matches!(
+        padding_oracle::decrypt_single_block(&ciphertext[..16], &ciphertext[16..48], 16, oracle),
+        Err(Error::WrongSize {
+            blocksize: 16,
+            found: 32
+        })
+    ));
+}
+
+#[test]
+fn it_can_send_only_two_blocks() {
+    let options = DecryptOptions {
+        layout: Layout::TwoBlocks,
+        precheck: true,
+        ..Default::default()
+    };
+
+    // Only accepts the IV and a single block
+    let short_oracle = |ciphertext: &[u8]| ciphertext.len() == 32 && oracle(ciphertext);
+
+    let plaintext =
+        padding_oracle::decrypt_with_options(&encrypt(PLAINTEXT), 16, short_oracle, &options);
+    assert_eq!(plaintext.unwrap(), pad(PLAINTEXT));
+}
+
+#[test]
+fn it_keeps_the_context_blocks() {
+    const HEADER: &[u8] = b"SESSION 0000042:";
+    const TRAILER: &[u8] = b"END.";
+
+    // Only accepts messages with the header and the trailer around the ciphertext
+    let framed_oracle = |message: &[u8]| {
+        message.starts_with(HEADER)
+            && message.ends_with(TRAILER)
+            && oracle(&message[HEADER.len()..message.len() - TRAILER.len()])
+    };
+
+    let options = DecryptOptions {
+        prefix: HEADER.to_vec(),
+        suffix: TRAILER.to_vec(),
+        precheck: true,
+        ..Default::default()
+    };
+
+    let plaintext =
+        padding_oracle::decrypt_with_options(&encrypt(PLAINTEXT), 16, framed_oracle, &options);
+    assert_eq!(plaintext.unwrap(), pad(PLAINTEXT));
+
+    assert!(padding_oracle::decrypt(&encrypt(PLAINTEXT), 16, framed_oracle).is_err());
+}
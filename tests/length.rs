@@ -0,0 +1,38 @@
+mod common;
+
+use common::{encrypt, oracle};
+use padding_oracle::Error;
+
+#[test]
+fn it_finds_the_plaintext_length() {
+    let message = b"000001With the bass kicked in and the Vega's are pumpin'";
+
+    for len in 0..=message.len() {
+        let mut queries = 0;
+        let length =
+            padding_oracle::plaintext_length(&encrypt(&message[..len]), 16, |p: &[u8]| {
+                queries += 1;
+                oracle(p)
+            })
+            .unwrap();
+
+        assert_eq!(length, len);
+        assert!(queries <= 17);
+    }
+}
+
+#[test]
+fn it_rejects_ciphertexts_the_oracle_rejects() {
+    let mut ciphertext = encrypt(b"YELLOW SUBMARINE");
+    let n = ciphertext.len();
+    ciphertext[n - 17] ^= 1;
+
+    assert!(matches!(
+        padding_oracle::plaintext_length(&ciphertext, 16, oracle),
+        Err(Error::RejectedCiphertext)
+    ));
+    assert!(matches!(
+        padding_oracle::plaintext_length(&ciphertext[..16], 16, oracle),
+        Err(Error::TooShort { min: 32, found: 16 })
+    ));
+}
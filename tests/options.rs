@@ -0,0 +1,669 @@
+mod common;
+
+use common::{blocks, encrypt, oracle, pad};
+use padding_oracle::{
+    BlockOrder, CandidateOrder, DecryptOptions, Error, FinalBlock, GuessContext, GuessStrategy,
+    PaddingOracleAttack, Progress, ProgressCallback, Verification,
+};
+
+const PLAINTEXT: &[u8] = b"000006And a high hat with a souped up tempo";
+
+/// Checks the padding of a plaintext whose last byte was flipped
+fn sloppy_oracle(ciphertext: &[u8]) -> bool {
+    use aes::cipher::{block_padding::NoPadding, BlockDecryptMut, KeyIvInit};
+
+    let mut buf = ciphertext.to_vec();
+    let mut plaintext = cbc::Decryptor::<aes::Aes128>::new(&[0; 16].into(), &[0; 16].into())
+        .decrypt_padded_mut::<NoPadding>(&mut buf)
+        .unwrap()
+        .to_vec();
+
+    let n = plaintext.len();
+    plaintext[n - 1] ^= 3;
+
+    let pad = plaintext[n - 1] as usize;
+    (1..=16).contains(&pad) && plaintext[n - pad..].iter().all(|&b| b as usize == pad)
+}
+
+/// Wrongly accepts the first full block of padding whose first byte is wrong, once
+fn flaky_oracle(ciphertext: &[u8]) -> bool {
+    use aes::cipher::{block_padding::NoPadding, BlockDecryptMut, KeyIvInit};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static LIED: AtomicBool = AtomicBool::new(false);
+
+    let mut buf = ciphertext.to_vec();
+    let plaintext = cbc::Decryptor::<aes::Aes128>::new(&[0; 16].into(), &[0; 16].into())
+        .decrypt_padded_mut::<NoPadding>(&mut buf)
+        .unwrap();
+
+    let last = &plaintext[plaintext.len() - 16..];
+
+    if last[1..].iter().all(|&b| b == 16) && !LIED.swap(true, Ordering::Relaxed) {
+        return true;
+    }
+
+    oracle(ciphertext)
+}
+
+#[test]
+fn it_checks_the_recovered_padding() {
+    let options = DecryptOptions {
+        check_padding: true,
+        ..Default::default()
+    };
+
+    let plaintext =
+        padding_oracle::decrypt_with_options(&encrypt(PLAINTEXT), 16, oracle, &options).unwrap();
+
+    assert_eq!(plaintext, pad(PLAINTEXT));
+}
+
+#[test]
+fn it_catches_inconsistent_padding() {
+    let options = DecryptOptions {
+        check_padding: true,
+        ..Default::default()
+    };
+
+    // The sloppy oracle makes the attack recover a wrong last byte
+    let ciphertext = encrypt(PLAINTEXT);
+    let unchecked = padding_oracle::decrypt(&ciphertext, 16, sloppy_oracle).unwrap();
+
+    assert_ne!(unchecked, pad(PLAINTEXT));
+
+    assert!(matches!(
+        padding_oracle::decrypt_with_options(&ciphertext, 16, sloppy_oracle, &options),
+        Err(Error::InconsistentPadding)
+    ));
+}
+
+/// Exactly two blocks long, so the last block is only padding
+const ALIGNED: &[u8] = b"000009ith my rag-top down so my ";
+
+#[test]
+fn it_can_assume_a_final_padding_block() {
+    let options = DecryptOptions {
+        final_block: FinalBlock::AssumePadding,
+        ..Default::default()
+    };
+
+    let mut attack = PaddingOracleAttack::with_options(&encrypt(ALIGNED), 16, &options).unwrap();
+
+    while let Some(probe) = attack.next_probe() {
+        attack.submit(oracle(&probe));
+    }
+
+    assert_eq!(attack.stats().block_queries[2], 0);
+    assert_eq!(attack.finish().unwrap(), pad(ALIGNED));
+}
+
+#[test]
+fn it_can_detect_a_final_padding_block() {
+    for plaintext in [ALIGNED, PLAINTEXT] {
+        let options = DecryptOptions {
+            final_block: FinalBlock::DetectPadding,
+            ..Default::default()
+        };
+
+        let ciphertext = encrypt(plaintext);
+        let mut attack = PaddingOracleAttack::with_options(&ciphertext, 16, &options).unwrap();
+
+        while let Some(probe) = attack.next_probe() {
+            attack.submit(oracle(&probe));
+        }
+
+        let last_block_queries = *attack.stats().block_queries.last().unwrap();
+
+        assert_eq!(attack.finish().unwrap(), pad(plaintext));
+
+        // Only the last byte is attacked in a padding block
+        if plaintext == ALIGNED {
+            assert!(last_block_queries <= 256 * 2);
+        } else {
+            assert!(last_block_queries > 256 * 2);
+        }
+    }
+}
+
+#[test]
+fn it_can_shuffle_the_blocks() {
+    let options = DecryptOptions {
+        block_order: BlockOrder::Shuffled { seed: 1337 },
+        ..Default::default()
+    };
+
+    let mut attack = PaddingOracleAttack::with_options(&encrypt(PLAINTEXT), 16, &options).unwrap();
+    let mut order = Vec::new();
+
+    while let Some(probe) = attack.next_probe() {
+        // Truncated probes end with the attacked block
+        if order.last() != Some(&probe.len()) {
+            order.push(probe.len());
+        }
+
+        attack.submit(oracle(&probe));
+    }
+
+    assert_ne!(order, [64, 48, 32]);
+    assert_eq!(attack.finish().unwrap(), pad(PLAINTEXT));
+}
+
+#[test]
+fn minimal_verification_saves_queries() {
+    let options = DecryptOptions {
+        verification: Verification::Minimal,
+        ..Default::default()
+    };
+
+    for plaintext in [PLAINTEXT, ALIGNED, b"000010Will it ever stop? Yo, I do"] {
+        let ciphertext = encrypt(plaintext);
+
+        let mut always = PaddingOracleAttack::new(&ciphertext, 16).unwrap();
+        let mut minimal = PaddingOracleAttack::with_options(&ciphertext, 16, &options).unwrap();
+
+        for attack in [&mut always, &mut minimal] {
+            while let Some(probe) = attack.next_probe() {
+                attack.submit(oracle(&probe));
+            }
+        }
+
+        assert!(minimal.queries() < always.queries());
+        assert_eq!(minimal.finish().unwrap(), pad(plaintext));
+    }
+}
+
+#[test]
+fn it_confirms_every_block() {
+    let options = DecryptOptions {
+        confirm_blocks: true,
+        ..Default::default()
+    };
+
+    let ciphertext = encrypt(PLAINTEXT);
+    let mut confirmed = PaddingOracleAttack::with_options(&ciphertext, 16, &options).unwrap();
+    let mut unconfirmed = PaddingOracleAttack::new(&ciphertext, 16).unwrap();
+
+    for attack in [&mut confirmed, &mut unconfirmed] {
+        while let Some(probe) = attack.next_probe() {
+            attack.submit(oracle(&probe));
+        }
+    }
+
+    assert_eq!(confirmed.queries(), unconfirmed.queries() + 3);
+    assert_eq!(confirmed.finish().unwrap(), pad(PLAINTEXT));
+
+    assert!(matches!(
+        padding_oracle::decrypt_with_options(&ciphertext, 16, flaky_oracle, &options),
+        Err(Error::UnconfirmedBlock { block: 3 })
+    ));
+}
+
+#[test]
+fn it_reports_progress() {
+    use std::sync::{Arc, Mutex};
+
+    let recovered = Arc::new(Mutex::new(Vec::<Progress>::new()));
+    let options = DecryptOptions {
+        progress: Some(ProgressCallback::new({
+            let recovered = recovered.clone();
+            move |progress| recovered.lock().unwrap().push(progress)
+        })),
+        ..Default::default()
+    };
+
+    let plaintext =
+        padding_oracle::decrypt_with_options(&encrypt(PLAINTEXT), 16, oracle, &options).unwrap();
+    let recovered = recovered.lock().unwrap();
+
+    assert_eq!(recovered.len(), plaintext.len());
+    assert!(recovered.windows(2).all(|w| w[0].queries <= w[1].queries));
+
+    for progress in recovered.iter() {
+        assert_eq!(
+            progress.value,
+            plaintext[(progress.block - 1) * 16 + progress.byte]
+        );
+    }
+}
+
+#[test]
+fn it_reports_the_throughput() {
+    use std::sync::mpsc;
+
+    let (sender, receiver) = mpsc::channel();
+    let options = DecryptOptions {
+        progress: Some(ProgressCallback::channel(sender)),
+        blocks: Some(2..4),
+        ..Default::default()
+    };
+
+    let slow = |ciphertext: &[u8]| {
+        std::thread::sleep(std::time::Duration::from_micros(20));
+        oracle(ciphertext)
+    };
+    padding_oracle::decrypt_with_options(&encrypt(PLAINTEXT), 16, slow, &options).unwrap();
+    drop(options);
+
+    let recovered: Vec<Progress> = receiver.iter().collect();
+    assert_eq!(recovered.len(), 32);
+
+    for (i, progress) in recovered.iter().enumerate() {
+        assert_eq!(progress.recovered, i + 1);
+        assert_eq!(progress.total, 32);
+    }
+
+    let last = recovered.last().unwrap();
+    assert_eq!(last.fraction(), 1.0);
+    assert_eq!(last.eta, Some(std::time::Duration::ZERO));
+    assert!(last.queries_per_second.is_some_and(|rate| rate > 0.0));
+}
+
+#[test]
+fn it_sends_progress_over_a_channel() {
+    use std::sync::mpsc;
+
+    let (sender, receiver) = mpsc::channel();
+    let options = DecryptOptions {
+        progress: Some(ProgressCallback::channel(sender)),
+        ..Default::default()
+    };
+
+    let plaintext =
+        padding_oracle::decrypt_with_options(&encrypt(PLAINTEXT), 16, oracle, &options).unwrap();
+    drop(options);
+
+    let recovered: Vec<Progress> = receiver.iter().collect();
+    assert_eq!(recovered.len(), plaintext.len());
+}
+
+#[test]
+fn it_only_attacks_the_selected_blocks() {
+    let ciphertext = encrypt(PLAINTEXT);
+    let options = DecryptOptions {
+        blocks: Some(2..3),
+        ..Default::default()
+    };
+
+    let mut attack = PaddingOracleAttack::with_options(&ciphertext, 16, &options).unwrap();
+
+    while let Some(probe) = attack.next_probe() {
+        attack.submit(oracle(&probe));
+    }
+
+    assert_eq!(attack.stats().block_queries[0], 0);
+    assert_eq!(attack.stats().block_queries[2], 0);
+    assert_eq!(attack.finish().unwrap(), pad(PLAINTEXT)[16..32]);
+
+    for (range, block) in [(0..2, 0), (2..5, 4)] {
+        let options = DecryptOptions {
+            blocks: Some(range),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            padding_oracle::decrypt_with_options(&ciphertext, 16, oracle, &options),
+            Err(Error::InvalidBlock { block: b, blocks: 4 }) if b == block
+        ));
+    }
+}
+
+#[test]
+fn likely_text_first_saves_queries_on_text() {
+    let ciphertext = encrypt(PLAINTEXT);
+    let mut queries = [0; 2];
+
+    for (order, queries) in [CandidateOrder::Sequential, CandidateOrder::LikelyText]
+        .into_iter()
+        .zip(&mut queries)
+    {
+        let options = DecryptOptions {
+            candidate_order: order,
+            ..Default::default()
+        };
+        let mut attack = PaddingOracleAttack::with_options(&ciphertext, 16, &options).unwrap();
+
+        while let Some(probe) = attack.next_probe() {
+            attack.submit(oracle(&probe));
+        }
+
+        *queries = attack.queries();
+        assert_eq!(attack.finish().unwrap(), pad(PLAINTEXT));
+    }
+
+    assert!(queries[1] * 2 < queries[0]);
+}
+
+/// Always guesses right, as if the plaintext was known
+#[derive(Debug)]
+struct Clairvoyant(Vec<u8>);
+
+impl GuessStrategy for Clairvoyant {
+    fn guesses(&self, context: &GuessContext<'_>) -> Vec<u8> {
+        let start = (context.block - 1) * context.blocksize;
+        let i = start + context.byte;
+
+        assert_eq!(context.following, &self.0[i + 1..start + context.blocksize]);
+        assert_eq!(context.last_block, i >= self.0.len() - context.blocksize);
+
+        vec![self.0[i]]
+    }
+}
+
+#[test]
+fn it_follows_custom_guess_strategies() {
+    let options = DecryptOptions {
+        candidate_order: CandidateOrder::Custom(std::sync::Arc::new(Clairvoyant(pad(PLAINTEXT)))),
+        ..Default::default()
+    };
+    let mut attack = PaddingOracleAttack::with_options(&encrypt(PLAINTEXT), 16, &options).unwrap();
+
+    while let Some(probe) = attack.next_probe() {
+        attack.submit(oracle(&probe));
+    }
+
+    // A query for each byte, and another to double check it
+    assert!(attack.queries() <= 2 * pad(PLAINTEXT).len());
+    assert_eq!(attack.finish().unwrap(), pad(PLAINTEXT));
+}
+
+#[test]
+fn it_guesses_english_text_in_few_queries() {
+    const PROSE: &[u8] = b"It was the best of times, it was the worst of times, it was the age of \
+        wisdom, it was the age of foolishness, it was the epoch of belief.";
+
+    let options = DecryptOptions {
+        candidate_order: CandidateOrder::Custom(std::sync::Arc::new(padding_oracle::EnglishText)),
+        ..Default::default()
+    };
+    let mut attack = PaddingOracleAttack::with_options(&encrypt(PROSE), 16, &options).unwrap();
+
+    while let Some(probe) = attack.next_probe() {
+        attack.submit(oracle(&probe));
+    }
+
+    // Including the double checks
+    assert!(attack.queries() < 20 * pad(PROSE).len());
+    assert_eq!(attack.finish().unwrap(), pad(PROSE));
+}
+
+#[test]
+fn it_guesses_the_charset_first() {
+    const SESSION_ID: &[u8] = b"5f4dcc3b5aa765d61d8327deb882cf99";
+    let ciphertext = encrypt(SESSION_ID);
+    let mut queries = [0; 2];
+
+    for (charset, queries) in [None, Some(b"0123456789abcdef".iter().copied().collect())]
+        .into_iter()
+        .zip(&mut queries)
+    {
+        let options = DecryptOptions {
+            charset,
+            ..Default::default()
+        };
+        let mut attack = PaddingOracleAttack::with_options(&ciphertext, 16, &options).unwrap();
+
+        while let Some(probe) = attack.next_probe() {
+            attack.submit(oracle(&probe));
+        }
+
+        *queries = attack.queries();
+        assert_eq!(attack.finish().unwrap(), pad(SESSION_ID));
+    }
+
+    assert!(queries[1] * 4 < queries[0]);
+}
+
+#[test]
+fn it_falls_back_out_of_the_charset() {
+    let options = DecryptOptions {
+        charset: Some(b"0123456789".iter().copied().collect()),
+        candidate_order: CandidateOrder::LikelyText,
+        ..Default::default()
+    };
+    let mut attack = PaddingOracleAttack::with_options(&encrypt(PLAINTEXT), 16, &options).unwrap();
+
+    while let Some(probe) = attack.next_probe() {
+        attack.submit(oracle(&probe));
+    }
+
+    assert_eq!(attack.finish().unwrap(), pad(PLAINTEXT));
+}
+
+#[test]
+fn it_guesses_the_known_plaintext_first() {
+    let ciphertext = encrypt(PLAINTEXT);
+    let mut queries = [0; 3];
+
+    // Nothing known, everything but the last byte known, and everything known wrong
+    let hints = [
+        Vec::new(),
+        PLAINTEXT[..PLAINTEXT.len() - 1]
+            .iter()
+            .copied()
+            .map(Some)
+            .collect(),
+        vec![Some(0xff); PLAINTEXT.len()],
+    ];
+
+    for (known_plaintext, queries) in hints.into_iter().zip(&mut queries) {
+        let options = DecryptOptions {
+            known_plaintext,
+            ..Default::default()
+        };
+        let mut attack = PaddingOracleAttack::with_options(&ciphertext, 16, &options).unwrap();
+
+        while let Some(probe) = attack.next_probe() {
+            attack.submit(oracle(&probe));
+        }
+
+        *queries = attack.queries();
+        assert_eq!(attack.finish().unwrap(), pad(PLAINTEXT));
+    }
+
+    assert!(queries[1] * 4 < queries[0]);
+}
+
+#[test]
+fn it_decrypts_known_intermediates_locally() {
+    let ciphertext = encrypt(PLAINTEXT);
+    let mut attack = PaddingOracleAttack::new(&ciphertext, 16).unwrap();
+
+    while let Some(probe) = attack.next_probe() {
+        attack.submit(oracle(&probe));
+    }
+
+    let known_intermediates = [1, 3]
+        .into_iter()
+        .map(|block| (block, attack.intermediate_block(block).unwrap().to_vec()))
+        .collect();
+    let options = DecryptOptions {
+        known_intermediates,
+        ..Default::default()
+    };
+    let mut resumed = PaddingOracleAttack::with_options(&ciphertext, 16, &options).unwrap();
+
+    while let Some(probe) = resumed.next_probe() {
+        resumed.submit(oracle(&probe));
+    }
+
+    assert_eq!(
+        resumed.stats().block_queries,
+        [0, attack.stats().block_queries[1], 0]
+    );
+    assert_eq!(resumed.finish().unwrap(), pad(PLAINTEXT));
+}
+
+#[test]
+fn it_rejects_misplaced_intermediates() {
+    use padding_oracle::Error;
+
+    let ciphertext = encrypt(PLAINTEXT);
+
+    for (block, size) in [(0, 16), (4, 16), (2, 15)] {
+        let options = DecryptOptions {
+            known_intermediates: [(block, vec![0; size])].into(),
+            ..Default::default()
+        };
+        let error = PaddingOracleAttack::with_options(&ciphertext, 16, &options).unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::InvalidBlock { .. } | Error::WrongSize { .. }
+        ));
+    }
+}
+
+#[test]
+fn it_stops_once_the_time_is_up() {
+    use std::time::{Duration, Instant};
+
+    let ciphertext = encrypt(PLAINTEXT);
+    let options = DecryptOptions {
+        timeout: Some(Duration::from_millis(50)),
+        ..Default::default()
+    };
+
+    // Only the last block is fast to recover
+    let slow_oracle = |probe: &[u8]| {
+        if probe.len() < ciphertext.len() {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        oracle(probe)
+    };
+
+    let start = Instant::now();
+    let error =
+        padding_oracle::decrypt_with_options(&ciphertext, 16, slow_oracle, &options).unwrap_err();
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    let Error::TimedOut { partial, stats } = error else {
+        panic!("unexpected error: {error}");
+    };
+    assert_eq!(partial, blocks(PLAINTEXT)[2..]);
+    assert!(stats.duration >= Duration::from_millis(50));
+}
+
+#[test]
+fn it_stops_once_the_queries_are_spent() {
+    let ciphertext = encrypt(PLAINTEXT);
+    let queries = padding_oracle::decrypt_with_stats(&ciphertext, 16, oracle)
+        .unwrap()
+        .1
+        .queries;
+
+    // The last block costs less than a third of the queries
+    let options = DecryptOptions {
+        max_queries: Some(queries / 3),
+        ..Default::default()
+    };
+    let error =
+        padding_oracle::decrypt_with_options(&ciphertext, 16, oracle, &options).unwrap_err();
+
+    let Error::QueryBudgetExceeded { partial, stats } = error else {
+        panic!("unexpected error: {error}");
+    };
+    assert_eq!(stats.queries, queries / 3);
+    assert!(blocks(PLAINTEXT).ends_with(&partial));
+
+    let options = DecryptOptions {
+        max_queries: Some(queries),
+        ..Default::default()
+    };
+    let plaintext = padding_oracle::decrypt_with_options(&ciphertext, 16, oracle, &options);
+    assert_eq!(plaintext.unwrap(), pad(PLAINTEXT));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn it_serializes_the_options() {
+    let options = DecryptOptions {
+        block_order: BlockOrder::Shuffled { seed: 42 },
+        final_block: FinalBlock::DetectPadding,
+        charset: Some(b"0123456789abcdef".iter().copied().collect()),
+        known_plaintext: vec![Some(b'{'), None],
+        blocks: Some(1..3),
+        progress: Some(ProgressCallback::new(|_| {})),
+        ..Default::default()
+    };
+
+    let json = serde_json::to_string(&options).unwrap();
+    let resumed: DecryptOptions = serde_json::from_str(&json).unwrap();
+    assert_eq!(resumed.block_order, options.block_order);
+    assert_eq!(resumed.final_block, options.final_block);
+    assert_eq!(resumed.charset, options.charset);
+    assert_eq!(resumed.known_plaintext, options.known_plaintext);
+    assert_eq!(resumed.blocks, options.blocks);
+    assert!(resumed.progress.is_none());
+
+    // Missing fields take their default value
+    let partial: DecryptOptions = serde_json::from_str(r#"{"retries":3}"#).unwrap();
+    assert_eq!(partial.retries, 3);
+    assert_eq!(partial.verification, Verification::Always);
+
+    let custom = DecryptOptions {
+        candidate_order: CandidateOrder::Custom(std::sync::Arc::new(padding_oracle::EnglishText)),
+        ..Default::default()
+    };
+    assert!(serde_json::to_string(&custom).is_err());
+}
+
+#[test]
+fn it_reuses_intermediates_across_ciphertexts() {
+    use padding_oracle::IntermediateCache;
+    use std::sync::Arc;
+
+    let cache = Arc::new(IntermediateCache::new());
+    let options = DecryptOptions {
+        intermediate_cache: Some(cache.clone()),
+        ..Default::default()
+    };
+
+    let queries = std::cell::Cell::new(0);
+    let counting = |probe: &[u8]| {
+        queries.set(queries.get() + 1);
+        oracle(probe)
+    };
+
+    let first = b"000007Same prefix, same blocks and then it differs";
+    let recovered = padding_oracle::decrypt_with_options(&encrypt(first), 16, counting, &options);
+    assert_eq!(recovered.unwrap(), pad(first));
+    assert_eq!(cache.len(), 4);
+
+    // Same IV and key, so the first two blocks are the same
+    queries.set(0);
+    let second = b"000007Same prefix, same blocks and not the end";
+    let recovered = padding_oracle::decrypt_with_options(&encrypt(second), 16, counting, &options);
+    assert_eq!(recovered.unwrap(), pad(second));
+    assert_eq!(cache.len(), 5);
+
+    // Only the last block was attacked
+    let alone = padding_oracle::decrypt_with_stats(&encrypt(second), 16, oracle)
+        .unwrap()
+        .1;
+    assert!(queries.get() < alone.queries / 2);
+}
+
+#[test]
+fn it_only_caches_the_blocks_which_pass_the_checks() {
+    use padding_oracle::IntermediateCache;
+    use std::sync::Arc;
+
+    let cache = Arc::new(IntermediateCache::new());
+    let options = DecryptOptions {
+        check_padding: true,
+        intermediate_cache: Some(cache.clone()),
+        ..Default::default()
+    };
+
+    // The sloppy oracle makes the attack recover a wrong last block
+    let ciphertext = encrypt(PLAINTEXT);
+    assert!(matches!(
+        padding_oracle::decrypt_with_options(&ciphertext, 16, sloppy_oracle, &options),
+        Err(Error::InconsistentPadding)
+    ));
+    assert_eq!(cache.get(&ciphertext[ciphertext.len() - 16..]), None);
+
+    let plaintext = padding_oracle::decrypt_with_options(&ciphertext, 16, oracle, &options);
+    assert_eq!(plaintext.unwrap(), pad(PLAINTEXT));
+}
@@ -0,0 +1,422 @@
+mod common;
+
+use common::{encrypt, oracle, pad};
+use std::time::{Duration, Instant};
+
+use padding_oracle::{
+    Balancing, DecryptOptions, Error, Failover, LoadBalanced, MacResult, MajorityVote, Memoized,
+    OracleResult, OracleUnavailable, PaddingOracle, PaddingScheme, RefreshSchedule, Retrying,
+    SessionExpired, Stateful, StatefulOracle, Transformed,
+};
+
+/// An oracle keeping track of what it was sent
+#[derive(Default)]
+struct RecordingOracle {
+    longest: usize,
+    queries: usize,
+}
+
+impl PaddingOracle for RecordingOracle {
+    type Response = bool;
+
+    fn query(&mut self, ciphertext: &[u8]) -> bool {
+        self.longest = self.longest.max(ciphertext.len());
+        self.queries += 1;
+
+        oracle(ciphertext)
+    }
+}
+
+#[test]
+fn it_accepts_stateful_oracles() {
+    let plaintexts: [&[u8]; 2] = [
+        b"YELLOW SUBMARINE",
+        b"000003Cooking MC's like a pound of bacon",
+    ];
+    let mut oracle = RecordingOracle::default();
+
+    for plaintext in plaintexts {
+        let recovered = padding_oracle::decrypt(&encrypt(plaintext), 16, oracle.by_ref()).unwrap();
+        assert_eq!(recovered, pad(plaintext));
+    }
+
+    assert_eq!(oracle.longest, encrypt(plaintexts[1]).len());
+    assert!(oracle.queries > 5 * 16);
+}
+
+/// An oracle whose every `period`th query times out
+fn unreliable_oracle(period: usize) -> impl FnMut(&[u8]) -> std::io::Result<bool> {
+    let mut queries = 0;
+
+    move |ciphertext| {
+        queries += 1;
+
+        if queries % period == 0 {
+            Err(std::io::ErrorKind::TimedOut.into())
+        } else {
+            Ok(oracle(ciphertext))
+        }
+    }
+}
+
+#[test]
+fn it_propagates_oracle_errors() {
+    let ciphertext = encrypt(b"YELLOW SUBMARINE");
+
+    let error = padding_oracle::decrypt(&ciphertext, 16, unreliable_oracle(100)).unwrap_err();
+    assert!(matches!(error, Error::Oracle(_)));
+}
+
+#[test]
+fn it_retries_failed_queries() {
+    let plaintext = b"000003Cooking MC's like a pound of bacon";
+    let options = DecryptOptions {
+        retries: 1,
+        ..Default::default()
+    };
+
+    let recovered = padding_oracle::decrypt_with_options(
+        &encrypt(plaintext),
+        16,
+        unreliable_oracle(100),
+        &options,
+    )
+    .unwrap();
+    assert_eq!(recovered, pad(plaintext));
+}
+
+/// An oracle which can't tell every `period`th query, as if it was rate limited
+fn rate_limited_oracle(period: usize) -> impl FnMut(&[u8]) -> OracleResult {
+    let mut queries = 0;
+
+    move |ciphertext| {
+        queries += 1;
+
+        if queries % period == 0 {
+            OracleResult::Inconclusive
+        } else {
+            oracle(ciphertext).into()
+        }
+    }
+}
+
+#[test]
+fn it_retries_inconclusive_queries() {
+    let plaintext = b"000003Cooking MC's like a pound of bacon";
+    let ciphertext = encrypt(plaintext);
+
+    let error = padding_oracle::decrypt(&ciphertext, 16, rate_limited_oracle(10)).unwrap_err();
+    assert!(matches!(error, Error::Inconclusive));
+
+    let options = DecryptOptions {
+        retries: 1,
+        ..Default::default()
+    };
+
+    let recovered =
+        padding_oracle::decrypt_with_options(&ciphertext, 16, rate_limited_oracle(10), &options)
+            .unwrap();
+    assert_eq!(recovered, pad(plaintext));
+}
+
+/// An oracle giving the wrong answer every `period`th query
+fn noisy_oracle(period: usize) -> impl FnMut(&[u8]) -> bool {
+    let mut queries = 0;
+
+    move |ciphertext| {
+        queries += 1;
+        oracle(ciphertext) ^ (queries % period == 0)
+    }
+}
+
+#[test]
+fn it_outvotes_noisy_answers() {
+    let plaintext = b"000003Cooking MC's like a pound of bacon";
+    let ciphertext = encrypt(plaintext);
+
+    let recovered =
+        padding_oracle::decrypt(&ciphertext, 16, MajorityVote::new(noisy_oracle(7), 3)).unwrap();
+    assert_eq!(recovered, pad(plaintext));
+}
+
+#[test]
+fn it_stops_voting_once_there_is_a_majority() {
+    let mut queries = 0;
+    let mut vote = MajorityVote::new(
+        |_: &[u8]| {
+            queries += 1;
+            true
+        },
+        5,
+    );
+
+    assert_eq!(vote.query(&[]).unwrap(), OracleResult::PaddingValid);
+    assert_eq!(queries, 3);
+
+    // Two votes each, and one inconclusive
+    let mut answers = [true, false, true, false].map(OracleResult::from).to_vec();
+    answers.push(OracleResult::Inconclusive);
+    let mut vote = MajorityVote::new(move |_: &[u8]| answers.remove(0), 5);
+    assert_eq!(vote.query(&[]).unwrap(), OracleResult::Inconclusive);
+}
+
+#[test]
+fn it_retries_with_backoff() {
+    let plaintext = b"YELLOW SUBMARINE";
+    let ciphertext = encrypt(plaintext);
+
+    let oracle = Retrying::new(unreliable_oracle(5), 2, Duration::from_micros(10));
+    let recovered = padding_oracle::decrypt(&ciphertext, 16, oracle).unwrap();
+    assert_eq!(recovered, pad(plaintext));
+
+    // Down for good
+    let mut attempts = 0;
+    let oracle = Retrying::with_max_backoff(
+        |_: &[u8]| {
+            attempts += 1;
+            OracleResult::Inconclusive
+        },
+        4,
+        Duration::from_millis(1),
+        Duration::from_millis(2),
+    );
+    let start = Instant::now();
+
+    let error = padding_oracle::decrypt(&ciphertext, 16, oracle).unwrap_err();
+    let Error::Oracle(error) = error else {
+        panic!("unexpected error: {error}");
+    };
+    let unavailable = error.downcast_ref::<OracleUnavailable>().unwrap();
+
+    assert_eq!(unavailable.attempts, 4);
+    assert!(unavailable.last_error.is_none());
+    assert_eq!(attempts, 4);
+
+    // 1ms, then 2ms twice
+    assert!(start.elapsed() >= Duration::from_millis(5));
+}
+
+#[test]
+fn it_transforms_the_probes() {
+    let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{b:02x}")).collect::<String>();
+    let unhex = |text: &[u8]| {
+        text.chunks(2)
+            .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    // Only understands hex in a JSON envelope
+    let json_oracle = |message: &[u8]| {
+        let message = message
+            .strip_prefix(br#"{"token":""#)
+            .and_then(|m| m.strip_suffix(br#""}"#));
+
+        message.is_some_and(|m| oracle(&unhex(m)))
+    };
+
+    let oracle = Transformed::new(json_oracle, |probe: &[u8]| {
+        format!(r#"{{"token":"{}"}}"#, hex(probe)).into_bytes()
+    });
+
+    let plaintext = b"000003Cooking MC's like a pound of bacon";
+    let recovered = padding_oracle::decrypt(&encrypt(plaintext), 16, oracle);
+    assert_eq!(recovered.unwrap(), pad(plaintext));
+}
+
+#[test]
+fn it_treats_mac_errors_as_valid_paddings() {
+    use aes::cipher::{block_padding::NoPadding, BlockDecryptMut, KeyIvInit};
+
+    // The last byte before the padding is a toy MAC: the sum of the others
+    let mac_oracle = |ciphertext: &[u8]| {
+        let mut buf = ciphertext.to_vec();
+        let plaintext = cbc::Decryptor::<aes::Aes128>::new(&[0; 16].into(), &[0; 16].into())
+            .decrypt_padded_mut::<NoPadding>(&mut buf)
+            .unwrap();
+
+        match PaddingScheme::Pkcs7.unpad(&plaintext[16..]) {
+            None => MacResult::PaddingError,
+            Some([data @ .., mac]) if data.iter().fold(0u8, |a, &b| a.wrapping_add(b)) == *mac => {
+                MacResult::Accepted
+            }
+            Some(_) => MacResult::MacError,
+        }
+    };
+
+    let mut plaintext = b"000004Burning 'em, if you ain't quick".to_vec();
+    plaintext.push(plaintext.iter().fold(0u8, |a, &b| a.wrapping_add(b)));
+    let ciphertext = encrypt(&plaintext);
+    assert_eq!(mac_oracle(&ciphertext), MacResult::Accepted);
+
+    let recovered = padding_oracle::decrypt(&ciphertext, 16, mac_oracle);
+    assert_eq!(recovered.unwrap(), pad(&plaintext));
+}
+
+#[test]
+fn it_remembers_the_answers() {
+    let plaintext = b"000005I go crazy when I hear a cymbal";
+    let ciphertext = encrypt(plaintext);
+
+    let mut oracle = Memoized::new(RecordingOracle::default(), 100_000);
+    let first = padding_oracle::decrypt(&ciphertext, 16, oracle.by_ref()).unwrap();
+    let queries = oracle.stats().misses;
+
+    let again = padding_oracle::decrypt(&ciphertext, 16, oracle.by_ref()).unwrap();
+    assert_eq!(first, again);
+    assert_eq!(oracle.stats().misses, queries);
+    assert_eq!(oracle.stats().hits, queries);
+    assert_eq!(oracle.into_inner().queries, queries);
+
+    // Only the last answers are kept
+    let mut oracle = Memoized::new(RecordingOracle::default(), 10);
+    padding_oracle::decrypt(&ciphertext, 16, oracle.by_ref()).unwrap();
+    assert_eq!(oracle.len(), 10);
+
+    padding_oracle::decrypt(&ciphertext, 16, oracle.by_ref()).unwrap();
+    assert!(oracle.stats().hits < 10);
+}
+
+#[test]
+fn it_spreads_the_queries_over_the_endpoints() {
+    let plaintext = b"000005I go crazy when I hear a cymbal";
+    let ciphertext = encrypt(plaintext);
+
+    for balancing in [Balancing::RoundRobin, Balancing::LeastLoaded] {
+        let endpoints = (0..3).map(|_| RecordingOracle::default());
+        let mut oracle = LoadBalanced::new(endpoints, balancing);
+
+        let recovered = padding_oracle::decrypt(&ciphertext, 16, oracle.by_ref());
+        assert_eq!(recovered.unwrap(), pad(plaintext));
+        assert_eq!(oracle.in_flight(), [0, 0, 0]);
+
+        let queries: Vec<usize> = oracle.into_inner().iter().map(|e| e.queries).collect();
+        assert!(queries.iter().max().unwrap() - queries.iter().min().unwrap() <= 1);
+    }
+}
+
+/// An oracle going down for good after `queries` queries
+fn dying_oracle(queries: usize) -> impl FnMut(&[u8]) -> std::io::Result<bool> {
+    let mut left = queries;
+
+    move |ciphertext| match left.checked_sub(1) {
+        Some(next) => {
+            left = next;
+            Ok(oracle(ciphertext))
+        }
+        None => Err(std::io::ErrorKind::ConnectionRefused.into()),
+    }
+}
+
+#[test]
+fn it_fails_over_to_the_next_endpoint() {
+    let plaintext = b"000003Cooking MC's like a pound of bacon";
+    let ciphertext = encrypt(plaintext);
+
+    let mut failovers = Vec::new();
+    let endpoints = [dying_oracle(500), dying_oracle(usize::MAX)];
+    let mut oracle = Failover::with_hook(endpoints, 3, |from, to, error| {
+        failovers.push((from, to, error.to_string()))
+    });
+
+    let recovered = padding_oracle::decrypt(&ciphertext, 16, oracle.by_ref());
+    assert_eq!(recovered.unwrap(), pad(plaintext));
+    assert_eq!(oracle.active(), 1);
+
+    drop(oracle);
+    assert_eq!(failovers.len(), 1);
+    assert_eq!(failovers[0].0, 0);
+    assert_eq!(failovers[0].1, 1);
+
+    // The query fails once every endpoint is down
+    let oracle = Failover::new([dying_oracle(100), dying_oracle(200)], 2);
+    let error = padding_oracle::decrypt(&ciphertext, 16, oracle).unwrap_err();
+    assert!(matches!(error, Error::Oracle(_)));
+}
+
+/// An oracle logging in, whose sessions expire after `lifetime` queries
+#[derive(Default)]
+struct SessionOracle {
+    lifetime: usize,
+    left: usize,
+    setups: usize,
+    refreshes: usize,
+    teardowns: std::rc::Rc<std::cell::Cell<usize>>,
+}
+
+impl StatefulOracle for SessionOracle {
+    type Response = bool;
+
+    fn setup(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if self.lifetime == 0 {
+            return Err("wrong password".into());
+        }
+
+        self.setups += 1;
+        self.left = self.lifetime;
+        Ok(())
+    }
+
+    fn query(&mut self, ciphertext: &[u8]) -> Result<bool, SessionExpired> {
+        self.left = self.left.checked_sub(1).ok_or(SessionExpired)?;
+        Ok(oracle(ciphertext))
+    }
+
+    fn refresh(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.refreshes += 1;
+        self.left = self.lifetime;
+        Ok(())
+    }
+
+    fn teardown(&mut self) {
+        self.teardowns.set(self.teardowns.get() + 1);
+    }
+}
+
+#[test]
+fn it_manages_the_session() {
+    let plaintext = b"000004Burning 'em, if you ain't quick and nimble";
+    let ciphertext = encrypt(plaintext);
+    let teardowns = std::rc::Rc::new(std::cell::Cell::new(0));
+
+    let session = SessionOracle {
+        lifetime: 300,
+        teardowns: teardowns.clone(),
+        ..Default::default()
+    };
+    let mut oracle = Stateful::new(session);
+    assert!(!oracle.is_open());
+
+    let recovered = padding_oracle::decrypt(&ciphertext, 16, oracle.by_ref());
+    assert_eq!(recovered.unwrap(), pad(plaintext));
+    assert!(oracle.is_open());
+
+    let session = oracle.into_inner();
+    assert_eq!(session.setups, 1);
+    assert!(session.refreshes > 0);
+    assert_eq!(teardowns.get(), 1);
+
+    // Refreshed before it expires
+    let session = SessionOracle {
+        lifetime: 300,
+        ..Default::default()
+    };
+    let schedule = RefreshSchedule {
+        queries: Some(100),
+        ..Default::default()
+    };
+    let mut oracle = Stateful::with_schedule(session, schedule);
+    padding_oracle::decrypt(&ciphertext, 16, oracle.by_ref()).unwrap();
+    assert!(oracle.into_inner().left >= 200);
+
+    // Torn down when dropped
+    let session = SessionOracle {
+        lifetime: 300,
+        teardowns: teardowns.clone(),
+        ..Default::default()
+    };
+    padding_oracle::decrypt(&ciphertext, 16, Stateful::new(session)).unwrap();
+    assert_eq!(teardowns.get(), 2);
+
+    let error = padding_oracle::decrypt(&ciphertext, 16, Stateful::new(SessionOracle::default()));
+    assert!(matches!(error, Err(Error::Oracle(_))));
+}
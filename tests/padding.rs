@@ -0,0 +1,159 @@
+mod common;
+
+use aes::cipher::block_padding::{AnsiX923, Iso10126, Iso7816, NoPadding, Padding, Pkcs7};
+use aes::cipher::consts::U16;
+use aes::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use common::{IV, KEY};
+use padding_oracle::{DecryptOptions, FinalBlock, PaddingScheme, Verification};
+
+type Aes128CbcEnc = cbc::Encryptor<aes::Aes128>;
+type Aes128CbcDec = cbc::Decryptor<aes::Aes128>;
+
+const PLAINTEXT: &[u8] = b"000008ooming with the bass, you don't ever believe";
+
+/// An oracle checking the padding `P`
+fn oracle<P: Padding<U16>>(ciphertext: &[u8]) -> bool {
+    let mut buf = ciphertext.to_vec();
+
+    Aes128CbcDec::new(&KEY.into(), &IV.into())
+        .decrypt_padded_mut::<P>(&mut buf)
+        .is_ok()
+}
+
+/// Pad the plaintext with `P`, encrypt it and prepend the IV
+fn encrypt<P: Padding<U16>>(plaintext: &[u8]) -> Vec<u8> {
+    let mut ciphertext = vec![0u8; (plaintext.len() / 16 + 1) * 16];
+
+    ciphertext[..plaintext.len()].copy_from_slice(plaintext);
+
+    let ciphertext = Aes128CbcEnc::new(&KEY.into(), &IV.into())
+        .encrypt_padded_mut::<P>(&mut ciphertext, plaintext.len())
+        .unwrap();
+
+    IV.iter().chain(ciphertext.iter()).copied().collect()
+}
+
+/// The padded plaintext, as the attack recovers it
+fn decrypt_raw(ciphertext: &[u8]) -> Vec<u8> {
+    let mut buf = ciphertext.to_vec();
+
+    Aes128CbcDec::new(&KEY.into(), &IV.into())
+        .decrypt_padded_mut::<NoPadding>(&mut buf)
+        .unwrap()[16..]
+        .to_vec()
+}
+
+/// Attack a ciphertext padded with `P`, whose oracle checks that padding
+fn attack<P: Padding<U16>>(plaintext: &[u8], options: &DecryptOptions) -> Vec<u8> {
+    let ciphertext = encrypt::<P>(plaintext);
+    let recovered =
+        padding_oracle::decrypt_with_options(&ciphertext, 16, oracle::<P>, options).unwrap();
+
+    assert_eq!(recovered, decrypt_raw(&ciphertext));
+    recovered
+}
+
+#[test]
+fn it_decrypts_ansi_x923() {
+    let options = DecryptOptions {
+        padding: PaddingScheme::AnsiX923,
+        check_padding: true,
+        precheck: true,
+        ..Default::default()
+    };
+
+    for plaintext in [PLAINTEXT, &PLAINTEXT[..48]] {
+        let recovered = attack::<AnsiX923>(plaintext, &options);
+        assert_eq!(PaddingScheme::AnsiX923.unpad(&recovered), Some(plaintext));
+    }
+}
+
+#[test]
+fn it_detects_ansi_x923_padding_blocks() {
+    let options = DecryptOptions {
+        padding: PaddingScheme::AnsiX923,
+        final_block: FinalBlock::DetectPadding,
+        ..Default::default()
+    };
+
+    let recovered = attack::<AnsiX923>(&PLAINTEXT[..32], &options);
+    assert_eq!(
+        PaddingScheme::AnsiX923.unpad(&recovered),
+        Some(&PLAINTEXT[..32])
+    );
+}
+
+#[test]
+fn it_decrypts_iso_7816() {
+    for verification in [Verification::Always, Verification::Minimal] {
+        let options = DecryptOptions {
+            padding: PaddingScheme::Iso7816,
+            verification,
+            check_padding: true,
+            precheck: true,
+            confirm_blocks: true,
+            ..Default::default()
+        };
+
+        for plaintext in [PLAINTEXT, &PLAINTEXT[..48], b"\x80\x00\x00"] {
+            let recovered = attack::<Iso7816>(plaintext, &options);
+            assert_eq!(PaddingScheme::Iso7816.unpad(&recovered), Some(plaintext));
+        }
+    }
+}
+
+#[test]
+fn it_recovers_the_last_bytes_with_iso_10126() {
+    let ciphertext = encrypt::<Iso10126>(PLAINTEXT);
+    let mut queries = 0;
+
+    let last_bytes = padding_oracle::recover_last_bytes(&ciphertext, 16, |probe: &[u8]| {
+        queries += 1;
+        oracle::<Iso10126>(probe)
+    })
+    .unwrap();
+
+    let expected: Vec<u8> = decrypt_raw(&ciphertext).chunks(16).map(|b| b[15]).collect();
+    assert_eq!(last_bytes, expected);
+
+    // Instead of 128 on average for a PKCS7 byte
+    assert!(queries < 40 * last_bytes.len());
+}
+
+#[test]
+fn it_refuses_to_attack_iso_10126() {
+    let options = DecryptOptions {
+        padding: PaddingScheme::Iso10126,
+        ..Default::default()
+    };
+    let error = padding_oracle::PaddingOracleAttack::with_options(
+        &encrypt::<Iso10126>(PLAINTEXT),
+        16,
+        &options,
+    )
+    .unwrap_err();
+
+    assert!(matches!(
+        error,
+        padding_oracle::Error::UnsupportedPadding {
+            padding: PaddingScheme::Iso10126
+        }
+    ));
+}
+
+#[test]
+fn it_detects_the_padding_scheme() {
+    fn detect<P: Padding<U16>>(plaintext: &[u8]) -> Option<PaddingScheme> {
+        padding_oracle::detect_padding_scheme(&encrypt::<P>(plaintext), 16, oracle::<P>).unwrap()
+    }
+
+    // Paddings of 1, 2, 3, 13 and 16 bytes
+    for length in [47, 46, 45, 35, 32] {
+        let plaintext = &PLAINTEXT[..length];
+
+        assert_eq!(detect::<Pkcs7>(plaintext), Some(PaddingScheme::Pkcs7));
+        assert_eq!(detect::<AnsiX923>(plaintext), Some(PaddingScheme::AnsiX923));
+        assert_eq!(detect::<Iso7816>(plaintext), Some(PaddingScheme::Iso7816));
+        assert_eq!(detect::<Iso10126>(plaintext), Some(PaddingScheme::Iso10126));
+    }
+}
@@ -0,0 +1,156 @@
+#![cfg(feature = "rayon")]
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use common::{blocks, encrypt, oracle, pad};
+use padding_oracle::{DecryptOptions, Error, FinalBlock};
+
+const PLAINTEXT: &[u8] = b"000001With the bass kicked in and the Vega's are pumpin'";
+
+#[test]
+fn it_can_decrypt_in_parallel() {
+    let queries = AtomicUsize::new(0);
+    let counting_oracle = |probe: &[u8]| {
+        queries.fetch_add(1, Ordering::Relaxed);
+        oracle(probe)
+    };
+
+    let plaintext = padding_oracle::decrypt_parallel(&encrypt(PLAINTEXT), 16, counting_oracle);
+    assert_eq!(plaintext.unwrap(), pad(PLAINTEXT));
+
+    // Each block gets checked once on its own
+    let sequential = AtomicUsize::new(0);
+    padding_oracle::decrypt(&encrypt(PLAINTEXT), 16, |probe: &[u8]| {
+        sequential.fetch_add(1, Ordering::Relaxed);
+        oracle(probe)
+    })
+    .unwrap();
+
+    assert_eq!(queries.into_inner(), sequential.into_inner());
+}
+
+#[test]
+fn it_keeps_the_options_in_parallel() {
+    let aligned = b"000009ith my rag-top down so my ";
+    let options = DecryptOptions {
+        final_block: FinalBlock::AssumePadding,
+        ..Default::default()
+    };
+
+    let plaintext =
+        padding_oracle::decrypt_parallel_with_options(&encrypt(aligned), 16, oracle, &options);
+    assert_eq!(plaintext.unwrap(), pad(aligned));
+
+    assert!(matches!(
+        padding_oracle::decrypt_parallel(&encrypt(PLAINTEXT), 16, |_: &[u8]| false),
+        Err(Error::InvalidPadding { .. })
+    ));
+}
+
+#[test]
+fn it_shares_the_query_budget_between_the_blocks() {
+    let ciphertext = encrypt(PLAINTEXT);
+    let stats = padding_oracle::decrypt_with_stats(&ciphertext, 16, oracle)
+        .unwrap()
+        .1;
+    let most = *stats.block_queries.iter().max().unwrap();
+    let budget = most + (stats.queries - most) / 2;
+
+    let queries = AtomicUsize::new(0);
+    let counting_oracle = |probe: &[u8]| {
+        queries.fetch_add(1, Ordering::Relaxed);
+        oracle(probe)
+    };
+    let options = DecryptOptions {
+        max_queries: Some(budget),
+        ..Default::default()
+    };
+
+    let error =
+        padding_oracle::decrypt_parallel_with_options(&ciphertext, 16, counting_oracle, &options);
+    let Err(Error::QueryBudgetExceeded { partial, stats }) = error else {
+        panic!("the budget isn't enforced: {error:?}");
+    };
+
+    assert_eq!(queries.into_inner(), budget);
+    assert_eq!(stats.queries, budget);
+    assert!(partial
+        .iter()
+        .all(|block| blocks(PLAINTEXT).contains(block)));
+}
+
+#[test]
+fn it_times_out_the_whole_attack() {
+    use std::time::{Duration, Instant};
+
+    let slow_oracle = |probe: &[u8]| {
+        std::thread::sleep(Duration::from_micros(50));
+        oracle(probe)
+    };
+    let options = DecryptOptions {
+        timeout: Some(Duration::from_millis(450)),
+        ..Default::default()
+    };
+
+    // One block at a time, so each block alone would fit in the time limit
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+    let started = Instant::now();
+    let error = pool.install(|| {
+        padding_oracle::decrypt_parallel_with_options(
+            &encrypt(PLAINTEXT),
+            16,
+            slow_oracle,
+            &options,
+        )
+    });
+
+    let Err(Error::TimedOut { stats, .. }) = error else {
+        panic!("the time limit isn't enforced: {error:?}");
+    };
+    assert!(stats.duration >= Duration::from_millis(450));
+    assert!(started.elapsed() < Duration::from_millis(800));
+}
+
+#[test]
+fn it_can_guess_bytes_in_parallel() {
+    let plaintext = padding_oracle::decrypt_speculative(&encrypt(PLAINTEXT), 16, oracle);
+    assert_eq!(plaintext.unwrap(), pad(PLAINTEXT));
+
+    // Two equally valid paddings for the last byte
+    let plaintext = b"000010Will it ever stop? Yo, I do";
+    let recovered = padding_oracle::decrypt_speculative(&encrypt(plaintext), 16, oracle);
+    assert_eq!(recovered.unwrap(), pad(plaintext));
+}
+
+#[test]
+fn it_keeps_to_the_query_budget_when_guessing_in_parallel() {
+    for budget in [5, 300] {
+        let queries = AtomicUsize::new(0);
+        let counting_oracle = |probe: &[u8]| {
+            queries.fetch_add(1, Ordering::Relaxed);
+            oracle(probe)
+        };
+        let options = DecryptOptions {
+            max_queries: Some(budget),
+            ..Default::default()
+        };
+
+        let error = padding_oracle::decrypt_speculative_with_options(
+            &encrypt(PLAINTEXT),
+            16,
+            counting_oracle,
+            &options,
+        );
+        let Err(Error::QueryBudgetExceeded { stats, .. }) = error else {
+            panic!("the budget isn't enforced: {error:?}");
+        };
+
+        assert_eq!(queries.into_inner(), budget);
+        assert_eq!(stats.queries, budget);
+    }
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use aes::cipher::{block_padding::NoPadding, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use common::KEY;
+use padding_oracle::{AlignedRecord, Error};
+
+const SECRET: &[u8] = b"session=0123456789abcdef&admin=0";
+
+/// Encrypt `pad` bytes, the secret, and an SSLv3 padding of a whole block, under a fresh IV
+fn record(pad: usize, iv: u128) -> Vec<u8> {
+    let mut plaintext = vec![b'A'; pad];
+    plaintext.extend_from_slice(SECRET);
+    plaintext.resize(plaintext.len().next_multiple_of(16), b'B');
+    plaintext.extend_from_slice(&[0x42; 15]);
+    plaintext.push(15);
+
+    let iv = iv.to_le_bytes();
+    let len = plaintext.len();
+    let mut ciphertext = iv.to_vec();
+    ciphertext.extend_from_slice(
+        cbc::Encryptor::<aes::Aes128>::new(&KEY.into(), &iv.into())
+            .encrypt_padded_mut::<NoPadding>(&mut plaintext, len)
+            .unwrap(),
+    );
+    ciphertext
+}
+
+/// Only checks the padding length, which must be a whole block for the MAC to be found
+fn sslv3_oracle(ciphertext: &[u8]) -> bool {
+    let mut buf = ciphertext.to_vec();
+    let plaintext = cbc::Decryptor::<aes::Aes128>::new(&KEY.into(), &[0; 16].into())
+        .decrypt_padded_mut::<NoPadding>(&mut buf)
+        .unwrap();
+
+    plaintext[plaintext.len() - 1] == 15
+}
+
+#[test]
+fn it_decrypts_sslv3_records() {
+    let mut iv = 0usize;
+    let secret = padding_oracle::decrypt_poodle(
+        16,
+        SECRET.len(),
+        |byte| {
+            iv += 1;
+            let pad = 15 - byte % 16;
+            AlignedRecord {
+                ciphertext: record(pad, iv as u128),
+                block: (pad + byte) / 16 + 1,
+            }
+        },
+        sslv3_oracle,
+    );
+
+    assert_eq!(secret.unwrap(), SECRET);
+    assert!(iv < 256 * 4 * SECRET.len());
+}
+
+#[test]
+fn it_rejects_misaligned_records() {
+    let secret = padding_oracle::decrypt_poodle(
+        16,
+        1,
+        |_| AlignedRecord {
+            ciphertext: record(15, 1),
+            block: 4,
+        },
+        sslv3_oracle,
+    );
+
+    assert!(matches!(
+        secret,
+        Err(Error::InvalidBlock {
+            block: 4,
+            blocks: 5
+        })
+    ));
+}
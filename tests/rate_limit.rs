@@ -0,0 +1,131 @@
+mod common;
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use common::{encrypt, oracle, pad};
+use padding_oracle::{DecryptOptions, MajorityVote, PaddingOracle, RateLimiter, Throttled};
+
+#[test]
+fn it_limits_the_rate_across_threads() {
+    let limiter = Arc::new(RateLimiter::new(200.0));
+    let start = Instant::now();
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let limiter = limiter.clone();
+            thread::spawn(move || (0..25).for_each(|_| limiter.acquire()))
+        })
+        .collect();
+
+    threads.into_iter().for_each(|t| t.join().unwrap());
+
+    // 100 calls at 200 calls per second, minus the first one
+    assert!(start.elapsed() >= Duration::from_millis(490));
+}
+
+#[test]
+fn it_is_consulted_by_records_attacks() {
+    let plaintext = b"000008ollin' in my five point oh";
+    let options = DecryptOptions {
+        rate_limiter: Some(Arc::new(RateLimiter::with_burst(1e6, 1000))),
+        ..Default::default()
+    };
+
+    let records = [encrypt(plaintext)];
+    let records: Vec<&[u8]> = records.iter().map(Vec::as_slice).collect();
+
+    let plaintexts =
+        padding_oracle::decrypt_records_with_options(&records, 16, oracle, &options).unwrap();
+
+    assert_eq!(plaintexts, [pad(plaintext)]);
+}
+
+#[test]
+fn it_throttles_wrapped_oracles() {
+    let limiter = Arc::new(RateLimiter::new(500.0));
+    let mut oracle = Throttled::with_jitter(
+        MajorityVote::new(oracle, 1),
+        limiter,
+        Duration::from_millis(2),
+    );
+    let start = Instant::now();
+
+    for _ in 0..50 {
+        oracle.query(&[]).unwrap();
+    }
+
+    // 50 calls at 500 calls per second, minus the first one
+    assert!(start.elapsed() >= Duration::from_millis(98));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn it_is_consulted_by_async_attacks() {
+    use padding_oracle::PaddingOracleAttack;
+
+    let ciphertext = encrypt(b"YELLOW SUBMARINE");
+    let options = DecryptOptions {
+        rate_limiter: Some(Arc::new(RateLimiter::with_burst(20_000.0, 1))),
+        ..Default::default()
+    };
+
+    for concurrency in [None, Some(8)] {
+        let mut attack = PaddingOracleAttack::with_options(&ciphertext, 16, &options).unwrap();
+        let start = Instant::now();
+
+        // The queries dropped in flight may not have waited for their turn
+        let sent = std::cell::Cell::new(0);
+        let async_oracle = |probe: Vec<u8>| {
+            let sent = &sent;
+            async move {
+                sent.set(sent.get() + 1);
+                oracle(&probe)
+            }
+        };
+
+        let run = async {
+            match concurrency {
+                None => attack.run_async(async_oracle, || false).await,
+                Some(n) => attack.run_async_concurrent(async_oracle, n, || false).await,
+            }
+        };
+        futures::executor::block_on(run).unwrap();
+
+        // One query every 50µs, minus the first one
+        let queries = sent.get();
+        assert!(start.elapsed() >= Duration::from_micros(50) * (queries - 1));
+        assert_eq!(attack.finish().unwrap(), pad(b"YELLOW SUBMARINE"));
+    }
+}
+
+#[cfg(all(feature = "async", target_os = "linux"))]
+#[test]
+fn it_sleeps_on_a_single_timer_thread() {
+    use futures::stream::{FuturesUnordered, StreamExt};
+    use padding_oracle::AsyncPaddingOracle;
+
+    fn threads() -> usize {
+        let status = std::fs::read_to_string("/proc/self/status").unwrap();
+        let line = status.lines().find(|l| l.starts_with("Threads:")).unwrap();
+        line[8..].trim().parse().unwrap()
+    }
+
+    let limiter = Arc::new(RateLimiter::with_burst(1000.0, 1));
+    let mut oracle = Throttled::new(|probe: Vec<u8>| async move { oracle(&probe) }, limiter);
+    let before = threads();
+
+    // Every query waits for its slot at once
+    let mut queries: FuturesUnordered<_> = (0..200).map(|_| oracle.query(vec![0; 32])).collect();
+    let most = futures::executor::block_on(async {
+        let mut most = threads();
+        while queries.next().await.is_some() {
+            most = most.max(threads());
+        }
+        most
+    });
+
+    // Other tests run threads of their own meanwhile
+    assert!(most < before + 50);
+}
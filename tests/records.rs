@@ -0,0 +1,155 @@
+mod common;
+
+use common::{encrypt, encrypt_with_iv, oracle, pad};
+use padding_oracle::{DecryptOptions, Error, PaddingOracleAttack};
+
+const PLAINTEXTS: [&[u8]; 3] = [
+    b"000003Cooking MC's like a pound of bacon",
+    b"000004Burning 'em, if you ain't quick and nimble",
+    b"000005I go crazy when I hear a cymbal",
+];
+
+fn iv_for_message(n: u64) -> Vec<u8> {
+    (n as u128).to_be_bytes().to_vec()
+}
+
+#[test]
+fn it_can_decrypt_records() {
+    let records: Vec<_> = PLAINTEXTS.iter().map(|p| encrypt(p)).collect();
+    let records: Vec<&[u8]> = records.iter().map(Vec::as_slice).collect();
+
+    let plaintexts = padding_oracle::decrypt_records(&records, 16, oracle).unwrap();
+
+    for (plaintext, expected) in plaintexts.iter().zip(PLAINTEXTS) {
+        assert_eq!(plaintext, &pad(expected));
+    }
+}
+
+#[test]
+fn it_can_decrypt_records_with_derived_ivs() {
+    let records: Vec<_> = PLAINTEXTS
+        .iter()
+        .zip(0..)
+        .map(|(p, n)| encrypt_with_iv(p, &iv_for_message(n).try_into().unwrap()))
+        .collect();
+    let records: Vec<&[u8]> = records.iter().map(Vec::as_slice).collect();
+
+    // The oracle doesn't care about the IV, since the attack sends its own
+    let plaintexts =
+        padding_oracle::decrypt_records_with_derived_iv(&records, 16, oracle, iv_for_message)
+            .unwrap();
+
+    for (plaintext, expected) in plaintexts.iter().zip(PLAINTEXTS) {
+        assert_eq!(plaintext, &pad(expected));
+    }
+}
+
+#[test]
+fn it_rejects_derived_ivs_of_the_wrong_size() {
+    let record = encrypt_with_iv(PLAINTEXTS[0], &[0; 16]);
+
+    assert!(matches!(
+        padding_oracle::decrypt_records_with_derived_iv(&[&record], 16, oracle, |_| vec![0; 8]),
+        Err(Error::WrongIvSize {
+            blocksize: 16,
+            found: 8
+        })
+    ));
+}
+
+#[test]
+fn it_exploits_reused_ivs() {
+    const REUSED_IV: [u8; 16] = *b"not so random iv";
+
+    let records: Vec<_> = PLAINTEXTS
+        .iter()
+        .map(|p| encrypt_with_iv(p, &REUSED_IV))
+        .collect();
+    let records: Vec<&[u8]> = records.iter().map(Vec::as_slice).collect();
+
+    // Only the first block of the first message is known
+    let iv = padding_oracle::recover_iv(records[0], 16, &PLAINTEXTS[0][..16], oracle).unwrap();
+    assert_eq!(iv, REUSED_IV);
+
+    let plaintext = padding_oracle::decrypt_with_iv(&iv, records[1], 16, oracle).unwrap();
+    assert_eq!(plaintext, pad(PLAINTEXTS[1]));
+
+    let plaintexts =
+        padding_oracle::decrypt_records_with_derived_iv(&records, 16, oracle, |_| iv.clone())
+            .unwrap();
+
+    for (plaintext, expected) in plaintexts.iter().zip(PLAINTEXTS) {
+        assert_eq!(plaintext, &pad(expected));
+    }
+}
+
+#[test]
+fn it_takes_the_iv_separately() {
+    let iv = *b"sent out of band";
+    let ciphertext = encrypt_with_iv(PLAINTEXTS[0], &iv);
+
+    let plaintext = padding_oracle::decrypt_with_iv(&iv, &ciphertext, 16, oracle).unwrap();
+    assert_eq!(plaintext, pad(PLAINTEXTS[0]));
+
+    let mut attack =
+        PaddingOracleAttack::with_iv(&iv, &ciphertext, 16, &DecryptOptions::default()).unwrap();
+
+    while let Some(probe) = attack.next_probe() {
+        attack.submit(oracle(&probe));
+    }
+
+    assert_eq!(attack.finish().unwrap(), pad(PLAINTEXTS[0]));
+
+    assert!(matches!(
+        PaddingOracleAttack::with_iv(&iv[..8], &ciphertext, 16, &DecryptOptions::default()),
+        Err(Error::WrongIvSize {
+            blocksize: 16,
+            found: 8
+        })
+    ));
+}
+
+#[test]
+fn it_tries_candidate_ivs_on_the_first_block() {
+    let iv = iv_for_message(42);
+    let ciphertext = encrypt_with_iv(PLAINTEXTS[0], &iv.clone().try_into().unwrap());
+    let candidates: Vec<_> = (40..45).map(iv_for_message).collect();
+
+    let plaintexts =
+        padding_oracle::decrypt_first_block(&ciphertext, 16, &candidates, oracle).unwrap();
+
+    assert_eq!(plaintexts.len(), candidates.len());
+    assert_eq!(plaintexts[2], PLAINTEXTS[0][..16]);
+    assert!(plaintexts
+        .iter()
+        .enumerate()
+        .all(|(i, p)| i == 2 || p != &PLAINTEXTS[0][..16]));
+
+    assert!(matches!(
+        padding_oracle::decrypt_first_block(&ciphertext, 16, &[[0u8; 8]], oracle),
+        Err(Error::WrongIvSize {
+            blocksize: 16,
+            found: 8
+        })
+    ));
+}
+
+#[test]
+fn it_needs_a_whole_known_block_to_recover_the_iv() {
+    let ciphertext = encrypt_with_iv(PLAINTEXTS[0], &[0; 16]);
+
+    assert!(matches!(
+        padding_oracle::recover_iv(&ciphertext, 16, b"000003", oracle),
+        Err(Error::LengthMismatch {
+            expected: 16,
+            found: 6
+        })
+    ));
+    assert!(matches!(
+        padding_oracle::recover_iv(&ciphertext[..8], 16, &PLAINTEXTS[0][..16], oracle),
+        Err(Error::WrongSize {
+            blocksize: 16,
+            found: 8
+        })
+    ));
+}
@@ -0,0 +1,177 @@
+use padding_oracle::{rsa_oaep, rsa_pkcs1, testing::local_rsa_oracle};
+
+/// A 128-bit key, small enough for the tests to be fast
+const MODULUS: &str = "f1553edb50a321bd13c93e23995bcc45";
+const PUBLIC_EXPONENT: &str = "010001";
+const PRIVATE_EXPONENT: &str = "da0ba850256a4f16b24d77234bd50cfd";
+
+/// `hello`, PKCS#1 v1.5 encrypted
+const CIPHERTEXT: &str = "9cc73624dc021b592fa6cb25955a84dd";
+const ENCODED: &str = "0002c3f509cc90c248880068656c6c6f";
+
+fn hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// An oracle only checking that the message starts with `0x00 0x02`
+fn oracle() -> impl FnMut(&[u8]) -> bool {
+    local_rsa_oracle(&hex(MODULUS), &hex(PRIVATE_EXPONENT), |message| {
+        message.starts_with(&[0x00, 0x02])
+    })
+}
+
+#[test]
+fn it_decrypts_pkcs1_ciphertexts() {
+    let (message, stats) = rsa_pkcs1::decrypt_with_stats(
+        &hex(MODULUS),
+        &hex(PUBLIC_EXPONENT),
+        &hex(CIPHERTEXT),
+        oracle(),
+    )
+    .unwrap();
+
+    assert_eq!(message, hex(ENCODED));
+    assert_eq!(rsa_pkcs1::unpad(&message), Some(&b"hello"[..]));
+
+    // The ciphertext was already conforming
+    assert_eq!(stats.blinding_queries, 1);
+    assert!(stats.iterations > 0);
+}
+
+#[test]
+fn it_blinds_non_conforming_ciphertexts() {
+    let (message, stats) = rsa_pkcs1::decrypt_with_stats(
+        &hex(MODULUS),
+        &hex(PUBLIC_EXPONENT),
+        // Picked so that the first blinding works, to keep the test fast
+        &hex("167b07af80175967e9fbfac2bbf5cbda"),
+        oracle(),
+    )
+    .unwrap();
+
+    assert_eq!(message, hex("6d5e9dc9f78eb2a4d0d89c70fccc7267"));
+    assert_eq!(rsa_pkcs1::unpad(&message), None);
+    assert_eq!(stats.blinding_queries, 2);
+}
+
+#[test]
+fn it_checks_the_sizes() {
+    let short = rsa_pkcs1::decrypt(&hex(MODULUS), &hex(PUBLIC_EXPONENT), &[0; 15], oracle());
+    assert!(matches!(
+        short,
+        Err(padding_oracle::Error::LengthMismatch {
+            expected: 16,
+            found: 15
+        })
+    ));
+
+    let small = rsa_pkcs1::decrypt(&[0xff; 10], &hex(PUBLIC_EXPONENT), &[0; 10], oracle());
+    assert!(matches!(
+        small,
+        Err(padding_oracle::Error::TooShort { min: 11, found: 10 })
+    ));
+}
+
+/// An oracle checking that the first byte of the message is zero, as OAEP decoders leak
+fn oaep_oracle() -> impl FnMut(&[u8]) -> bool {
+    local_rsa_oracle(&hex(MODULUS), &hex(PRIVATE_EXPONENT), |message| {
+        message[0] == 0
+    })
+}
+
+#[test]
+fn it_decrypts_oaep_ciphertexts() {
+    let (message, stats) = rsa_oaep::decrypt_with_stats(
+        &hex(MODULUS),
+        &hex(PUBLIC_EXPONENT),
+        &hex(CIPHERTEXT),
+        oaep_oracle(),
+    )
+    .unwrap();
+
+    assert_eq!(message, hex(ENCODED));
+    assert_eq!(stats.blinding_queries, 1);
+
+    // About one query per bit of the modulus
+    assert!(stats.queries < 2 * 128, "{stats:?}");
+}
+
+#[test]
+fn it_blinds_oaep_ciphertexts() {
+    // Doesn't start with a zero byte
+    let ciphertext = b"not conforming!!";
+    let message = rsa_oaep::decrypt(
+        &hex(MODULUS),
+        &hex(PUBLIC_EXPONENT),
+        ciphertext,
+        oaep_oracle(),
+    )
+    .unwrap();
+
+    assert_eq!(message, hex("5e07671690bb8f7c9fb13bfb54b7f42a"));
+}
+
+#[test]
+fn it_detects_inconsistent_oaep_oracles() {
+    let error = rsa_oaep::decrypt(
+        &hex(MODULUS),
+        &hex(PUBLIC_EXPONENT),
+        &hex(CIPHERTEXT),
+        |_: &[u8]| true,
+    )
+    .unwrap_err();
+
+    assert!(matches!(error, padding_oracle::Error::InconsistentOracle));
+}
+
+/// Whether the attack on PKCS#1 v1.5 gives up after 1000 queries to `oracle`
+fn gives_up(oracle: impl FnMut(&[u8]) -> bool) -> bool {
+    let options = rsa_pkcs1::RsaOptions {
+        max_queries: Some(1000),
+    };
+    let error = rsa_pkcs1::decrypt_with_options(
+        &hex(MODULUS),
+        &hex(PUBLIC_EXPONENT),
+        &hex(CIPHERTEXT),
+        oracle,
+        &options,
+    );
+
+    matches!(
+        error,
+        Err(padding_oracle::Error::QueryBudgetExceeded { stats, .. }) if stats.queries == 1000
+    )
+}
+
+#[test]
+fn it_gives_up_on_pkcs1_oracles_that_never_accept() {
+    // Keeps blinding the ciphertext
+    assert!(gives_up(|_| false));
+
+    // Keeps searching the first conforming multiple
+    let mut first = true;
+    assert!(gives_up(|_| core::mem::take(&mut first)));
+}
+
+#[test]
+fn it_gives_up_on_oaep_oracles_that_never_accept() {
+    let options = rsa_oaep::RsaOptions {
+        max_queries: Some(1000),
+    };
+    let error = rsa_oaep::decrypt_with_options(
+        &hex(MODULUS),
+        &hex(PUBLIC_EXPONENT),
+        &hex(CIPHERTEXT),
+        |_: &[u8]| false,
+        &options,
+    )
+    .unwrap_err();
+
+    let padding_oracle::Error::QueryBudgetExceeded { stats, .. } = error else {
+        panic!("unexpected error: {error}");
+    };
+    assert_eq!(stats.queries, 1000);
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+use common::{encrypt, oracle, pad};
+use padding_oracle::socket::{ResponseFraming, SocketOracle};
+
+/// A server reading length-prefixed ciphertexts, and answering `ok\n` or `bad padding\n`. It
+/// closes the connection after `per_connection` requests. Returns its address.
+fn serve(per_connection: usize) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+
+            for _ in 0..per_connection {
+                let mut len = [0u8; 2];
+                if stream.read_exact(&mut len).is_err() {
+                    break;
+                }
+
+                let mut ciphertext = vec![0u8; u16::from_be_bytes(len) as usize];
+                stream.read_exact(&mut ciphertext).unwrap();
+
+                let response: &[u8] = if oracle(&ciphertext) {
+                    b"ok\n"
+                } else {
+                    b"bad padding\n"
+                };
+                stream.write_all(response).unwrap();
+            }
+        }
+    });
+
+    address
+}
+
+fn frame(probe: &[u8]) -> Vec<u8> {
+    [&(probe.len() as u16).to_be_bytes()[..], probe].concat()
+}
+
+#[test]
+fn it_decrypts_over_a_socket() {
+    let plaintext = b"000001With the bass kicked in";
+    let ciphertext = encrypt(plaintext);
+
+    // The server hangs up regularly
+    let oracle = SocketOracle::new(
+        serve(100),
+        frame,
+        ResponseFraming::Delimiter(b'\n'),
+        |response: &[u8]| response == b"ok\n",
+    );
+
+    let recovered = padding_oracle::decrypt(&ciphertext, 16, oracle).unwrap();
+    assert_eq!(recovered, pad(plaintext));
+}
+
+#[test]
+fn it_reconnects_for_every_query() {
+    let plaintext = b"YELLOW SUBMARINE";
+    let ciphertext = encrypt(plaintext);
+    let address = serve(1);
+
+    let mut connections = 0;
+    let oracle = SocketOracle::with_connector(
+        || {
+            connections += 1;
+            std::net::TcpStream::connect(&address)
+        },
+        frame,
+        ResponseFraming::UntilClose,
+        |response: &[u8]| response == b"ok\n",
+    );
+
+    let recovered = padding_oracle::decrypt(&ciphertext, 16, oracle).unwrap();
+    assert_eq!(recovered, pad(plaintext));
+    assert!(connections > 16);
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::{encrypt, oracle, pad};
+use padding_oracle::Error;
+
+#[test]
+fn it_flips_the_plaintext() {
+    let plaintext = b"000000user=guest;comment=whatever";
+    let ciphertext = encrypt(plaintext);
+
+    // Flipping the IV doesn't garble anything
+    let forged = padding_oracle::flip_plaintext(&ciphertext, 16, 11, b"guest", b"admin").unwrap();
+    let recovered = padding_oracle::decrypt(&forged, 16, oracle).unwrap();
+
+    assert_eq!(recovered, pad(b"000000user=admin;comment=whatever"));
+}
+
+#[test]
+fn it_rejects_invalid_flips() {
+    let ciphertext = encrypt(b"YELLOW SUBMARINE");
+
+    assert!(matches!(
+        padding_oracle::flip_plaintext(&ciphertext, 16, 0, b"YELLOW", b"RED"),
+        Err(Error::LengthMismatch {
+            expected: 6,
+            found: 3
+        })
+    ));
+    assert!(matches!(
+        padding_oracle::flip_plaintext(&ciphertext, 16, 31, b"ab", b"cd"),
+        Err(Error::InvalidRange {
+            start: 31,
+            end: 33,
+            len: 32
+        })
+    ));
+}
+
+#[test]
+fn it_rewrites_the_plaintext() {
+    let plaintext = pad(b"000000comment=whatever;user=guest;and=more");
+    let ciphertext = encrypt(b"000000comment=whatever;user=guest;and=more");
+
+    // The change is in the third block, so the second one gets garbled and must be re-forged
+    let desired = pad(b"000000comment=whatever;user=admin;and=more");
+    let forged =
+        padding_oracle::rewrite_plaintext(&ciphertext, 16, &plaintext, &desired, oracle).unwrap();
+
+    assert_eq!(
+        &forged[forged.len() - 16..],
+        &ciphertext[ciphertext.len() - 16..]
+    );
+    assert_eq!(
+        padding_oracle::decrypt(&forged, 16, oracle).unwrap(),
+        desired
+    );
+
+    assert!(matches!(
+        padding_oracle::rewrite_plaintext(&ciphertext, 16, &plaintext, b"short", oracle),
+        Err(Error::LengthMismatch {
+            expected: 48,
+            found: 5
+        })
+    ));
+}
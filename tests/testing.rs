@@ -0,0 +1,28 @@
+mod common;
+
+use aes::cipher::{BlockDecrypt, KeyInit};
+use common::{encrypt, pad, KEY};
+use padding_oracle::{testing::local_oracle, PaddingOracleAttack, PaddingScheme};
+
+#[test]
+fn it_builds_an_oracle_from_a_block_cipher() {
+    let cipher = aes::Aes128::new(&KEY.into());
+    let plaintext = b"000001With the bass kicked in and the Vega's are pumpin'";
+
+    let mut oracle = local_oracle(
+        |block: &[u8; 16]| {
+            let mut block = (*block).into();
+            cipher.decrypt_block(&mut block);
+            block.into()
+        },
+        PaddingScheme::Pkcs7,
+    );
+
+    let mut attack = PaddingOracleAttack::new(&encrypt(plaintext), 16).unwrap();
+
+    while let Some(probe) = attack.next_probe() {
+        attack.submit(oracle(&probe));
+    }
+
+    assert_eq!(attack.finish().unwrap(), pad(plaintext));
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{encrypt, oracle, pad};
+use padding_oracle::{DecryptOptions, OracleResult, PaddingOracle, TimingOptions, TimingOracle};
+
+/// Simulated response times, slower when the padding is valid, with up to 400µs of noise
+fn measure() -> impl FnMut(&[u8]) -> Duration {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+
+    move |probe| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let base = if oracle(probe) { 1300 } else { 1000 };
+        Duration::from_micros(base + state % 400)
+    }
+}
+
+#[test]
+fn it_decrypts_through_response_times() {
+    let plaintext = b"timing is everything";
+    let ciphertext = encrypt(plaintext);
+
+    let mut invalid = ciphertext.clone();
+    invalid[ciphertext.len() - 17] ^= 0x80;
+
+    let oracle =
+        TimingOracle::calibrate(measure(), &ciphertext, &invalid, &TimingOptions::default());
+    let options = DecryptOptions {
+        retries: 10,
+        ..Default::default()
+    };
+
+    let recovered =
+        padding_oracle::decrypt_with_options(&ciphertext, 16, oracle, &options).unwrap();
+    assert_eq!(recovered, pad(plaintext));
+}
+
+#[test]
+fn it_is_inconclusive_without_a_difference() {
+    let valid = vec![Duration::from_millis(1); 10];
+    let invalid = vec![Duration::from_millis(3); 10];
+
+    // Right in the middle
+    let mut oracle = TimingOracle::from_samples(
+        |_: &[u8]| Duration::from_millis(2),
+        &valid,
+        &invalid,
+        &TimingOptions::default(),
+    );
+    assert_eq!(oracle.query(&[]), OracleResult::Inconclusive);
+
+    let mut oracle = TimingOracle::from_samples(
+        |_: &[u8]| Duration::from_millis(3),
+        &valid,
+        &invalid,
+        &TimingOptions::default(),
+    );
+    assert_eq!(oracle.query(&[]), OracleResult::PaddingInvalid);
+}
@@ -0,0 +1,76 @@
+use padding_oracle::util::{compare_recoveries, diff_plaintexts};
+
+#[test]
+fn it_diffs_plaintexts_of_different_lengths() {
+    assert_eq!(diff_plaintexts(b"abc", b"abcde"), vec![3, 4]);
+    assert_eq!(diff_plaintexts(b"abcde", b"xbc"), vec![0, 3, 4]);
+    assert!(diff_plaintexts(b"", b"").is_empty());
+}
+
+#[test]
+fn it_finds_shared_blocks() {
+    let ciphertext_a = [[0u8; 4], [1; 4], [2; 4]].concat();
+    let ciphertext_b = [[9u8; 4], [3; 4], [4; 4]].concat();
+
+    let comparison = compare_recoveries(&ciphertext_a, b"AAAABBBB", &ciphertext_b, b"BBBBCCCC", 4);
+
+    assert_eq!(comparison.shared, vec![(1, 0)]);
+    assert!(comparison.inconsistent.is_empty());
+}
+
+#[test]
+fn it_flags_inconsistent_recoveries() {
+    // The second block of each ciphertext is the same, with different previous blocks
+    let ciphertext_a = [[0u8; 4], [1; 4], [7; 4]].concat();
+    let ciphertext_b = [[5u8; 4], [7; 4]].concat();
+
+    // Intermediate state of the [7; 4] block is "BBBB" ^ [1; 4]
+    let plaintext_b: Vec<u8> = b"BBBB".iter().map(|b| b ^ 1 ^ 5).collect();
+    let comparison = compare_recoveries(&ciphertext_a, b"AAAABBBB", &ciphertext_b, &plaintext_b, 4);
+    assert!(comparison.inconsistent.is_empty());
+
+    let comparison = compare_recoveries(&ciphertext_a, b"AAAABBBB", &ciphertext_b, b"BBBB", 4);
+    assert_eq!(comparison.inconsistent, vec![(1, 0)]);
+}
+
+#[test]
+fn it_compares_recoveries_of_mismatched_lengths() {
+    let ciphertext = [[0u8; 16], [1; 16]].concat();
+    let plaintext = [b'A'; 32];
+
+    let comparison = compare_recoveries(&ciphertext, &plaintext, &ciphertext, &plaintext, 16);
+    assert_eq!(comparison.shared, vec![(0, 0)]);
+    assert!(comparison.inconsistent.is_empty());
+
+    let comparison = compare_recoveries(&ciphertext[..8], &plaintext, &ciphertext, &plaintext, 16);
+    assert_eq!(comparison, Default::default());
+
+    let comparison = compare_recoveries(&ciphertext, &plaintext, &ciphertext, &plaintext, 0);
+    assert_eq!(comparison, Default::default());
+}
+
+#[test]
+fn it_measures_entropy() {
+    use padding_oracle::util::entropy;
+
+    assert_eq!(entropy(b""), 0.0);
+    assert_eq!(entropy(&(0..=255).collect::<Vec<u8>>()), 8.0);
+    assert!(entropy(b"Now that the party is jumping") < 5.0);
+}
+
+#[test]
+fn it_displays_partial_utf8() {
+    use padding_oracle::util::display_partial;
+
+    let text = "naïve café 🦀".as_bytes();
+
+    for i in 0..=text.len() {
+        // Never panics, whatever the cut
+        let _ = display_partial(&text[i..]);
+        let _ = display_partial(&text[..i]);
+    }
+
+    assert_eq!(display_partial(&text[..text.len() - 1]), "naïve café …");
+    assert_eq!(display_partial(&text[3..]), "…ve café 🦀");
+    assert_eq!(display_partial(b""), "");
+}
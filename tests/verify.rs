@@ -0,0 +1,97 @@
+mod common;
+
+use common::{encrypt, oracle, pad};
+use padding_oracle::{DecryptOptions, Error};
+
+const PLAINTEXT: &[u8] = b"000003Cooking MC's like a pound of bacon";
+
+#[test]
+fn it_accepts_a_sane_oracle() {
+    padding_oracle::verify_oracle(&encrypt(PLAINTEXT), 16, oracle).unwrap();
+}
+
+#[test]
+fn it_catches_setup_mistakes() {
+    let ciphertext = encrypt(PLAINTEXT);
+
+    assert!(matches!(
+        padding_oracle::verify_oracle(&ciphertext, 16, |_: &[u8]| true),
+        Err(Error::OracleAlwaysValid)
+    ));
+    assert!(matches!(
+        padding_oracle::verify_oracle(&ciphertext, 16, |_: &[u8]| false),
+        Err(Error::RejectedCiphertext)
+    ));
+    assert!(matches!(
+        padding_oracle::verify_oracle(&ciphertext, 8, oracle),
+        Err(Error::Misaligned { blocksize: 8 })
+    ));
+}
+
+#[test]
+fn it_prechecks_before_the_attack() {
+    let options = DecryptOptions {
+        precheck: true,
+        ..Default::default()
+    };
+
+    let plaintext = padding_oracle::decrypt_with_options(&encrypt(PLAINTEXT), 16, oracle, &options);
+    assert_eq!(plaintext.unwrap(), pad(PLAINTEXT));
+
+    assert!(matches!(
+        padding_oracle::decrypt_with_options(&encrypt(PLAINTEXT), 8, oracle, &options),
+        Err(Error::Misaligned { blocksize: 8 })
+    ));
+}
+
+#[test]
+fn it_prechecks_in_every_driver() {
+    use padding_oracle::PaddingOracleAttack;
+
+    let ciphertext = encrypt(PLAINTEXT);
+    let options = DecryptOptions {
+        precheck: true,
+        ..Default::default()
+    };
+    let attack = || PaddingOracleAttack::with_options(&ciphertext, 16, &options).unwrap();
+    let always_valid = |_: &[u8]| true;
+
+    let run = attack().run(always_valid, || false);
+    assert!(matches!(run, Err(Error::OracleAlwaysValid)));
+
+    let blocks: Vec<_> = attack().into_blocks(always_valid).collect();
+    assert!(matches!(blocks[..], [Err(Error::OracleAlwaysValid)]));
+
+    #[cfg(feature = "rayon")]
+    {
+        let parallel =
+            padding_oracle::decrypt_parallel_with_options(&ciphertext, 16, always_valid, &options);
+        assert!(matches!(parallel, Err(Error::OracleAlwaysValid)));
+
+        let speculative = padding_oracle::decrypt_speculative_with_options(
+            &ciphertext,
+            16,
+            always_valid,
+            &options,
+        );
+        assert!(matches!(speculative, Err(Error::OracleAlwaysValid)));
+    }
+
+    #[cfg(feature = "async")]
+    futures::executor::block_on(async {
+        use futures::StreamExt;
+
+        let always_valid = |_: Vec<u8>| async { true };
+
+        let run = attack().run_async(always_valid, || false).await;
+        assert!(matches!(run, Err(Error::OracleAlwaysValid)));
+
+        let run = attack()
+            .run_async_concurrent(always_valid, 8, || false)
+            .await;
+        assert!(matches!(run, Err(Error::OracleAlwaysValid)));
+
+        let blocks: Vec<_> = attack().into_stream(always_valid).collect().await;
+        assert!(matches!(blocks[..], [Err(Error::OracleAlwaysValid)]));
+    });
+}